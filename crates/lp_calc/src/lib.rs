@@ -0,0 +1,67 @@
+//! Evaluating the arithmetic typed into the launcher.
+//!
+//! Numbers stay exact rationals through `+ - * / ^`, so `0.1 + 0.2` is `0.3` and `2^100`
+//! has all its digits, and turn into floating point only where a function like `sqrt` or
+//! `sin` needs them to.
+//!
+//! ```text
+//! 2^10*3            3072
+//! 17% of 230        39.1
+//! sqrt(2) * pi      4.44288293815837
+//! 0xff + 0b1 in hex 0x100
+//! 10! / 3           1209600
+//! ```
+//!
+//! Quantities convert between units with [`convert`], `5 mi in km` is `8.04672 km`.
+
+use thiserror::Error;
+
+pub use number::{Base, Number};
+pub use units::{convert, Conversion, Kind};
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum Error {
+    #[error("{0}")]
+    Syntax(String),
+    #[error("division by zero")]
+    DivisionByZero,
+    #[error("{0} isn't defined there")]
+    Domain(&'static str),
+    #[error("the result is undefined")]
+    Undefined,
+    #[error("the result is too large")]
+    TooLarge,
+    #[error("only integers can be written in {0:?}")]
+    NotAnInteger(Base),
+    #[error("unknown unit '{0}'")]
+    UnknownUnit(String),
+    #[error("can't convert {0} to {1}")]
+    Incompatible(Kind, Kind),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The value of an expression, and how it was asked to be written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer {
+    pub value: Number,
+    pub base: Base,
+    /// Whether there was anything to work out, rather than a lone decimal number.
+    pub computed: bool,
+}
+
+impl Answer {
+    /// The value as it should be shown and copied.
+    pub fn text(&self) -> Result<String> {
+        self.value.format(self.base)
+    }
+}
+
+/// Evaluates `input`, an expression optionally followed by `in hex`, `to bin` and the like.
+pub fn evaluate(input: &str) -> Result<Answer> {
+    parse::Parser::new(input)?.answer()
+}
+
+mod number;
+mod parse;
+mod units;
@@ -0,0 +1,396 @@
+//! Numbers that stay exact as long as the operations allow.
+
+use std::fmt;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use crate::{Error, Result};
+
+/// Exponents up to which powers are computed exactly.
+const MAX_EXACT_EXPONENT: u32 = 100_000;
+/// Bits a number may grow to, past that it's too large to be of any use.
+const MAX_BITS: u64 = 1 << 20;
+/// Significant digits shown of a fraction that doesn't end as a decimal.
+const FRACTION_DIGITS: usize = 20;
+/// Fractional digits shown at most, of a decimal that does end but takes long to.
+const MAX_DIGITS: usize = 100;
+/// Significant digits shown of an approximate number, about what an `f64` holds.
+const SIGNIFICANT_DIGITS: i32 = 15;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    /// A rational number, from literals and the arithmetic on them.
+    Exact(BigRational),
+    /// The result of a function like `sqrt` or `sin`, or of a power with a fractional
+    /// exponent.
+    Approx(f64),
+}
+
+/// How an integer is written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Base {
+    Binary,
+    Octal,
+    #[default]
+    Decimal,
+    Hexadecimal,
+}
+
+impl Base {
+    pub fn radix(self) -> u32 {
+        match self {
+            Base::Binary => 2,
+            Base::Octal => 8,
+            Base::Decimal => 10,
+            Base::Hexadecimal => 16,
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Base::Binary => "0b",
+            Base::Octal => "0o",
+            Base::Decimal => "",
+            Base::Hexadecimal => "0x",
+        }
+    }
+}
+
+impl Number {
+    pub fn integer(value: impl Into<BigInt>) -> Self {
+        Number::Exact(BigRational::from_integer(value.into()))
+    }
+
+    /// `numerator / 10^scale`, for decimal literals.
+    pub fn decimal(numerator: BigInt, scale: i32) -> Self {
+        let power = BigInt::from(10).pow(scale.unsigned_abs());
+
+        Number::Exact(if scale >= 0 {
+            BigRational::new(numerator, power)
+        } else {
+            BigRational::from_integer(numerator * power)
+        })
+    }
+
+    /// A result of floating point math, which has to be a number.
+    pub fn approx(value: f64) -> Result<Self> {
+        if value.is_finite() {
+            Ok(Number::Approx(value))
+        } else if value.is_nan() {
+            Err(Error::Undefined)
+        } else {
+            Err(Error::TooLarge)
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Exact(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Approx(value) => *value,
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Number::Exact(_))
+    }
+
+    /// The value as an integer, if it is one.
+    pub fn to_integer(&self) -> Option<BigInt> {
+        match self {
+            Number::Exact(value) if value.is_integer() => Some(value.to_integer()),
+            Number::Approx(value) if value.fract() == 0. && value.abs() < 2f64.powi(53) => Some(BigInt::from(*value as i64)),
+            _ => None,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Exact(value) => value.is_zero(),
+            Number::Approx(value) => *value == 0.,
+        }
+    }
+
+    pub fn negate(self) -> Self {
+        match self {
+            Number::Exact(value) => Number::Exact(-value),
+            Number::Approx(value) => Number::Approx(-value),
+        }
+    }
+
+    pub fn checked_add(self, other: Self) -> Result<Self> {
+        self.combine(other, |a, b| Ok(a + b), |a, b| a + b)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Result<Self> {
+        self.combine(other, |a, b| Ok(a - b), |a, b| a - b)
+    }
+
+    pub fn checked_mul(self, other: Self) -> Result<Self> {
+        self.combine(other, |a, b| Ok(a * b), |a, b| a * b)
+    }
+
+    pub fn checked_div(self, other: Self) -> Result<Self> {
+        if other.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+
+        self.combine(other, |a, b| Ok(a / b), |a, b| a / b)
+    }
+
+    /// The remainder of a division rounding down, which has the sign of the divisor.
+    pub fn checked_rem(self, other: Self) -> Result<Self> {
+        if other.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+
+        self.combine(
+            other,
+            |a, b| Ok(&a - &b * (&a / &b).floor()),
+            |a, b| a - b * (a / b).floor(),
+        )
+    }
+
+    pub fn pow(self, exponent: Self) -> Result<Self> {
+        if let (Number::Exact(base), Some(power)) = (&self, exponent.to_integer().filter(|_| exponent.is_exact())) {
+            if let Some(result) = exact_pow(base, &power)? {
+                return Ok(Number::Exact(result));
+            }
+        }
+
+        Number::approx(self.to_f64().powf(exponent.to_f64()))
+    }
+
+    /// `n!` of a whole number.
+    pub fn factorial(self) -> Result<Self> {
+        let n = self
+            .to_integer()
+            .filter(|n| !n.is_negative())
+            .ok_or(Error::Domain("factorial"))?
+            .to_u32()
+            .filter(|n| *n <= 20_000)
+            .ok_or(Error::TooLarge)?;
+
+        Ok(Number::integer((1..=n).fold(BigInt::one(), |product, i| product * i)))
+    }
+
+    /// Applies a function of real numbers, exact where `exact` knows how.
+    pub fn map(self, exact: fn(&BigRational) -> Option<BigRational>, approx: fn(f64) -> f64) -> Result<Self> {
+        match &self {
+            Number::Exact(value) => match exact(value) {
+                Some(result) => Ok(Number::Exact(result)),
+                None => Number::approx(approx(self.to_f64())),
+            },
+            Number::Approx(value) => Number::approx(approx(*value)),
+        }
+    }
+
+    fn combine(
+        self,
+        other: Self,
+        exact: impl FnOnce(BigRational, BigRational) -> Result<BigRational>,
+        approx: impl FnOnce(f64, f64) -> f64,
+    ) -> Result<Self> {
+        match (self, other) {
+            (Number::Exact(a), Number::Exact(b)) => {
+                let result = exact(a, b)?;
+                check_size(&result)?;
+                Ok(Number::Exact(result))
+            }
+            (a, b) => Number::approx(approx(a.to_f64(), b.to_f64())),
+        }
+    }
+
+    /// The number rounded to `digits` significant digits, for values that carry no more
+    /// precision than that.
+    pub fn rounded(&self, digits: u32) -> Self {
+        match self {
+            Number::Exact(value) if !value.is_zero() => {
+                let magnitude = magnitude(value);
+                let scale = digits as i64 - 1 - magnitude;
+                let power = BigRational::from_integer(BigInt::from(10).pow(scale.unsigned_abs() as u32));
+
+                Number::Exact(if scale >= 0 {
+                    (value * &power).round() / power
+                } else {
+                    (value / &power).round() * power
+                })
+            }
+            _ => self.clone(),
+        }
+    }
+
+    /// Writes the number out in `base`, which only integers can be in other than decimal.
+    pub fn format(&self, base: Base) -> Result<String> {
+        if base != Base::Decimal {
+            let integer = self.to_integer().ok_or(Error::NotAnInteger(base))?;
+            let sign = if integer.is_negative() { "-" } else { "" };
+            return Ok(format!("{sign}{}{}", base.prefix(), integer.abs().to_str_radix(base.radix())));
+        }
+
+        Ok(match self {
+            Number::Exact(value) => format_exact(value),
+            Number::Approx(value) => format_approx(*value),
+        })
+    }
+
+    /// The value as a fraction in lowest terms, when it's an exact one that doesn't end
+    /// as a decimal.
+    pub fn fraction(&self) -> Option<String> {
+        match self {
+            Number::Exact(value) if !terminates(value.denom()) => Some(format!("{}/{}", value.numer(), value.denom())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Exact(value) => f.write_str(&format_exact(value)),
+            Number::Approx(value) => f.write_str(&format_approx(*value)),
+        }
+    }
+}
+
+/// `base^power`, `None` when it's better computed approximately.
+fn exact_pow(base: &BigRational, power: &BigInt) -> Result<Option<BigRational>> {
+    let Some(exponent) = power.abs().to_u32().filter(|e| *e <= MAX_EXACT_EXPONENT) else {
+        return Ok(None);
+    };
+
+    if base.is_zero() && power.is_negative() {
+        return Err(Error::DivisionByZero);
+    }
+
+    // the size of the result is known up front, better not to compute what's too large
+    let bits = base.numer().bits().max(base.denom().bits());
+    if bits.saturating_mul(exponent as u64) > MAX_BITS {
+        return if base.abs() > BigRational::one() && power.is_positive() { Err(Error::TooLarge) } else { Ok(None) };
+    }
+
+    let result = num_traits::pow(base.clone(), exponent as usize);
+    Ok(Some(if power.is_negative() { result.recip() } else { result }))
+}
+
+/// `floor(log10(|value|))` of a non-zero value, also of ones far outside the `f64` range.
+fn magnitude(value: &BigRational) -> i64 {
+    let digits = |n: &BigInt| n.magnitude().to_string().len() as i64;
+    let (numer, denom) = (value.numer().abs(), value.denom().clone());
+
+    // a quotient of an `a` and a `b` digit number is below 10^(a-b+1) and at least 10^(a-b-1)
+    let estimate = digits(&numer) - digits(&denom);
+    let power = BigInt::from(10).pow(estimate.unsigned_abs() as u32);
+    let at_least = if estimate >= 0 { numer >= denom * power } else { numer * power >= denom };
+
+    if at_least {
+        estimate
+    } else {
+        estimate - 1
+    }
+}
+
+fn check_size(value: &BigRational) -> Result<()> {
+    if value.numer().bits() > MAX_BITS || value.denom().bits() > MAX_BITS {
+        Err(Error::TooLarge)
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether `1 / denominator` has a finite decimal expansion.
+fn terminates(denominator: &BigInt) -> bool {
+    let mut rest = denominator.clone();
+
+    for factor in [2, 5] {
+        let factor = BigInt::from(factor);
+
+        while rest.is_multiple_of(&factor) {
+            rest /= &factor;
+        }
+    }
+
+    rest.is_one()
+}
+
+/// All the digits of a decimal that ends, the first [`FRACTION_DIGITS`] significant ones
+/// of one that doesn't. Values too small to show a digit in [`MAX_DIGITS`] are written
+/// in scientific notation.
+fn format_exact(value: &BigRational) -> String {
+    if value.is_integer() {
+        return value.to_integer().to_string();
+    }
+
+    let sign = if value.is_negative() { "-" } else { "" };
+    let value = value.abs();
+    let whole = value.trunc().to_integer();
+
+    let magnitude = magnitude(&value);
+    if magnitude < -(MAX_DIGITS as i64) {
+        let shift = BigRational::from_integer(BigInt::from(10).pow(magnitude.unsigned_abs() as u32));
+        return format!("{sign}{}e{magnitude}", format_exact(&(value * shift)));
+    }
+    let terminating = terminates(value.denom());
+    let ten = BigRational::from_integer(BigInt::from(10));
+
+    let mut rest = value.fract();
+    let mut digits = String::new();
+    let mut significant = if whole.is_zero() { 0 } else { whole.to_string().len() };
+
+    while !rest.is_zero() && digits.len() < MAX_DIGITS {
+        if !terminating && significant >= FRACTION_DIGITS {
+            break;
+        }
+
+        rest *= &ten;
+        let digit = rest.trunc().to_integer();
+        rest = rest.fract();
+
+        if significant > 0 || !digit.is_zero() {
+            significant += 1;
+        }
+
+        digits.push_str(&digit.to_string());
+    }
+
+    let digits = digits.trim_end_matches('0');
+
+    if digits.is_empty() {
+        format!("{sign}{whole}")
+    } else {
+        format!("{sign}{whole}.{digits}")
+    }
+}
+
+/// The significant digits of an `f64`, without the noise past them.
+fn format_approx(value: f64) -> String {
+    if value == 0. {
+        return "0".to_owned();
+    }
+
+    let magnitude = value.abs().log10().floor() as i32;
+
+    if !(-6..16).contains(&magnitude) {
+        let text = format!("{:.*e}", (SIGNIFICANT_DIGITS - 1) as usize, value);
+        let (mantissa, exponent) = text.split_once('e').unwrap_or((&text, "0"));
+        let mantissa = trim_fraction(mantissa);
+        return format!("{mantissa}e{exponent}");
+    }
+
+    let decimals = (SIGNIFICANT_DIGITS - 1 - magnitude).max(0) as usize;
+    let text = trim_fraction(&format!("{value:.decimals$}")).to_owned();
+
+    if text == "-0" {
+        "0".to_owned()
+    } else {
+        text
+    }
+}
+
+fn trim_fraction(text: &str) -> &str {
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        text
+    }
+}
@@ -0,0 +1,493 @@
+//! Reading and evaluating expressions, in one go.
+//!
+//! ```text
+//! answer  := sum [("in" | "to" | "as") base]
+//! sum     := product (("+" | "-") product)*
+//! product := unary (("*" | "/" | "mod" | "%") unary | unary)*
+//! unary   := ("-" | "+") unary | power
+//! power   := postfix [("^" | "**") unary]
+//! postfix := primary ("!" | "%" ["of" unary])*
+//! primary := number | constant | function ("(" sum ("," sum)* ")" | power) | "(" sum ")"
+//! ```
+//!
+//! A product without an operator between, like `2pi` or `3(4 + 5)`, multiplies.
+
+use std::cmp::Ordering;
+use std::f64::consts;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Num, Signed};
+use crate::{Answer, Base, Error, Number, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A literal, and whether it was written in a base other than decimal.
+    Number(Number, bool),
+    Name(String),
+    Symbol(char),
+    End,
+}
+
+pub struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// Whether anything was worked out, rather than a number or constant merely read.
+    computed: bool,
+}
+
+impl Parser {
+    pub fn new(input: &str) -> Result<Self> {
+        Ok(Self { tokens: tokenize(input)?, position: 0, computed: false })
+    }
+
+    pub fn answer(mut self) -> Result<Answer> {
+        let value = self.sum()?;
+        let mut base = Base::Decimal;
+
+        if self.keyword(&["in", "to", "as"]) {
+            base = match self.next() {
+                Token::Name(name) => parse_base(&name).ok_or_else(|| syntax(format!("unknown base '{name}'")))?,
+                token => return Err(unexpected(&token)),
+            };
+            self.computed = true;
+        }
+
+        match self.next() {
+            Token::End => Ok(Answer { value, base, computed: self.computed }),
+            token => Err(unexpected(&token)),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Number> {
+        let mut value = self.product()?;
+
+        loop {
+            value = match self.peek() {
+                Token::Symbol('+') => self.binary(value, Self::product, Number::checked_add)?,
+                Token::Symbol('-') => self.binary(value, Self::product, Number::checked_sub)?,
+                _ => return Ok(value),
+            };
+        }
+    }
+
+    fn product(&mut self) -> Result<Number> {
+        let mut value = self.unary()?;
+
+        loop {
+            value = match self.peek() {
+                Token::Symbol('*') => self.binary(value, Self::unary, Number::checked_mul)?,
+                Token::Symbol('/') => self.binary(value, Self::unary, Number::checked_div)?,
+                Token::Symbol('%') if self.starts_operand(1) => self.binary(value, Self::unary, Number::checked_rem)?,
+                Token::Name(name) if name == "mod" => self.binary(value, Self::unary, Number::checked_rem)?,
+                _ if self.starts_operand(0) => {
+                    self.computed = true;
+                    value.checked_mul(self.unary()?)?
+                }
+                _ => return Ok(value),
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Result<Number> {
+        match self.peek() {
+            Token::Symbol('-') => {
+                self.position += 1;
+                Ok(self.unary()?.negate())
+            }
+            Token::Symbol('+') => {
+                self.position += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Number> {
+        let base = self.postfix()?;
+
+        match self.peek() {
+            Token::Symbol('^') => self.binary(base, Self::unary, Number::pow),
+            _ => Ok(base),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Number> {
+        let mut value = self.primary()?;
+
+        loop {
+            value = match self.peek() {
+                Token::Symbol('!') => {
+                    self.position += 1;
+                    value.factorial()?
+                }
+                // followed by an operand it's the remainder, which a product takes care of
+                Token::Symbol('%') if !self.starts_operand(1) => {
+                    self.position += 1;
+                    let fraction = value.checked_div(Number::integer(100))?;
+
+                    if self.keyword(&["of"]) {
+                        fraction.checked_mul(self.unary()?)?
+                    } else {
+                        fraction
+                    }
+                }
+                _ => return Ok(value),
+            };
+            self.computed = true;
+        }
+    }
+
+    fn primary(&mut self) -> Result<Number> {
+        match self.next() {
+            Token::Number(value, radix) => {
+                self.computed |= radix;
+                Ok(value)
+            }
+            Token::Symbol('(') => {
+                let value = self.sum()?;
+                self.expect(')')?;
+                Ok(value)
+            }
+            Token::Name(name) => {
+                if let Some(value) = constant(&name) {
+                    return value;
+                }
+
+                let function = function(&name).ok_or_else(|| syntax(format!("unknown name '{name}'")))?;
+                let arguments = self.arguments()?;
+                self.computed = true;
+
+                call(&name, function, arguments)
+            }
+            token => Err(unexpected(&token)),
+        }
+    }
+
+    /// Arguments of a function: a list in parentheses, or a single power like `sqrt 2`.
+    fn arguments(&mut self) -> Result<Vec<Number>> {
+        if self.peek() != &Token::Symbol('(') {
+            return Ok(vec![self.power()?]);
+        }
+
+        self.position += 1;
+        let mut arguments = vec![self.sum()?];
+
+        while self.peek() == &Token::Symbol(',') {
+            self.position += 1;
+            arguments.push(self.sum()?);
+        }
+
+        self.expect(')')?;
+        Ok(arguments)
+    }
+
+    /// Applies the operator at the current token to `left` and the `operand` after it.
+    fn binary(
+        &mut self,
+        left: Number,
+        operand: fn(&mut Self) -> Result<Number>,
+        operator: fn(Number, Number) -> Result<Number>,
+    ) -> Result<Number> {
+        self.position += 1;
+        self.computed = true;
+        operator(left, operand(self)?)
+    }
+
+    /// Whether the token `offset` tokens ahead can start an operand.
+    fn starts_operand(&self, offset: usize) -> bool {
+        match self.tokens.get(self.position + offset) {
+            Some(Token::Number(..) | Token::Symbol('(')) => true,
+            Some(Token::Name(name)) => !matches!(name.as_str(), "in" | "to" | "as" | "of" | "mod"),
+            _ => false,
+        }
+    }
+
+    /// Takes the current token if it's one of the `words`.
+    fn keyword(&mut self, words: &[&str]) -> bool {
+        let found = matches!(self.peek(), Token::Name(name) if words.contains(&name.as_str()));
+
+        if found {
+            self.position += 1;
+        }
+
+        found
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<()> {
+        match self.next() {
+            Token::Symbol(found) if found == symbol => Ok(()),
+            Token::End => Err(syntax(format!("missing '{symbol}'"))),
+            token => Err(unexpected(&token)),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        self.tokens.get(self.position).unwrap_or(&Token::End)
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.peek().clone();
+        self.position += 1;
+        token
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let end = number_end(input, start);
+            tokens.push(number(&input[start..end])?);
+
+            while chars.peek().is_some_and(|(i, _)| *i < end) {
+                chars.next();
+            }
+        } else if c.is_alphabetic() {
+            let mut name = String::new();
+
+            while let Some(&(_, c)) = chars.peek().filter(|(_, c)| c.is_alphanumeric() || *c == '_') {
+                name.push(c.to_ascii_lowercase());
+                chars.next();
+            }
+
+            tokens.push(Token::Name(name));
+        } else {
+            chars.next();
+
+            let symbol = match c {
+                '*' if chars.peek().is_some_and(|(_, c)| *c == '*') => {
+                    chars.next();
+                    '^'
+                }
+                '×' | '·' => '*',
+                '÷' => '/',
+                '−' => '-',
+                '+' | '-' | '*' | '/' | '^' | '!' | '%' | '(' | ')' | ',' => c,
+                _ => return Err(syntax(format!("unexpected '{c}'"))),
+            };
+
+            tokens.push(Token::Symbol(symbol));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// The number `text` starts with, and the rest of it.
+pub(crate) fn leading_number(text: &str) -> Option<(Number, &str)> {
+    if !text.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+
+    let end = number_end(text, 0);
+
+    match number(&text[..end]) {
+        Ok(Token::Number(value, _)) => Some((value, &text[end..])),
+        _ => None,
+    }
+}
+
+/// Where the number starting at `start` ends, a prefixed integer or a decimal with an
+/// optional exponent.
+fn number_end(input: &str, start: usize) -> usize {
+    let bytes = input.as_bytes();
+    let digits_from = |from: usize, radix: u32| {
+        from + bytes[from..].iter().take_while(|b| (**b as char).is_digit(radix) || **b == b'_').count()
+    };
+
+    if bytes[start] == b'0' && matches!(bytes.get(start + 1), Some(b'x' | b'b' | b'o')) {
+        return digits_from(start + 2, 16);
+    }
+
+    let mut end = digits_from(start, 10);
+
+    if bytes.get(end) == Some(&b'.') {
+        end = digits_from(end + 1, 10);
+    }
+
+    // an `e` not followed by digits is the constant
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(bytes.get(end + 1), Some(b'+' | b'-')));
+
+        if bytes.get(end + 1 + sign).is_some_and(u8::is_ascii_digit) {
+            end = digits_from(end + 1 + sign, 10);
+        }
+    }
+
+    end
+}
+
+fn number(text: &str) -> Result<Token> {
+    let invalid = || syntax(format!("invalid number '{text}'"));
+    let text_digits = text.replace('_', "");
+
+    for (prefix, radix) in [("0x", 16), ("0b", 2), ("0o", 8)] {
+        if let Some(digits) = text_digits.strip_prefix(prefix) {
+            let value = BigInt::from_str_radix(digits, radix).map_err(|_| invalid())?;
+            return Ok(Token::Number(Number::integer(value), true));
+        }
+    }
+
+    let (mantissa, exponent) = match text_digits.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().map_err(|_| invalid())?),
+        None => (text_digits.as_str(), 0),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+
+    let numerator = format!("{whole}{fraction}").parse::<BigInt>().map_err(|_| invalid())?;
+    let scale = (fraction.len() as i32).checked_sub(exponent).ok_or(Error::TooLarge)?;
+
+    if scale.unsigned_abs() > 100_000 {
+        return Err(Error::TooLarge);
+    }
+
+    Ok(Token::Number(Number::decimal(numerator, scale), false))
+}
+
+fn parse_base(name: &str) -> Option<Base> {
+    Some(match name {
+        "bin" | "binary" => Base::Binary,
+        "oct" | "octal" => Base::Octal,
+        "dec" | "decimal" => Base::Decimal,
+        "hex" | "hexadecimal" => Base::Hexadecimal,
+        _ => return None,
+    })
+}
+
+fn constant(name: &str) -> Option<Result<Number>> {
+    let value = match name {
+        "pi" | "π" => consts::PI,
+        "tau" | "τ" => consts::TAU,
+        "e" => consts::E,
+        "phi" | "φ" => (1. + 5f64.sqrt()) / 2.,
+        _ => return None,
+    };
+
+    Some(Number::approx(value))
+}
+
+#[derive(Clone, Copy)]
+enum Function {
+    /// Of one number, exactly where the first function knows how.
+    Unary(fn(&BigRational) -> Option<BigRational>, fn(f64) -> f64),
+    /// `log`, of base 10 or the second argument.
+    Log,
+    Min,
+    Max,
+}
+
+fn function(name: &str) -> Option<Function> {
+    let inexact = |_: &BigRational| None;
+
+    Some(match name {
+        "sqrt" => Function::Unary(|x| root(x, 2), f64::sqrt),
+        "cbrt" => Function::Unary(|x| root(x, 3), f64::cbrt),
+        "abs" => Function::Unary(|x| Some(x.abs()), f64::abs),
+        "floor" => Function::Unary(|x| Some(x.floor()), f64::floor),
+        "ceil" => Function::Unary(|x| Some(x.ceil()), f64::ceil),
+        "round" => Function::Unary(|x| Some(x.round()), f64::round),
+        "trunc" => Function::Unary(|x| Some(x.trunc()), f64::trunc),
+        "sin" => Function::Unary(inexact, f64::sin),
+        "cos" => Function::Unary(inexact, f64::cos),
+        "tan" => Function::Unary(inexact, f64::tan),
+        "asin" => Function::Unary(inexact, f64::asin),
+        "acos" => Function::Unary(inexact, f64::acos),
+        "atan" => Function::Unary(inexact, f64::atan),
+        "sinh" => Function::Unary(inexact, f64::sinh),
+        "cosh" => Function::Unary(inexact, f64::cosh),
+        "tanh" => Function::Unary(inexact, f64::tanh),
+        "exp" => Function::Unary(inexact, f64::exp),
+        "ln" => Function::Unary(inexact, f64::ln),
+        "log2" => Function::Unary(inexact, f64::log2),
+        "log10" => Function::Unary(inexact, f64::log10),
+        "log" => Function::Log,
+        "min" => Function::Min,
+        "max" => Function::Max,
+        _ => return None,
+    })
+}
+
+fn call(name: &str, function: Function, arguments: Vec<Number>) -> Result<Number> {
+    let arity = |expected: &[usize]| {
+        if expected.contains(&arguments.len()) {
+            Ok(())
+        } else {
+            Err(syntax(format!("{name} takes {} arguments, not {}", expected[0], arguments.len())))
+        }
+    };
+
+    match function {
+        Function::Unary(exact, approx) => {
+            arity(&[1])?;
+            let argument = arguments.into_iter().next().expect("one argument");
+
+            argument.map(exact, approx).map_err(|e| match e {
+                Error::Undefined => Error::Domain(domain(name)),
+                e => e,
+            })
+        }
+        Function::Log => {
+            arity(&[1, 2])?;
+            let value = arguments[0].to_f64();
+            let base = arguments.get(1).map_or(10., Number::to_f64);
+
+            Number::approx(value.log(base)).map_err(|_| Error::Domain("log"))
+        }
+        Function::Min | Function::Max => {
+            if arguments.is_empty() {
+                return Err(syntax(format!("{name} takes at least 1 argument")));
+            }
+
+            let wanted = if matches!(function, Function::Min) { Ordering::Less } else { Ordering::Greater };
+
+            Ok(arguments.into_iter().reduce(|a, b| if compare(&b, &a) == wanted { b } else { a }).expect("an argument"))
+        }
+    }
+}
+
+/// The `&'static` name of a function, for [`Error::Domain`].
+fn domain(name: &str) -> &'static str {
+    ["sqrt", "asin", "acos", "acosh", "ln", "log2", "log10"]
+        .into_iter()
+        .find(|known| *known == name)
+        .unwrap_or("the function")
+}
+
+fn compare(a: &Number, b: &Number) -> Ordering {
+    match (a, b) {
+        (Number::Exact(a), Number::Exact(b)) => a.cmp(b),
+        _ => a.to_f64().partial_cmp(&b.to_f64()).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// The `n`th root of a rational whose numerator and denominator are perfect powers.
+fn root(value: &BigRational, n: u32) -> Option<BigRational> {
+    if value.is_negative() && n.is_multiple_of(2) {
+        return None;
+    }
+
+    let exact = |x: &BigInt| Some(x.nth_root(n)).filter(|root| root.pow(n) == *x);
+
+    Some(BigRational::new(exact(value.numer())?, exact(value.denom())?))
+}
+
+fn syntax(message: String) -> Error {
+    Error::Syntax(message)
+}
+
+fn unexpected(token: &Token) -> Error {
+    syntax(match token {
+        Token::Number(value, _) => format!("unexpected number {value}"),
+        Token::Name(name) => format!("unexpected '{name}'"),
+        Token::Symbol(symbol) => format!("unexpected '{symbol}'"),
+        Token::End => "unexpected end".to_owned(),
+    })
+}
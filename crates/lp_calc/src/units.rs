@@ -0,0 +1,347 @@
+//! Converting quantities between units, like `5 mi in km` or `72F to C`.
+//!
+//! A unit is a multiple of the base unit of its kind, temperatures also an offset from it.
+//! Units like the metre or the byte take SI prefixes, `km` or `MB`, and data units binary
+//! ones too, `GiB`. Symbols are case sensitive, but when a unit is known only ignoring case,
+//! like `gb`, that's what it is. Speeds are a length over a time, `km/h` or `miles per hour`.
+
+use std::fmt;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::One;
+use crate::parse::leading_number;
+use crate::{Error, Number, Result};
+
+/// Significant digits of a converted value, past which the factors aren't that precise.
+const SIGNIFICANT_DIGITS: u32 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Length,
+    Mass,
+    Temperature,
+    Volume,
+    Data,
+    Time,
+    Speed,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Kind::Length => "length",
+            Kind::Mass => "mass",
+            Kind::Temperature => "temperature",
+            Kind::Volume => "volume",
+            Kind::Data => "data size",
+            Kind::Time => "time",
+            Kind::Speed => "speed",
+        })
+    }
+}
+
+/// Which prefixes a unit takes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Prefixes {
+    None,
+    /// All the SI ones.
+    Si,
+    /// SI ones from kilo up, and binary ones, for data where a fraction of a bit is no use.
+    Data,
+}
+
+struct Unit {
+    /// Symbols, the first one is how the unit is written out.
+    symbols: &'static [&'static str],
+    /// Names, in lowercase and singular.
+    names: &'static [&'static str],
+    kind: Kind,
+    /// Size in the base unit of the kind, as a decimal or a fraction.
+    factor: &'static str,
+    /// Added before the factor applies, for temperatures.
+    offset: &'static str,
+    prefixes: Prefixes,
+}
+
+const fn unit(
+    symbols: &'static [&'static str],
+    names: &'static [&'static str],
+    kind: Kind,
+    factor: &'static str,
+    prefixes: Prefixes,
+) -> Unit {
+    Unit { symbols, names, kind, factor, offset: "0", prefixes }
+}
+
+/// Every unit, in the order they are preferred when one is known only ignoring case.
+const UNITS: &[Unit] = &[
+    unit(&["m"], &["meter", "metre"], Kind::Length, "1", Prefixes::Si),
+    unit(&["in", "\""], &["inch"], Kind::Length, "0.0254", Prefixes::None),
+    unit(&["ft", "'"], &["foot", "feet"], Kind::Length, "0.3048", Prefixes::None),
+    unit(&["yd"], &["yard"], Kind::Length, "0.9144", Prefixes::None),
+    unit(&["mi"], &["mile"], Kind::Length, "1609.344", Prefixes::None),
+    unit(&["nmi"], &["nautical mile"], Kind::Length, "1852", Prefixes::None),
+    unit(&["au"], &["astronomical unit"], Kind::Length, "149597870700", Prefixes::None),
+    unit(&["ly"], &["light year", "lightyear"], Kind::Length, "9460730472580800", Prefixes::None),
+    unit(&["g"], &["gram", "gramme"], Kind::Mass, "1", Prefixes::Si),
+    unit(&["t"], &["tonne", "metric ton"], Kind::Mass, "1000000", Prefixes::None),
+    unit(&["lb", "lbs"], &["pound"], Kind::Mass, "453.59237", Prefixes::None),
+    unit(&["oz"], &["ounce"], Kind::Mass, "28.349523125", Prefixes::None),
+    unit(&["st"], &["stone"], Kind::Mass, "6350.29318", Prefixes::None),
+    unit(&["K"], &["kelvin"], Kind::Temperature, "1", Prefixes::None),
+    Unit {
+        symbols: &["C", "°C"],
+        names: &["celsius"],
+        kind: Kind::Temperature,
+        factor: "1",
+        offset: "273.15",
+        prefixes: Prefixes::None,
+    },
+    Unit {
+        symbols: &["F", "°F"],
+        names: &["fahrenheit"],
+        kind: Kind::Temperature,
+        factor: "5/9",
+        offset: "459.67",
+        prefixes: Prefixes::None,
+    },
+    unit(&["L", "l"], &["liter", "litre"], Kind::Volume, "1", Prefixes::Si),
+    unit(&["m³", "m3"], &["cubic meter", "cubic metre"], Kind::Volume, "1000", Prefixes::None),
+    unit(&["cm³", "cm3", "cc"], &["cubic centimeter", "cubic centimetre"], Kind::Volume, "0.001", Prefixes::None),
+    unit(&["gal"], &["gallon"], Kind::Volume, "3.785411784", Prefixes::None),
+    unit(&["qt"], &["quart"], Kind::Volume, "0.946352946", Prefixes::None),
+    unit(&["pt"], &["pint"], Kind::Volume, "0.473176473", Prefixes::None),
+    unit(&["cup"], &["cup"], Kind::Volume, "0.2365882365", Prefixes::None),
+    unit(&["fl oz", "floz"], &["fluid ounce"], Kind::Volume, "0.0295735295625", Prefixes::None),
+    unit(&["tbsp"], &["tablespoon"], Kind::Volume, "0.01478676478125", Prefixes::None),
+    unit(&["tsp"], &["teaspoon"], Kind::Volume, "0.00492892159375", Prefixes::None),
+    unit(&["B"], &["byte"], Kind::Data, "1", Prefixes::Data),
+    unit(&["b", "bit"], &["bit"], Kind::Data, "1/8", Prefixes::Data),
+    unit(&["s", "sec"], &["second"], Kind::Time, "1", Prefixes::Si),
+    unit(&["min"], &["minute"], Kind::Time, "60", Prefixes::None),
+    unit(&["h", "hr"], &["hour"], Kind::Time, "3600", Prefixes::None),
+    unit(&["d"], &["day"], Kind::Time, "86400", Prefixes::None),
+    unit(&["wk"], &["week"], Kind::Time, "604800", Prefixes::None),
+    // the Julian year and a twelfth of it, as in astronomy
+    unit(&["mo"], &["month"], Kind::Time, "2629800", Prefixes::None),
+    unit(&["yr", "y"], &["year"], Kind::Time, "31557600", Prefixes::None),
+    unit(&["mph"], &[], Kind::Speed, "0.44704", Prefixes::None),
+    unit(&["kph", "kmh"], &[], Kind::Speed, "5/18", Prefixes::None),
+    unit(&["kn", "kt"], &["knot"], Kind::Speed, "1852/3600", Prefixes::None),
+];
+
+/// SI prefixes, as symbols and names, with their power of ten. From large to small, in the
+/// order preferred when ignoring case.
+const SI: &[(&str, &str, i32)] = &[
+    ("E", "exa", 18),
+    ("P", "peta", 15),
+    ("T", "tera", 12),
+    ("G", "giga", 9),
+    ("M", "mega", 6),
+    ("k", "kilo", 3),
+    ("h", "hecto", 2),
+    ("da", "deca", 1),
+    ("d", "deci", -1),
+    ("c", "centi", -2),
+    ("m", "milli", -3),
+    ("µ", "micro", -6),
+    ("u", "micro", -6),
+    ("n", "nano", -9),
+    ("p", "pico", -12),
+];
+
+/// Binary prefixes, with their power of 1024.
+const BINARY: &[(&str, &str, u32)] = &[
+    ("Ei", "exbi", 6),
+    ("Pi", "pebi", 5),
+    ("Ti", "tebi", 4),
+    ("Gi", "gibi", 3),
+    ("Mi", "mebi", 2),
+    ("Ki", "kibi", 1),
+];
+
+/// A quantity converted to another unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion {
+    /// The quantity asked about, as in `5 mi`.
+    pub from: String,
+    pub value: Number,
+    /// Symbol of the unit of `value`.
+    pub unit: String,
+}
+
+impl Conversion {
+    /// The value alone, as it's copied.
+    pub fn text(&self) -> String {
+        self.value.to_string()
+    }
+}
+
+impl fmt::Display for Conversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.value, self.unit)
+    }
+}
+
+/// Converts a query like `3 GiB in MB`. Returns `None` when it doesn't have that shape,
+/// a number and a unit, then `in`, `to`, `as` or `into` and another unit.
+pub fn convert(input: &str) -> Option<Result<Conversion>> {
+    let words = input.split_whitespace().collect::<Vec<_>>();
+    let separator = words.iter().rposition(|word| matches!(*word, "in" | "to" | "as" | "into" | "->"))?;
+    let quantity = words[..separator].join(" ");
+    let target = words[separator + 1..].join(" ");
+
+    let (negative, quantity) = match quantity.strip_prefix('-') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, quantity.as_str()),
+    };
+    let (value, source) = leading_number(quantity)?;
+    let source = source.trim();
+
+    if source.is_empty() || target.is_empty() {
+        return None;
+    }
+
+    let value = if negative { value.negate() } else { value };
+    Some(convert_value(value, source, &target))
+}
+
+fn convert_value(value: Number, source: &str, target: &str) -> Result<Conversion> {
+    let from = resolve(source)?;
+    let to = resolve(target)?;
+
+    if from.kind != to.kind {
+        return Err(Error::Incompatible(from.kind, to.kind));
+    }
+
+    let converted = value
+        .clone()
+        .checked_add(Number::Exact(from.offset))?
+        .checked_mul(Number::Exact(from.factor))?
+        .checked_div(Number::Exact(to.factor))?
+        .checked_sub(Number::Exact(to.offset))?;
+
+    Ok(Conversion {
+        from: format!("{value} {}", from.symbol),
+        value: converted.rounded(SIGNIFICANT_DIGITS),
+        unit: to.symbol,
+    })
+}
+
+/// A unit as written, with any prefix applied.
+struct Resolved {
+    kind: Kind,
+    factor: BigRational,
+    offset: BigRational,
+    symbol: String,
+}
+
+fn resolve(text: &str) -> Result<Resolved> {
+    let text = text.replace(" per ", "/");
+
+    if let Some((length, time)) = text.split_once('/').filter(|_| find(&text).is_none()) {
+        let (length, time) = (resolve(length.trim())?, resolve(time.trim())?);
+
+        if length.kind != Kind::Length || time.kind != Kind::Time {
+            return Err(Error::UnknownUnit(text));
+        }
+
+        return Ok(Resolved {
+            kind: Kind::Speed,
+            factor: length.factor / time.factor,
+            offset: BigRational::default(),
+            symbol: format!("{}/{}", length.symbol, time.symbol),
+        });
+    }
+
+    find(&text).ok_or(Error::UnknownUnit(text))
+}
+
+/// The unit written `text`, exactly or else ignoring case.
+fn find(text: &str) -> Option<Resolved> {
+    lookup(text, false).or_else(|| lookup(text, true))
+}
+
+fn lookup(text: &str, ignore_case: bool) -> Option<Resolved> {
+    let same = |a: &str, b: &str| if ignore_case { a.eq_ignore_ascii_case(b) } else { a == b };
+    let names = singulars(&text.to_lowercase());
+    let named = |unit_name: &str, prefix: &str| {
+        names.iter().any(|name| name.strip_prefix(prefix) == Some(unit_name))
+    };
+
+    for unit in UNITS {
+        if unit.symbols.iter().any(|symbol| same(symbol, text)) || unit.names.iter().any(|name| named(name, "")) {
+            return Some(resolved(unit, BigRational::one(), unit.symbols[0].to_owned()));
+        }
+    }
+
+    for unit in UNITS.iter().filter(|unit| unit.prefixes != Prefixes::None) {
+        for (symbol, prefix_name, factor) in prefixes(unit.prefixes) {
+            let by_symbol = unit.symbols.iter().any(|unit_symbol| {
+                text.len() == symbol.len() + unit_symbol.len()
+                    && text.is_char_boundary(symbol.len())
+                    && same(&text[..symbol.len()], symbol)
+                    && same(&text[symbol.len()..], unit_symbol)
+            });
+            let by_name = unit.names.iter().any(|unit_name| named(unit_name, prefix_name));
+
+            if by_symbol || by_name {
+                return Some(resolved(unit, factor, format!("{symbol}{}", unit.symbols[0])));
+            }
+        }
+    }
+
+    None
+}
+
+fn resolved(unit: &Unit, prefix: BigRational, symbol: String) -> Resolved {
+    Resolved { kind: unit.kind, factor: ratio(unit.factor) * prefix, offset: ratio(unit.offset), symbol }
+}
+
+/// The prefixes of a kind, with the factors they stand for.
+fn prefixes(kind: Prefixes) -> impl Iterator<Item = (&'static str, &'static str, BigRational)> {
+    let ten = |power: i32| {
+        let magnitude = BigInt::from(10).pow(power.unsigned_abs());
+        if power >= 0 { BigRational::from_integer(magnitude) } else { BigRational::new(BigInt::one(), magnitude) }
+    };
+
+    let si = SI
+        .iter()
+        .filter(move |(.., power)| kind == Prefixes::Si || *power >= 3)
+        .map(move |(symbol, name, power)| (*symbol, *name, ten(*power)));
+    let binary = BINARY
+        .iter()
+        .filter(move |_| kind == Prefixes::Data)
+        .map(|(symbol, name, power)| (*symbol, *name, BigRational::from_integer(BigInt::from(1024).pow(*power))));
+
+    // `KiB` reads as kibibytes before `K` is taken for an unknown prefix
+    binary.chain(si)
+}
+
+/// What a name may be in the singular, without a plural ending or a leading "degrees".
+fn singulars(name: &str) -> Vec<String> {
+    let name = name.strip_prefix("degrees ").or_else(|| name.strip_prefix("degree ")).unwrap_or(name);
+    let mut singulars = vec![name.to_owned()];
+
+    for (plural, singular) in [("es", ""), ("s", ""), ("ies", "y")] {
+        if let Some(stem) = name.strip_suffix(plural).filter(|stem| !stem.is_empty()) {
+            singulars.push(format!("{stem}{singular}"));
+        }
+    }
+
+    singulars
+}
+
+/// A factor of the table, a decimal or a fraction of two.
+fn ratio(text: &str) -> BigRational {
+    let decimal = |text: &str| {
+        let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let numerator = format!("{whole}{fraction}").parse::<BigInt>().expect("a valid factor");
+        BigRational::new(numerator, BigInt::from(10).pow(fraction.len() as u32))
+    };
+
+    match text.split_once('/') {
+        Some((numerator, denominator)) => decimal(numerator) / decimal(denominator),
+        None => decimal(text),
+    }
+}
@@ -0,0 +1,50 @@
+use lp_calc::{convert, Error, Kind};
+
+fn converted(input: &str) -> String {
+    match convert(input) {
+        Some(Ok(conversion)) => conversion.to_string(),
+        other => panic!("{input}: {other:?}"),
+    }
+}
+
+#[test]
+fn converts_between_units() {
+    assert_eq!(converted("5 mi in km"), "8.04672 km");
+    assert_eq!(converted("90 min in h"), "1.5 h");
+    assert_eq!(converted("2 lb to g"), "907.18474 g");
+    assert_eq!(converted("1 gal in L"), "3.785411784 L");
+    assert_eq!(converted("100 km/h in mph"), "62.1371192237334 mph");
+    assert_eq!(converted("3 miles per hour to m/s"), "1.34112 m/s");
+}
+
+#[test]
+fn converts_temperatures() {
+    assert_eq!(converted("72F to C"), "22.2222222222222 C");
+    assert_eq!(converted("-40 °C in °F"), "-40 F");
+    assert_eq!(converted("0 degrees celsius in kelvin"), "273.15 K");
+}
+
+#[test]
+fn takes_prefixes() {
+    assert_eq!(converted("3 GiB in MB"), "3221.225472 MB");
+    assert_eq!(converted("1 gb to mb"), "1000 MB");
+    assert_eq!(converted("8 Mb in kB"), "1000 kB");
+    assert_eq!(converted("2.5 kilometers in m"), "2500 m");
+    assert_eq!(converted("250ms in s"), "0.25 s");
+}
+
+#[test]
+fn rounds_values_outside_the_f64_range() {
+    assert_eq!(converted("1e320 m in m"), format!("1{} m", "0".repeat(320)));
+    assert_eq!(converted("1e400 m in km"), format!("1{} km", "0".repeat(397)));
+    assert_eq!(converted("1e-400 km in m"), "1e-397 m");
+}
+
+#[test]
+fn leaves_other_queries() {
+    assert!(convert("firefox").is_none());
+    assert!(convert("255 in hex").is_none());
+    assert_eq!(convert("5 kg in km"), Some(Err(Error::Incompatible(Kind::Mass, Kind::Length))));
+    assert_eq!(converted("5 in in cm"), "12.7 cm");
+    assert_eq!(convert("1 parsec in m"), Some(Err(Error::UnknownUnit("parsec".to_owned()))));
+}
@@ -0,0 +1,74 @@
+use lp_calc::{evaluate, Error};
+
+fn text(input: &str) -> String {
+    evaluate(input).and_then(|answer| answer.text()).unwrap_or_else(|e| panic!("{input}: {e}"))
+}
+
+#[test]
+fn follows_precedence() {
+    assert_eq!(text("2^10*3"), "3072");
+    assert_eq!(text("1 + 2 * 3"), "7");
+    assert_eq!(text("(1 + 2) * 3"), "9");
+    assert_eq!(text("-2^2"), "-4");
+    assert_eq!(text("2^3^2"), "512");
+    assert_eq!(text("2pi / pi"), "2");
+    assert_eq!(text("10 mod 3 + 7 % 4"), "4");
+}
+
+#[test]
+fn stays_exact() {
+    assert_eq!(text("0.1 + 0.2"), "0.3");
+    assert_eq!(text("1/3"), "0.33333333333333333333");
+    assert_eq!(text("2^100"), "1267650600228229401496703205376");
+    assert_eq!(text("20!"), "2432902008176640000");
+    assert_eq!(text("sqrt(9/4)"), "1.5");
+    assert_eq!(evaluate("1/3").unwrap().value.fraction().as_deref(), Some("1/3"));
+}
+
+#[test]
+fn writes_tiny_values_in_scientific_notation() {
+    assert_eq!(text("1e-400"), "1e-400");
+    assert_eq!(text("0.1^400"), "1e-400");
+    assert_eq!(text("-3 * 10^-150"), "-3e-150");
+    assert_eq!(text("1/3 * 10^-200"), "3.3333333333333333333e-201");
+
+    let power = text("2^-400");
+    assert!(power.starts_with("3.872591914849318"), "{power}");
+    assert!(power.ends_with("e-121"), "{power}");
+
+    assert_eq!(text("10^-100"), format!("0.{}1", "0".repeat(99)));
+}
+
+#[test]
+fn takes_percentages() {
+    assert_eq!(text("17% of 230"), "39.1");
+    assert_eq!(text("50% * 8"), "4");
+}
+
+#[test]
+fn approximates_functions() {
+    assert_eq!(text("sqrt 2"), "1.4142135623731");
+    assert_eq!(text("sin(pi / 2)"), "1");
+    assert_eq!(text("log(1000)"), "3");
+    assert_eq!(text("log(8, 2)"), "3");
+    assert_eq!(text("max(1, 5/2, 2)"), "2.5");
+}
+
+#[test]
+fn converts_bases() {
+    assert_eq!(text("255 in hex"), "0xff");
+    assert_eq!(text("0xff + 0b1 to bin"), "0b100000000");
+    assert_eq!(text("0o17"), "15");
+    assert_eq!(text("1e3 as oct"), "0o1750");
+    assert_eq!(evaluate("1.5 in hex").and_then(|a| a.text()), Err(Error::NotAnInteger(lp_calc::Base::Hexadecimal)));
+}
+
+#[test]
+fn reports_errors() {
+    assert_eq!(evaluate("1/0").unwrap_err(), Error::DivisionByZero);
+    assert_eq!(evaluate("sqrt(-1)").unwrap_err(), Error::Domain("sqrt"));
+    assert!(matches!(evaluate("2 +").unwrap_err(), Error::Syntax(_)));
+    assert!(matches!(evaluate("firefox").unwrap_err(), Error::Syntax(_)));
+    assert!(!evaluate("42").unwrap().computed);
+    assert!(evaluate("6*7").unwrap().computed);
+}
@@ -0,0 +1,113 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use serde::Deserialize;
+use crate::error::{self, Error, Result};
+use crate::{theme, Config};
+
+/// Layers the configuration sources on top of each other, lowest priority first:
+/// the defaults, the selected theme, the configuration file and explicit overrides.
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    path: Option<PathBuf>,
+    overrides: toml::Table,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses the given file instead of `<config dir>/config.toml`. Unlike the default
+    /// location, the file has to exist.
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Overrides a value, `key` being a dotted path such as `window.width`.
+    pub fn set(mut self, key: &str, value: impl Into<toml::Value>) -> Self {
+        let mut table = &mut self.overrides;
+        let mut parts = key.split('.').peekable();
+
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                table.insert(part.to_owned(), value.into());
+                break;
+            }
+
+            let entry = table
+                .entry(part)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+
+            if !entry.is_table() {
+                *entry = toml::Value::Table(toml::Table::new());
+            }
+
+            table = entry.as_table_mut().expect("entry is a table");
+        }
+
+        self
+    }
+
+    pub fn build(self) -> Result<Config> {
+        let file = self.read_file()?;
+
+        let theme_name = [&self.overrides, &file]
+            .into_iter()
+            .find_map(|layer| layer.get("theme").and_then(|v| v.as_str()))
+            .map(str::to_owned)
+            .unwrap_or_else(|| Config::default().theme);
+
+        let mut merged = toml::Table::try_from(Config::default())
+            .expect("default configuration serializes to a table");
+
+        let mut theme_layer = toml::Table::new();
+        theme_layer.insert("colors".to_owned(), theme::resolve(&theme_name)?.into());
+
+        merge(&mut merged, theme_layer);
+        merge(&mut merged, file);
+        merge(&mut merged, self.overrides);
+
+        Config::deserialize(toml::Value::Table(merged)).map_err(Error::Merge)
+    }
+
+    /// The configuration file that [`build`](Self::build) reads, if there is one.
+    pub fn file_path(&self) -> Option<PathBuf> {
+        self.path
+            .clone()
+            .or_else(|| crate::config_dir().map(|dir| dir.join("config.toml")))
+    }
+
+    fn read_file(&self) -> Result<toml::Table> {
+        let Some(path) = self.file_path() else {
+            return Ok(toml::Table::new());
+        };
+        let required = self.path.is_some();
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !required => {
+                return Ok(toml::Table::new());
+            }
+            Err(e) => return Err(error::read(path, e)),
+        };
+
+        // deserializing the file on its own gives errors with line and column information,
+        // which are lost once the layers are merged
+        toml::from_str::<Config>(&text).map_err(|e| error::parse(&path, e))?;
+        toml::from_str(&text).map_err(|e| error::parse(&path, e))
+    }
+}
+
+/// Recursively merges `layer` into `base`, tables are merged and anything else is replaced.
+fn merge(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(layer)) => merge(base, layer),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
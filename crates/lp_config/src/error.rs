@@ -0,0 +1,37 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to read configuration file '{}': {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("Invalid configuration in '{}': {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Invalid configuration: {0}")]
+    Merge(toml::de::Error),
+    #[error("Unknown theme '{0}'")]
+    UnknownTheme(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+pub(crate) fn read(path: impl Into<PathBuf>, source: io::Error) -> Error {
+    Error::Read {
+        path: path.into(),
+        source,
+    }
+}
+
+pub(crate) fn parse(path: impl Into<PathBuf>, source: toml::de::Error) -> Error {
+    Error::Parse {
+        path: path.into(),
+        source,
+    }
+}
@@ -1,8 +1,39 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::str::FromStr;
 use fuzzer::Fuzzable;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Command {
-    name: String,
-    description: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Shell command line to run, defaults to the name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec: Option<String>,
+    /// Icon name from the icon theme, or an absolute path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// File the item stands for, like the desktop entry of an application. Shown and
+    /// previewed in the preview pane.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl Command {
+    pub fn exec(&self) -> &str {
+        self.exec.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Secondary line shown under the name, if there is anything to show.
+    pub fn subtitle(&self) -> Option<&str> {
+        Some(self.description.as_str()).filter(|d| !d.is_empty())
+    }
 }
 
 impl Fuzzable for Command {
@@ -11,7 +42,612 @@ impl Fuzzable for Command {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// An external program that provides items, see the `lp_plugin` crate for the protocol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Plugin {
+    pub name: String,
+    /// Typed at the start of the query, hands the rest of the query to the plugin.
+    pub prefix: String,
+    /// Shell command line starting the plugin.
+    pub command: String,
+}
+
+/// The effective configuration, after merging the defaults, the selected theme, the
+/// configuration file and any overrides.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Source shown when the launcher opens.
+    pub mode: String,
+    /// Sources shown as tabs, in order.
+    pub modes: Vec<String>,
+    pub theme: String,
+    pub window: Window,
+    pub layout: Layout,
+    pub font: Font,
+    pub colors: Palette,
+    pub animation: Animation,
+    pub icons: Icons,
+    pub preview: Preview,
+    pub mouse: Mouse,
+    /// Query prefixes that hand the rest of the query to a source, like `>` for commands.
+    pub prefixes: BTreeMap<String, String>,
+    pub combi: Combi,
+    pub files: Files,
+    pub commands: Vec<Command>,
+    pub plugins: Vec<Plugin>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            mode: "commands".to_owned(),
+            modes: vec!["commands".to_owned()],
+            theme: "dark".to_owned(),
+            window: Window::default(),
+            layout: Layout::default(),
+            font: Font::default(),
+            colors: Palette::default(),
+            animation: Animation::default(),
+            icons: Icons::default(),
+            preview: Preview::default(),
+            mouse: Mouse::default(),
+            prefixes: [(">", "commands"), ("!", "run"), ("=", "calc")]
+                .into_iter()
+                .map(|(prefix, mode)| (prefix.to_owned(), mode.to_owned()))
+                .collect(),
+            combi: Combi::default(),
+            files: Files::default(),
+            commands: Vec::new(),
+            plugins: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Window {
+    /// Logical size, multiplied by the scale factor on screen, or a percentage of the
+    /// display like `"60%"`.
+    pub width: Length,
+    /// The most the window grows to with `auto_height`.
+    pub height: Length,
+    /// UI scale factor, detected from the display and `GDK_SCALE` or `QT_SCALE_FACTOR`
+    /// when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+    pub position: Position,
+    /// Title bar and borders from the window manager.
+    pub decorations: bool,
+    pub always_on_top: bool,
+    pub skip_taskbar: bool,
+    /// Marks the window as a utility window, which window managers don't tile.
+    pub utility: bool,
+    /// Shrinks the window to fit the results shown.
+    pub auto_height: bool,
+}
+
+/// A size in logical pixels, or a percentage of the display.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Pixels(u32),
+    Percent(f32),
+}
+
+impl Length {
+    /// The size in a display `available` large. Percentages are already relative to the
+    /// display, so they aren't scaled any further.
+    pub fn resolve(self, available: u32, scale: f32) -> u32 {
+        match self {
+            Length::Pixels(n) => (n as f32 * scale).round() as u32,
+            Length::Percent(p) => (available as f32 * p / 100.).round() as u32,
+        }
+    }
+
+    fn is_valid(self) -> bool {
+        match self {
+            Length::Pixels(n) => n > 0,
+            Length::Percent(p) => p > 0. && p <= 100.,
+        }
+    }
+}
+
+impl From<u32> for Length {
+    fn from(value: u32) -> Self {
+        Length::Pixels(value)
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Length::Pixels(n) => write!(f, "{n}"),
+            Length::Percent(p) => write!(f, "{p}%"),
+        }
+    }
+}
+
+impl FromStr for Length {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("expected pixels or a percentage like '60%', got '{s}'");
+
+        match s.trim().strip_suffix('%') {
+            Some(percent) => percent.trim().parse().map(Length::Percent).map_err(|_| invalid()),
+            None => s.trim().parse().map(Length::Pixels).map_err(|_| invalid()),
+        }
+    }
+}
+
+impl Serialize for Length {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Length::Pixels(n) => serializer.serialize_u32(*n),
+            Length::Percent(_) => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Length {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Pixels(u32),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Pixels(n) => Ok(Length::Pixels(n)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// Where the window opens, either relative to a display or at explicit coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Position {
+    Placement(Placement),
+    At { x: i32, y: i32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Placement {
+    /// The middle of the primary display.
+    Center,
+    /// Horizontally centered near the top of the primary display.
+    Top,
+    /// The middle of the display the mouse cursor is on.
+    Cursor,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position::Placement(Placement::Center)
+    }
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            width: Length::Pixels(800),
+            height: Length::Pixels(600),
+            scale: None,
+            position: Position::default(),
+            decorations: true,
+            always_on_top: false,
+            skip_taskbar: false,
+            utility: false,
+            auto_height: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    /// Maximum number of result rows shown.
+    pub lines: u32,
+    /// Radius of the rounded corners of the selection, the icon badges and the preview.
+    pub corner_radius: u32,
+    /// Single-line rows without the subtitle.
+    pub compact: bool,
+    /// Height of a result row with a title and a subtitle.
+    pub row_height: u32,
+    /// Height of a result row in compact mode.
+    pub compact_row_height: u32,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            lines: 16,
+            corner_radius: 6,
+            compact: false,
+            row_height: 52,
+            compact_row_height: 32,
+        }
+    }
+}
+
+impl Layout {
+    pub fn effective_row_height(&self) -> u32 {
+        if self.compact {
+            self.compact_row_height
+        } else {
+            self.row_height
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Font {
+    /// Family name, a generic family like `sans-serif` or the path of a font file.
+    pub family: String,
+    /// Families tried in order for characters the main font has no glyph for. Missing
+    /// ones are skipped.
+    pub fallback: Vec<String>,
+    pub prompt_size: u16,
+    pub title_size: u16,
+    pub subtitle_size: u16,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self {
+            family: "sans-serif".to_owned(),
+            fallback: ["Noto Sans CJK SC", "Noto Color Emoji", "Noto Sans Symbols 2", "DejaVu Sans"]
+                .map(str::to_owned)
+                .to_vec(),
+            prompt_size: 32,
+            title_size: 24,
+            subtitle_size: 16,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Animation {
+    /// Jump straight to the end of every animation.
+    pub reduce_motion: bool,
+    pub stiffness: f64,
+    /// Critically damped at `2 * sqrt(stiffness)`, lower values overshoot.
+    pub damping: f64,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            reduce_motion: false,
+            stiffness: 400.,
+            damping: 40.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Icons {
+    pub enabled: bool,
+    /// Icon theme, the GTK icon theme of the user when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Icon size in pixels.
+    pub size: u32,
+}
+
+impl Default for Icons {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            theme: None,
+            size: 24,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewPosition {
+    Right,
+    Bottom,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Preview {
+    pub enabled: bool,
+    pub position: PreviewPosition,
+    /// Fraction of the space below the prompt that the pane takes.
+    pub size: f32,
+    /// Shell command whose output is shown instead of the built-in preview. `{}` is
+    /// replaced by the quoted path of the item, or its name if it has no path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// How long the command may run before it's killed.
+    pub timeout_ms: u64,
+}
+
+impl Default for Preview {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            position: PreviewPosition::Right,
+            size: 0.4,
+            command: None,
+            timeout_ms: 1000,
+        }
+    }
+}
+
+impl Preview {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Mouse {
+    /// Moves the selection to the row under the pointer.
+    pub hover_select: bool,
+    /// Launches with a single click rather than a double click.
+    pub single_click: bool,
+    /// Closes the launcher once it loses the focus, which clicking outside of it does.
+    pub close_on_focus_loss: bool,
+}
+
+impl Default for Mouse {
+    fn default() -> Self {
+        Self {
+            hover_select: true,
+            single_click: false,
+            close_on_focus_loss: true,
+        }
+    }
+}
+
+/// How the combined mode arranges the results of its sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CombiLayout {
+    /// Each source's results together under its header, the best source first.
+    Grouped,
+    /// Best result first whatever the source, with a header wherever the source changes.
+    Interleaved,
+}
+
+/// The `combi` mode, which runs the query through several sources at once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Combi {
+    pub modes: Vec<String>,
+    /// Results taken from each source at most.
+    pub max_per_mode: u32,
+    pub layout: CombiLayout,
+}
+
+impl Default for Combi {
+    fn default() -> Self {
+        Self {
+            modes: ["calc", "units", "apps", "run", "commands"].map(str::to_owned).to_vec(),
+            max_per_mode: 5,
+            layout: CombiLayout::Grouped,
+        }
+    }
+}
+
+/// The `files` mode, which searches the files below some directories.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Files {
+    /// Directories searched, a leading `~` standing for the home directory.
+    pub roots: Vec<String>,
+    /// Includes hidden files and directories.
+    pub hidden: bool,
+    /// Leaves out what `.gitignore` and the other ignore files of git leave out.
+    pub gitignore: bool,
+    /// Paths left out, as globs in `.gitignore` syntax.
+    pub exclude: Vec<String>,
+    /// Files indexed at most, the rest are left out.
+    pub max_files: u32,
+}
+
+impl Default for Files {
+    fn default() -> Self {
+        Self {
+            roots: vec!["~".to_owned()],
+            hidden: false,
+            gitignore: true,
+            exclude: vec!["node_modules".to_owned(), "__pycache__".to_owned()],
+            max_files: 100_000,
+        }
+    }
+}
+
+impl Config {
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("configuration serializes to toml")
+    }
+
+    /// Looks for problems that parse fine but can't work at runtime.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        if !self.window.width.is_valid() || !self.window.height.is_valid() {
+            diagnostics.push(Diagnostic::error(format!(
+                "window size must be non-zero and percentages at most 100%, got {}x{}",
+                self.window.width,
+                self.window.height,
+            )));
+        }
+
+        if self.window.scale.is_some_and(|scale| !(scale > 0. && scale.is_finite())) {
+            diagnostics.push(Diagnostic::error(format!(
+                "window.scale must be positive, got {}",
+                self.window.scale.unwrap_or_default(),
+            )));
+        }
+
+        if self.layout.effective_row_height() == 0 {
+            diagnostics.push(Diagnostic::error("the row height must be non-zero"));
+        }
+
+        if self.font.prompt_size == 0 || self.font.title_size == 0 || self.font.subtitle_size == 0 {
+            diagnostics.push(Diagnostic::error("font sizes must be non-zero"));
+        }
+
+        if self.layout.lines == 0 {
+            diagnostics.push(Diagnostic::warning("layout.lines is 0, no results will be shown"));
+        }
+
+        if !(self.preview.size > 0. && self.preview.size < 1.) {
+            diagnostics.push(Diagnostic::error(format!(
+                "preview.size must be between 0 and 1, got {}",
+                self.preview.size,
+            )));
+        }
+
+        // also false for NaN, which no spring settles with
+        let (stiffness, damping) = (self.animation.stiffness, self.animation.damping);
+        let settles = stiffness > 0. && stiffness.is_finite() && damping >= 0. && damping.is_finite();
+
+        if !settles {
+            diagnostics.push(Diagnostic::error(format!(
+                "animation.stiffness must be positive and animation.damping non-negative, got {} and {}",
+                self.animation.stiffness,
+                self.animation.damping,
+            )));
+        }
+
+        for (index, command) in self.commands.iter().enumerate() {
+            if command.name.trim().is_empty() {
+                diagnostics.push(Diagnostic::error(format!("commands[{index}] has an empty name")));
+            }
+
+            if self.commands[..index].iter().any(|c| c.name == command.name) {
+                diagnostics.push(Diagnostic::warning(format!(
+                    "commands[{index}] duplicates the name '{}'",
+                    command.name,
+                )));
+            }
+        }
+
+        if self.combi.max_per_mode == 0 {
+            diagnostics.push(Diagnostic::warning("combi.max_per_mode is 0, the combi mode shows nothing"));
+        }
+
+        if self.combi.modes.iter().any(|mode| mode == "combi") {
+            diagnostics.push(Diagnostic::error("combi.modes can't include combi itself"));
+        }
+
+        if self.files.roots.is_empty() || self.files.max_files == 0 {
+            diagnostics.push(Diagnostic::warning(
+                "files.roots is empty or files.max_files is 0, the files mode shows nothing",
+            ));
+        }
+
+        if self.prefixes.contains_key("") {
+            diagnostics.push(Diagnostic::error("prefixes can't be empty"));
+        }
+
+        for (index, plugin) in self.plugins.iter().enumerate() {
+            if plugin.name.trim().is_empty() || plugin.prefix.is_empty() || plugin.command.trim().is_empty() {
+                diagnostics.push(Diagnostic::error(format!(
+                    "plugins[{index}] needs a name, a prefix and a command",
+                )));
+            }
+
+            if self.prefixes.contains_key(&plugin.prefix) {
+                diagnostics.push(Diagnostic::error(format!(
+                    "plugins[{index}] prefix '{}' is also in prefixes",
+                    plugin.prefix,
+                )));
+            }
+
+            if let Some(other) = self.plugins[..index].iter().find(|p| !p.prefix.is_empty() && p.prefix == plugin.prefix) {
+                diagnostics.push(Diagnostic::error(format!(
+                    "plugins[{index}] prefix '{}' is also the prefix of '{}'",
+                    plugin.prefix,
+                    other.name,
+                )));
+            }
+        }
+
+        // a query starting with the longer prefix goes to its mode, never to the shorter one's
+        let prefixes = self
+            .prefixes
+            .iter()
+            .chain(self.plugins.iter().map(|plugin| (&plugin.prefix, &plugin.name)))
+            .filter(|(prefix, _)| !prefix.is_empty())
+            .collect::<Vec<_>>();
+
+        for (longer, longer_mode) in &prefixes {
+            for (shorter, shorter_mode) in &prefixes {
+                if longer.len() > shorter.len() && longer.starts_with(shorter.as_str()) {
+                    diagnostics.push(Diagnostic::warning(format!(
+                        "the prefix '{longer}' of '{longer_mode}' starts with the prefix '{shorter}' of \
+                         '{shorter_mode}', queries starting with '{longer}' go to '{longer_mode}'",
+                    )));
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { severity: Severity::Error, message: message.into() }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self { severity: Severity::Warning, message: message.into() }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.message),
+            Severity::Error => write!(f, "error: {}", self.message),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/launchpad`, falling back to `~/.config/launchpad`.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("launchpad"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Rgb {
     red: u8,
     green: u8,
@@ -40,3 +676,46 @@ impl Rgb {
         self.blue
     }
 }
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii())
+            .ok_or_else(|| format!("expected a colour like '#rrggbb', got '{s}'"))?;
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16)
+            .map_err(|_| format!("invalid hex digits in colour '{s}'"));
+
+        Ok(Rgb::new(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+impl Serialize for Rgb {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgb {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+pub use builder::Builder;
+pub use error::{Error, Result};
+pub use theme::{Palette, BUILTIN_THEMES};
+
+mod builder;
+pub mod error;
+pub mod theme;
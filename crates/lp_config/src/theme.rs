@@ -0,0 +1,75 @@
+use std::fs;
+use serde::{Deserialize, Serialize};
+use crate::error::{self, Error, Result};
+use crate::Rgb;
+
+/// Colours used by the launcher. A theme is just a named palette.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Palette {
+    pub background: Rgb,
+    pub prompt_background: Rgb,
+    pub text: Rgb,
+    /// Secondary text, like descriptions under result titles.
+    pub subtitle: Rgb,
+    pub cursor: Rgb,
+    pub selection: Rgb,
+    /// Characters of the results that the query matched.
+    pub highlight: Rgb,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+impl Palette {
+    pub const DARK: Palette = Palette {
+        background: Rgb::ALMOST_BLACK,
+        prompt_background: Rgb::new(32, 30, 35),
+        text: Rgb::ALMOST_WHITE,
+        subtitle: Rgb::new(148, 146, 154),
+        cursor: Rgb::WHITE,
+        selection: Rgb::new(62, 59, 68),
+        highlight: Rgb::new(255, 184, 108),
+    };
+
+    pub const LIGHT: Palette = Palette {
+        background: Rgb::new(245, 245, 245),
+        prompt_background: Rgb::new(225, 223, 228),
+        text: Rgb::new(40, 40, 40),
+        subtitle: Rgb::new(112, 110, 118),
+        cursor: Rgb::BLACK,
+        selection: Rgb::new(205, 203, 212),
+        highlight: Rgb::new(184, 92, 0),
+    };
+}
+
+pub const BUILTIN_THEMES: &[(&str, Palette)] = &[
+    ("dark", Palette::DARK),
+    ("light", Palette::LIGHT),
+];
+
+/// Resolves a theme by name, looking at the built-in themes first and then at
+/// `<config dir>/themes/<name>.toml`.
+pub fn resolve(name: &str) -> Result<toml::Table> {
+    if let Some((_, palette)) = BUILTIN_THEMES.iter().find(|(n, _)| *n == name) {
+        return Ok(to_table(palette));
+    }
+
+    let path = crate::config_dir()
+        .map(|dir| dir.join("themes").join(format!("{name}.toml")))
+        .filter(|path| path.is_file())
+        .ok_or_else(|| Error::UnknownTheme(name.to_owned()))?;
+
+    let text = fs::read_to_string(&path).map_err(|e| error::read(&path, e))?;
+
+    // parse into a palette first so that mistakes are reported against the theme file
+    toml::from_str::<Palette>(&text).map_err(|e| error::parse(&path, e))?;
+    toml::from_str(&text).map_err(|e| error::parse(&path, e))
+}
+
+fn to_table(palette: &Palette) -> toml::Table {
+    toml::Table::try_from(palette).expect("palette serializes to a table")
+}
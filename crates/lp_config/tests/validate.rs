@@ -0,0 +1,47 @@
+use lp_config::{Config, Length, Plugin, Severity};
+
+fn errors(config: &Config) -> usize {
+    config.validate().iter().filter(|d| d.severity == Severity::Error).count()
+}
+
+#[test]
+fn accepts_the_defaults() {
+    assert_eq!(errors(&Config::default()), 0);
+}
+
+#[test]
+fn rejects_window_sizes_that_cannot_be_shown() {
+    for width in [Length::Pixels(0), Length::Percent(0.), Length::Percent(-20.), Length::Percent(150.), Length::Percent(f32::NAN)] {
+        let mut config = Config::default();
+        config.window.width = width;
+
+        assert_eq!(errors(&config), 1, "{width:?}");
+    }
+}
+
+#[test]
+fn rejects_springs_that_never_settle() {
+    for (stiffness, damping) in [(0., 20.), (-1., 20.), (f64::NAN, 20.), (f64::INFINITY, 20.), (300., -1.), (300., f64::NAN)] {
+        let mut config = Config::default();
+        config.animation.stiffness = stiffness;
+        config.animation.damping = damping;
+
+        assert_eq!(errors(&config), 1, "stiffness {stiffness}, damping {damping}");
+    }
+}
+
+#[test]
+fn rejects_duplicate_prefixes_and_warns_of_overlapping_ones() {
+    let plugin = |name: &str, prefix: &str| Plugin { name: name.to_owned(), prefix: prefix.to_owned(), command: "cat".to_owned() };
+    let warnings = |config: &Config| config.validate().iter().filter(|d| d.severity == Severity::Warning).count();
+
+    let mut config = Config { plugins: vec![plugin("echo", ">>"), plugin("dict", "?")], ..Config::default() };
+    assert_eq!((errors(&config), warnings(&config)), (0, 1));
+
+    config.plugins.push(plugin("define", "?d"));
+    assert_eq!((errors(&config), warnings(&config)), (0, 2));
+
+    config.plugins.push(plugin("ask", "?"));
+    config.plugins.push(plugin("compare", ">"));
+    assert_eq!(errors(&config), 2);
+}
@@ -1,9 +1,16 @@
 use std::rc::Rc;
 use std::borrow::Cow;
-use rank::{Score, Ranker};
+pub use rank::{Score, Ranker};
 
 pub trait Fuzzable {
     fn pattern(&self) -> String;
+
+    /// Index of the char where the part of the pattern that matters most starts, like the
+    /// file name at the end of a path. A match within it scores higher than one that
+    /// spreads over the rest.
+    fn emphasis(&self) -> usize {
+        0
+    }
 }
 
 impl Fuzzable for String {
@@ -40,7 +47,7 @@ where
         &mut self.input
     }
 
-    pub fn matches(&mut self) -> Matches<T> {
+    pub fn matches(&mut self) -> Matches<'_, T> {
         if self.last_input != self.input {
             self.last_input.clone_from(&self.input);
             self.rankings = Some(Rc::new(self.ranker.rankings_of(&self.input)))
@@ -53,6 +60,17 @@ where
         }
     }
 
+    /// Number of items matching the input at all.
+    pub fn matched_count(&mut self) -> usize {
+        // the rankings are sorted by score, the items that don't match come last
+        self.matches().rankings.partition_point(|(score, _)| score.value > 0)
+    }
+
+    /// `score` of one of the matches relative to the best possible, see [`Ranker::normalize`].
+    pub fn normalize(&self, score: i64) -> f64 {
+        self.ranker.normalize(score, &self.input)
+    }
+
     pub fn get_rankings(&mut self) -> Rc<Vec<(Score, usize)>> {
         Rc::clone(self.rankings
             .get_or_insert_with(|| Rc::new(self.ranker.rankings_of(&self.input))))
@@ -66,7 +84,7 @@ pub struct MatchOwned<T> {
 }
 
 impl<T> MatchOwned<T> {
-    pub fn borrowed(&self) -> Match<T> {
+    pub fn borrowed(&self) -> Match<'_, T> {
         Match {
             item: &self.item,
             score: self.score,
@@ -118,6 +136,12 @@ where
             None
         }
     }
+
+    /// Skips ahead without building the matches in between.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.idx = self.idx.saturating_add(n);
+        self.next()
+    }
 }
 
 mod rank;
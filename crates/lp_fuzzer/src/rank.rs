@@ -2,6 +2,9 @@ use std::cmp::Reverse;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 
+/// Factor on the score of a match within the emphasized part of a pattern.
+const EMPHASIS_WEIGHT: i64 = 2;
+
 #[derive(Default, Debug, Clone)]
 pub struct Score {
     pub value: i64,
@@ -34,13 +37,43 @@ where
         &self.choices
     }
 
+    /// Score of a choice that is the input itself, the best any choice can get.
+    pub fn best_score(&self, input: &str) -> i64 {
+        self.matcher.fuzzy_match(input, input).unwrap_or_default()
+    }
+
+    /// `score` relative to the best any choice can get for `input`, from 0 to 1. Unlike
+    /// the scores themselves, these compare across rankers with different choices.
+    pub fn normalize(&self, score: i64, input: &str) -> f64 {
+        match self.best_score(input) {
+            best if best > 0 => (score as f64 / best as f64).clamp(0., 1.),
+            _ => 0.,
+        }
+    }
+
+    /// The better of `score`, the match on the whole pattern, and the weighted match on the
+    /// part starting at the `emphasis`th char.
+    fn emphasized(&self, pattern: &str, emphasis: usize, input: &str, score: Score) -> Score {
+        let Some((start, _)) = pattern.char_indices().nth(emphasis).filter(|_| emphasis > 0) else {
+            return score;
+        };
+
+        match self.matcher.fuzzy_indices(&pattern[start..], input) {
+            Some((value, indices)) if value * EMPHASIS_WEIGHT > score.value => {
+                Score::new(value * EMPHASIS_WEIGHT, indices.into_iter().map(|i| i + emphasis).collect())
+            }
+            _ => score,
+        }
+    }
+
     pub fn rankings_of(&mut self, input: &str) -> Vec<(Score, usize)> {
         let mut scores = Vec::with_capacity(self.choices.len());
 
         for (index, choice) in self.choices.iter().enumerate() {
+            let pattern = choice.pattern();
             let score = self.matcher
-                .fuzzy_indices(&choice.pattern(), input)
-                .map(|(score, indices)| Score::new(score, indices))
+                .fuzzy_indices(&pattern, input)
+                .map(|(score, indices)| self.emphasized(&pattern, choice.emphasis(), input, Score::new(score, indices)))
                 .unwrap_or_default();
 
             scores.push((score, index));
@@ -0,0 +1,59 @@
+//! The reference plugin: offers the query itself as the only item.
+//!
+//! Activating the item appends its text to the file given as the first argument, or prints
+//! it to stderr without one.
+//!
+//! ```toml
+//! [[plugins]]
+//! name = "Echo"
+//! prefix = "echo "
+//! command = "lp-echo-plugin"
+//! ```
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use lp_plugin::{Action, Item, Reply, Request, VERSION};
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("lp-echo-plugin: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> io::Result<()> {
+    let output = std::env::args_os().nth(1);
+
+    for request in lp_plugin::requests(io::stdin().lock()) {
+        match request? {
+            Request::Hello { version } if version != VERSION => {
+                return Err(io::Error::other(format!("unsupported protocol version {version}")));
+            }
+            Request::Hello { .. } => {}
+            Request::Query { id, text } => {
+                let items = match text.trim() {
+                    "" => Vec::new(),
+                    text => vec![Item {
+                        id: text.to_owned(),
+                        title: text.to_owned(),
+                        subtitle: "Echo".to_owned(),
+                        icon: None,
+                        actions: vec![Action { id: "echo".to_owned(), title: "Echo".to_owned() }],
+                    }],
+                };
+
+                lp_plugin::write(io::stdout().lock(), &Reply::Items { query: id, items })?;
+            }
+            Request::Activate { item, .. } => match &output {
+                Some(path) => writeln!(File::options().create(true).append(true).open(path)?, "{item}")?,
+                None => eprintln!("{item}"),
+            },
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+//! Plugins: external programs that provide items to the launcher.
+//!
+//! The launcher starts a plugin as a shell command and talks to it in lines of JSON, one
+//! message per line. It writes [`Request`]s to the plugin's stdin: a `hello` carrying the
+//! protocol [`VERSION`] first, then a `query` every time the query changes, and finally an
+//! `activate` when the user picks one of the plugin's items. The plugin answers on its
+//! stdout with [`Reply`]s, `items` for the latest query it has seen. Anything it writes to
+//! stderr goes to the launcher's stderr.
+//!
+//! ```text
+//! → {"type":"hello","version":1}
+//! → {"type":"query","id":1,"text":"hel"}
+//! → {"type":"query","id":2,"text":"hello"}
+//! ← {"type":"items","query":2,"items":[{"id":"hello","title":"hello","actions":[{"id":"copy","title":"Copy"}]}]}
+//! → {"type":"activate","item":"hello","action":"copy"}
+//! ```
+//!
+//! Once the launcher is done with a plugin it closes the plugin's stdin, which the plugin
+//! should take as the signal to exit. After an activation it waits a little for that to
+//! happen so the plugin can finish acting on it.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+/// Version of the protocol, bumped whenever it changes incompatibly.
+pub const VERSION: u32 = 1;
+
+/// A message from the launcher to the plugin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// The first message, a plugin that speaks another version should exit.
+    Hello { version: u32 },
+    /// The query changed, to the text after the plugin's prefix. Items sent for it refer to
+    /// its `id`, which grows with every query.
+    Query { id: u64, text: String },
+    /// The user picked `item`, through `action` when the item has actions.
+    Activate {
+        item: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        action: Option<String>,
+    },
+}
+
+/// A message from the plugin to the launcher.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Reply {
+    /// The items for query `query`, best first, replacing any sent before. Items for a
+    /// query other than the latest are ignored.
+    Items { query: u64, items: Vec<Item> },
+    /// Something went wrong, the launcher reports it and carries on.
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+    /// Identifies the item when it's activated.
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub subtitle: String,
+    /// Icon name or path, looked up like the icons of commands.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// What can be done with the item, the first one being the default.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<Action>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Action {
+    pub id: String,
+    pub title: String,
+}
+
+/// Reads the requests written by the launcher from `input`, for plugins written in Rust.
+/// Lines that aren't requests end up as errors.
+pub fn requests(input: impl BufRead) -> impl Iterator<Item = io::Result<Request>> {
+    input.lines().map(|line| {
+        let line = line?;
+        serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    })
+}
+
+/// Writes one message as a line of JSON and flushes it.
+pub fn write<T: Serialize>(mut output: impl Write, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    output.write_all(line.as_bytes())?;
+    output.flush()
+}
+
+/// A running plugin, seen from the launcher. Killed when dropped.
+pub struct Plugin {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    replies: Receiver<Reply>,
+    query: u64,
+    items: Vec<Item>,
+}
+
+impl Plugin {
+    /// Starts `command` with `sh -c` and greets it. `wake` is called from another thread
+    /// whenever the plugin sent something, to be picked up with [`Plugin::poll`].
+    pub fn spawn(command: &str, wake: impl Fn() + Send + 'static) -> io::Result<Self> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            // in a group of its own, so that whatever it starts is killed along with it
+            .process_group(0)
+            .spawn()?;
+
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (sender, replies) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let reply = serde_json::from_str(&line).unwrap_or_else(|e| Reply::Error {
+                    message: format!("malformed reply: {e}"),
+                });
+
+                if sender.send(reply).is_err() {
+                    break;
+                }
+
+                wake();
+            }
+        });
+
+        let mut plugin = Self { stdin: child.stdin.take(), child, replies, query: 0, items: Vec::new() };
+        plugin.send(&Request::Hello { version: VERSION })?;
+
+        Ok(plugin)
+    }
+
+    /// Hands the new query to the plugin. The items it sent for earlier ones are kept until
+    /// it answers this one.
+    pub fn query(&mut self, text: &str) -> io::Result<()> {
+        self.query += 1;
+        self.send(&Request::Query { id: self.query, text: text.to_owned() })
+    }
+
+    /// Picks up what the plugin sent since the last call, returns whether the items changed.
+    /// Errors reported by the plugin are printed.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+
+        while let Ok(reply) = self.replies.try_recv() {
+            match reply {
+                Reply::Items { query, items } if query == self.query => {
+                    self.items = items;
+                    changed = true;
+                }
+                Reply::Items { .. } => {}
+                Reply::Error { message } => eprintln!("warning: plugin: {message}"),
+            }
+        }
+
+        changed
+    }
+
+    /// The items for the latest query answered.
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Tells the plugin the user picked `item` and gives it up to `timeout` to act on it
+    /// and exit.
+    pub fn activate(mut self, item: &Item, action: Option<&Action>, timeout: Duration) -> io::Result<()> {
+        self.send(&Request::Activate { item: item.id.clone(), action: action.map(|a| a.id.clone()) })?;
+        self.stdin = None;
+
+        let deadline = Instant::now() + timeout;
+
+        while self.child.try_wait()?.is_none() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        Ok(())
+    }
+
+    fn send(&mut self, request: &Request) -> io::Result<()> {
+        match &mut self.stdin {
+            Some(stdin) => write(stdin, request),
+            None => Err(io::Error::new(io::ErrorKind::BrokenPipe, "the plugin's input is closed")),
+        }
+    }
+}
+
+impl Drop for Plugin {
+    fn drop(&mut self) {
+        self.stdin = None;
+
+        if let Ok(None) = self.child.try_wait() {
+            // SAFETY: kill has no memory safety requirements, the group is the child's own
+            unsafe { libc::kill(-(self.child.id() as libc::pid_t), libc::SIGKILL) };
+        }
+
+        let _ = self.child.wait();
+    }
+}
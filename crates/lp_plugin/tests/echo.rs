@@ -0,0 +1,65 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use lp_plugin::Plugin;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Starts the echo plugin, writing activations to `output`, along with a channel that
+/// receives a message whenever it sends something.
+fn echo(output: &Path) -> (Plugin, mpsc::Receiver<()>) {
+    let command = format!("'{}' '{}'", env!("CARGO_BIN_EXE_lp-echo-plugin"), output.display());
+    let (wake, woken) = mpsc::channel();
+    let plugin = Plugin::spawn(&command, move || {
+        let _ = wake.send(());
+    })
+    .unwrap();
+
+    (plugin, woken)
+}
+
+fn output(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("lp-echo-{}-{name}", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// Waits until the plugin's items change.
+fn answer(plugin: &mut Plugin, woken: &mpsc::Receiver<()>) {
+    while !plugin.poll() {
+        woken.recv_timeout(TIMEOUT).expect("the plugin didn't answer");
+    }
+}
+
+#[test]
+fn answers_the_latest_query() {
+    let path = output("query");
+    let (mut plugin, woken) = echo(&path);
+
+    plugin.query("hel").unwrap();
+    plugin.query("hello").unwrap();
+
+    // the answer to the first query is ignored
+    answer(&mut plugin, &woken);
+
+    let titles = plugin.items().iter().map(|item| item.title.as_str()).collect::<Vec<_>>();
+    assert_eq!(titles, ["hello"]);
+    assert_eq!(plugin.items()[0].subtitle, "Echo");
+}
+
+#[test]
+fn hands_activation_to_the_plugin() {
+    let path = output("activate");
+    let (mut plugin, woken) = echo(&path);
+
+    plugin.query("hello world").unwrap();
+    answer(&mut plugin, &woken);
+
+    let item = plugin.items()[0].clone();
+    plugin.activate(&item, item.actions.first(), TIMEOUT).unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "hello world\n");
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,131 @@
+//! Frame-rate independent animation primitives. Everything advances by the time elapsed
+//! since the previous frame rather than by a fixed amount per frame.
+
+use std::time::Duration;
+
+/// Longest time advanced in one call, so that a late frame doesn't make a spring jump.
+const MAX_STEP: f64 = 0.1;
+/// Integration step, small enough to keep stiff springs stable.
+const SUBSTEP: f64 = 1. / 240.;
+
+/// Damped spring of unit mass pulling a value toward its target.
+#[derive(Debug, Clone)]
+pub struct Spring {
+    target: f64,
+    value: f64,
+    velocity: f64,
+    stiffness: f64,
+    damping: f64,
+}
+
+impl Spring {
+    pub fn with_params(value: f64, stiffness: f64, damping: f64) -> Self {
+        Self {
+            target: value,
+            value,
+            velocity: 0.,
+            stiffness,
+            damping,
+        }
+    }
+
+    pub fn update_target(&mut self, new_value: f64) {
+        self.target = new_value;
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn step(&mut self, dt: Duration) {
+        let mut remaining = dt.as_secs_f64().min(MAX_STEP);
+
+        while remaining > 0. {
+            let h = remaining.min(SUBSTEP);
+            let acceleration = self.stiffness * (self.target - self.value) - self.damping * self.velocity;
+
+            self.velocity += acceleration * h;
+            self.value += self.velocity * h;
+            remaining -= h;
+        }
+
+        if self.is_settled() {
+            self.snap();
+        }
+    }
+
+    /// Jumps straight to the target.
+    pub fn snap(&mut self) {
+        self.value = self.target;
+        self.velocity = 0.;
+    }
+
+    /// Whether the spring is close enough to rest that another step wouldn't show.
+    pub fn is_settled(&self) -> bool {
+        (self.target - self.value).abs() < 0.5 && self.velocity.abs() < 1.
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    QuadOut,
+    CubicOut,
+}
+
+impl Easing {
+    /// Maps linear progress in `[0, 1]` onto the curve.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0., 1.);
+
+        match self {
+            Easing::QuadOut => 1. - (1. - t) * (1. - t),
+            Easing::CubicOut => 1. - (1. - t).powi(3),
+        }
+    }
+}
+
+/// Value going from one point to another over a fixed duration.
+#[derive(Debug, Clone)]
+pub struct Tween {
+    from: f64,
+    to: f64,
+    elapsed: Duration,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Tween {
+    pub fn new(from: f64, to: f64, duration: Duration, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            elapsed: Duration::ZERO,
+            duration,
+            easing,
+        }
+    }
+
+    pub fn step(&mut self, dt: Duration) {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+    }
+
+    pub fn finish(&mut self) {
+        self.elapsed = self.duration;
+    }
+
+    pub fn value(&self) -> f64 {
+        if self.duration.is_zero() {
+            return self.to;
+        }
+
+        let progress = self.elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        self.from + (self.to - self.from) * self.easing.apply(progress)
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+#[cfg(test)]
+mod tests;
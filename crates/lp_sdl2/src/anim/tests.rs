@@ -0,0 +1,63 @@
+use std::time::Duration;
+use super::{Easing, Spring, Tween};
+
+const FRAME: Duration = Duration::from_micros(16_500);
+
+fn spring() -> Spring {
+    let mut spring = Spring::with_params(0., 300., 30.);
+    spring.update_target(100.);
+    spring
+}
+
+#[test]
+fn springs_move_alike_whatever_the_frame_rate() {
+    let (mut slow, mut fast) = (spring(), spring());
+
+    for _ in 0..10 {
+        slow.step(FRAME * 2);
+        fast.step(FRAME);
+        fast.step(FRAME);
+
+        assert!((slow.value() - fast.value()).abs() < 1., "{} against {}", slow.value(), fast.value());
+    }
+}
+
+#[test]
+fn springs_settle_on_their_target() {
+    let mut spring = spring();
+    let frames = (0..1000).take_while(|_| {
+        spring.step(FRAME);
+        !spring.is_settled()
+    });
+
+    assert!(frames.count() < 1000, "never settled");
+    assert_eq!(spring.value(), 100.);
+}
+
+#[test]
+fn late_frames_advance_springs_by_a_bounded_step() {
+    let (mut late, mut bounded) = (spring(), spring());
+    late.step(Duration::from_secs(5));
+    bounded.step(Duration::from_millis(100));
+
+    assert_eq!(late.value(), bounded.value());
+}
+
+#[test]
+fn tweens_move_alike_whatever_the_frame_rate() {
+    let tween = || Tween::new(0., 1., Duration::from_millis(120), Easing::CubicOut);
+    let (mut slow, mut fast) = (tween(), tween());
+
+    for _ in 0..3 {
+        slow.step(FRAME * 2);
+        fast.step(FRAME);
+        fast.step(FRAME);
+
+        assert!((slow.value() - fast.value()).abs() < 1e-9);
+        assert!(!slow.is_settled());
+    }
+
+    slow.step(FRAME * 2);
+    assert!(slow.is_settled());
+    assert_eq!(slow.value(), 1.);
+}
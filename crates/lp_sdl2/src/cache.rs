@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use lru::LruCache;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect as SdlRect;
+use sdl2::render::{BlendMode, Canvas, RenderTarget, Texture, TextureCreator};
+use sdl2::surface::Surface;
+use config::Rgb;
+use crate::font::{FontHandle, FontKey};
+use crate::{error, sdl_color, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TextKey {
+    text: String,
+    font: FontKey,
+    color: Rgb,
+}
+
+/// Rendered strings, so that the same result names aren't rasterized again on every frame.
+///
+/// Entries are evicted least recently used first, once either the entry count or the
+/// texture memory budget is exceeded.
+pub struct TextCache<'a, C> {
+    creator: &'a TextureCreator<C>,
+    entries: LruCache<TextKey, Texture<'a>>,
+    bytes: usize,
+    max_bytes: usize,
+}
+
+impl<'a, C> TextCache<'a, C> {
+    const MAX_ENTRIES: usize = 512;
+    const MAX_BYTES: usize = 32 * 1024 * 1024;
+
+    pub fn new(creator: &'a TextureCreator<C>) -> Self {
+        Self {
+            creator,
+            entries: LruCache::new(NonZeroUsize::new(Self::MAX_ENTRIES).expect("non-zero capacity")),
+            bytes: 0,
+            max_bytes: Self::MAX_BYTES,
+        }
+    }
+
+    pub fn get_or_render(&mut self, text: &str, font: &FontHandle<'_>, color: Rgb) -> Result<&mut Texture<'a>> {
+        let key = TextKey {
+            text: text.to_owned(),
+            font: font.key(),
+            color,
+        };
+
+        if !self.entries.contains(&key) {
+            let texture = render_runs(text, font, color)?
+                .as_texture(self.creator)
+                .map_err(error::draw)?;
+
+            self.bytes += texture_bytes(&texture);
+
+            if let Some((_, evicted)) = self.entries.push(key.clone(), texture) {
+                self.bytes -= texture_bytes(&evicted);
+            }
+
+            while self.bytes > self.max_bytes && self.entries.len() > 1 {
+                if let Some((_, evicted)) = self.entries.pop_lru() {
+                    self.bytes -= texture_bytes(&evicted);
+                }
+            }
+        }
+
+        Ok(self.entries.get_mut(&key).expect("entry was just inserted"))
+    }
+}
+
+/// Renders `text` in one surface, switching to the fallback fonts for the characters the
+/// main font has no glyph for.
+fn render_runs(text: &str, font: &FontHandle<'_>, color: Rgb) -> Result<Surface<'static>> {
+    let runs = font.runs(text);
+
+    if let [(font, run)] = runs[..] {
+        return font.render(run).blended(sdl_color(color)).map_err(error::draw);
+    }
+
+    let ascent = runs.iter().map(|(font, _)| font.ascent()).max().unwrap_or(0);
+    let surfaces = runs
+        .iter()
+        .map(|(font, run)| {
+            let mut surface = font.render(run).blended(sdl_color(color)).map_err(error::draw)?;
+            // copy the pixels as they are, alpha included, the runs don't overlap
+            surface.set_blend_mode(BlendMode::None).map_err(error::draw)?;
+            Ok((ascent - font.ascent(), surface))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let width = surfaces.iter().map(|(_, s)| s.width()).sum::<u32>().max(1);
+    let height = surfaces.iter().map(|(y, s)| *y as u32 + s.height()).max().unwrap_or(1).max(1);
+
+    let mut line = Surface::new(width, height, PixelFormatEnum::ARGB8888).map_err(error::draw)?;
+    let mut x = 0;
+
+    for (y, surface) in surfaces {
+        let dst = SdlRect::new(x, y, surface.width(), surface.height());
+        surface.blit(None, &mut line, dst).map_err(error::draw)?;
+        x += surface.width() as i32;
+    }
+
+    Ok(line)
+}
+
+fn texture_bytes(texture: &Texture<'_>) -> usize {
+    let query = texture.query();
+    query.width as usize * query.height as usize * 4
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Glyph {
+    src: SdlRect,
+    advance: u32,
+}
+
+/// Every glyph used so far, packed in shelves into a single texture.
+///
+/// The prompt changes on every keystroke, so caching it as whole strings would only fill
+/// the [`TextCache`] with prefixes of the query. Glyphs are rasterized in white and tinted
+/// with the texture colour modulation when drawn.
+pub struct GlyphAtlas<'a> {
+    texture: Texture<'a>,
+    glyphs: HashMap<(FontKey, char), Glyph>,
+    shelf_x: u32,
+    shelf_y: u32,
+    shelf_height: u32,
+}
+
+impl<'a> GlyphAtlas<'a> {
+    const SIZE: u32 = 1024;
+
+    pub fn new<C>(creator: &'a TextureCreator<C>) -> Result<Self> {
+        let mut texture = creator
+            .create_texture_static(PixelFormatEnum::ARGB8888, Self::SIZE, Self::SIZE)
+            .map_err(error::draw)?;
+        texture.set_blend_mode(BlendMode::Blend);
+
+        Ok(Self {
+            texture,
+            glyphs: HashMap::new(),
+            shelf_x: 0,
+            shelf_y: 0,
+            shelf_height: 0,
+        })
+    }
+
+    /// Draws `text` with its top left corner at (`x`, `y`), without going past `max_x`.
+    /// The alpha of `color` applies to the whole string. Returns the drawn width.
+    pub fn draw<T: RenderTarget>(
+        &mut self,
+        canvas: &mut Canvas<T>,
+        text: &str,
+        font: &FontHandle<'_>,
+        color: Color,
+        (x, y): (i32, i32),
+        max_x: i32,
+    ) -> Result<u32> {
+        let mut pen = x;
+
+        for ch in text.chars() {
+            let glyph = self.glyph(font, ch)?;
+            let visible = (max_x - pen).clamp(0, glyph.src.width() as i32) as u32;
+
+            if visible == 0 {
+                break;
+            }
+
+            let src = SdlRect::new(glyph.src.x(), glyph.src.y(), visible, glyph.src.height());
+            let dst = SdlRect::new(pen, y, visible, glyph.src.height());
+
+            self.texture.set_color_mod(color.r, color.g, color.b);
+            self.texture.set_alpha_mod(color.a);
+            canvas.copy(&self.texture, Some(src), Some(dst)).map_err(error::draw)?;
+
+            pen += glyph.advance as i32;
+        }
+
+        Ok((pen - x).min(max_x - x).max(0) as u32)
+    }
+
+    /// Width of `text` as [`draw`](Self::draw) would lay it out.
+    pub fn measure(&mut self, text: &str, font: &FontHandle<'_>) -> Result<u32> {
+        text.chars().try_fold(0, |width, ch| Ok(width + self.glyph(font, ch)?.advance))
+    }
+
+    fn glyph(&mut self, font: &FontHandle<'_>, ch: char) -> Result<Glyph> {
+        let (key, font) = font.for_char(ch);
+
+        if let Some(glyph) = self.glyphs.get(&(key, ch)) {
+            return Ok(*glyph);
+        }
+
+        let surface = font
+            .render_char(ch)
+            .blended(Color::WHITE)
+            .map_err(error::draw)?
+            .convert_format(PixelFormatEnum::ARGB8888)
+            .map_err(error::draw)?;
+
+        let (width, height) = (surface.width(), surface.height());
+        // the metrics lookup takes a 16 bit char, beyond that it would measure another one
+        let advance = match ch {
+            '\0'..='\u{FFFF}' => font.find_glyph_metrics(ch).map_or(width, |m| m.advance.max(0) as u32),
+            _ => width,
+        };
+
+        if width > Self::SIZE || height > Self::SIZE {
+            return Err(error::draw(format!("glyph '{ch}' is larger than the atlas")));
+        }
+
+        if self.shelf_x + width > Self::SIZE {
+            self.shelf_x = 0;
+            self.shelf_y += self.shelf_height;
+            self.shelf_height = 0;
+        }
+
+        if self.shelf_y + height > Self::SIZE {
+            // the atlas is full, start over empty: glyphs already drawn this frame were copied
+            // before their pixels get overwritten, and any still needed are rasterized again
+            self.glyphs.clear();
+            self.shelf_x = 0;
+            self.shelf_y = 0;
+            self.shelf_height = 0;
+        }
+
+        let src = SdlRect::new(self.shelf_x as i32, self.shelf_y as i32, width, height);
+        let pitch = surface.pitch() as usize;
+
+        surface
+            .with_lock(|pixels| self.texture.update(src, pixels, pitch))
+            .map_err(error::draw)?;
+
+        self.shelf_x += width;
+        self.shelf_height = self.shelf_height.max(height);
+
+        let glyph = Glyph { src, advance };
+        self.glyphs.insert((key, ch), glyph);
+
+        Ok(glyph)
+    }
+}
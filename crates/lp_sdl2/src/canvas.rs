@@ -0,0 +1,363 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect as SdlRect;
+use sdl2::render::{BlendMode, Canvas, RenderTarget, TextureCreator};
+use sdl2::surface::{Surface, SurfaceContext};
+use sdl2::video::{Window, WindowContext};
+use config::Rgb;
+use crate::cache::{GlyphAtlas, TextCache};
+use crate::elide::{self, Elide, Label};
+use crate::icon::IconCache;
+use crate::layout::{Align, Axis, Insets, Rect, Size};
+use crate::font::FontHandle;
+use crate::{error, Result};
+
+/// What the UI is drawn to: the window, or an offscreen surface when rendering headless.
+pub trait Output: RenderTarget + Sized {
+    /// Context of the texture creator, the same as the render target one.
+    type TextureContext;
+
+    fn texture_creator(canvas: &Canvas<Self>) -> TextureCreator<Self::TextureContext>;
+
+    fn set_opacity(canvas: &mut Canvas<Self>, opacity: f32);
+
+    /// Drawable pixels per logical pixel, above 1 on high-DPI displays. `None` when there's
+    /// no display whose scale to follow.
+    fn backing_scale(canvas: &Canvas<Self>) -> Option<f32>;
+}
+
+impl Output for Window {
+    type TextureContext = WindowContext;
+
+    fn texture_creator(canvas: &Canvas<Self>) -> TextureCreator<Self::TextureContext> {
+        canvas.texture_creator()
+    }
+
+    fn set_opacity(canvas: &mut Canvas<Self>, opacity: f32) {
+        // not every window manager supports opacity, the fade-in is just skipped there
+        let _ = canvas.window_mut().set_opacity(opacity);
+    }
+
+    fn backing_scale(canvas: &Canvas<Self>) -> Option<f32> {
+        let (logical, _) = canvas.window().size();
+        let drawable = canvas.output_size().map_or(logical, |(width, _)| width);
+
+        Some(drawable as f32 / logical.max(1) as f32)
+    }
+}
+
+impl Output for Surface<'static> {
+    type TextureContext = SurfaceContext<'static>;
+
+    fn texture_creator(canvas: &Canvas<Self>) -> TextureCreator<Self::TextureContext> {
+        canvas.texture_creator()
+    }
+
+    /// A surface has no opacity of its own, it's always drawn fully shown.
+    fn set_opacity(_: &mut Canvas<Self>, _: f32) {}
+
+    fn backing_scale(_: &Canvas<Self>) -> Option<f32> {
+        None
+    }
+}
+
+/// The canvas along with the caches of what has been rendered to it, as textures made by
+/// `creator`, which has to be the one of the canvas.
+pub struct Target<'a, T: Output> {
+    canvas: Canvas<T>,
+    text: TextCache<'a, T::TextureContext>,
+    glyphs: GlyphAtlas<'a>,
+    icons: IconCache<'a, T::TextureContext>,
+}
+
+impl<'a, T: Output> Target<'a, T> {
+    pub fn new(
+        canvas: Canvas<T>,
+        creator: &'a TextureCreator<T::TextureContext>,
+        icon_theme: Option<&str>,
+    ) -> Result<Self> {
+        Ok(Self {
+            canvas,
+            text: TextCache::new(creator),
+            glyphs: GlyphAtlas::new(creator)?,
+            icons: IconCache::new(creator, icon_theme),
+        })
+    }
+
+    pub fn canvas(&mut self) -> &mut Canvas<T> {
+        &mut self.canvas
+    }
+}
+
+/// A region of the window. Drawing through it is clipped to its area, so children can't
+/// spill over their siblings.
+pub struct VirtualCanvas<'a, T: Output> {
+    canvas: Rc<RefCell<Target<'a, T>>>,
+    area: Rect,
+    /// What's drawn is cut off outside of it. The area itself unless the canvas reaches
+    /// past its parent.
+    clip: Rect,
+    /// Opacity applied to everything drawn through this canvas.
+    alpha: u8,
+}
+
+impl<'a, T: Output> VirtualCanvas<'a, T> {
+    pub fn new(canvas: Rc<RefCell<Target<'a, T>>>, area: Rect) -> Self {
+        Self { canvas, area, clip: area, alpha: u8::MAX }
+    }
+
+    pub fn with_opacity(self, opacity: f64) -> Self {
+        Self {
+            alpha: (opacity.clamp(0., 1.) * u8::MAX as f64).round() as u8,
+            ..self
+        }
+    }
+
+    pub fn root(canvas: Rc<RefCell<Target<'a, T>>>) -> Self {
+        let v = canvas.borrow().canvas.viewport();
+
+        Self::new(canvas, Rect::new(v.x as _, v.y as _, v.width(), v.height()))
+    }
+
+    /// Canvas for a part of this one, `area` is clipped to fit.
+    pub fn child(&self, area: Rect) -> Self {
+        Self {
+            canvas: Rc::clone(&self.canvas),
+            area: self.area.intersect(area),
+            clip: self.clip.intersect(area),
+            alpha: self.alpha,
+        }
+    }
+
+    /// Canvas for `area`, which may reach past this one. Unlike with
+    /// [`child`](Self::child) the area keeps its size, what lies outside this canvas is
+    /// cut off when drawn instead.
+    pub fn overflowing(&self, area: Rect) -> Self {
+        Self {
+            canvas: Rc::clone(&self.canvas),
+            area,
+            clip: self.clip.intersect(area),
+            alpha: self.alpha,
+        }
+    }
+
+    pub fn split(&self, axis: Axis, gap: u32, sizes: &[Size]) -> Vec<Self> {
+        self.area
+            .split(axis, gap, sizes)
+            .into_iter()
+            .map(|area| self.child(area))
+            .collect()
+    }
+
+    /// Splits into children stacked top to bottom.
+    pub fn rows(&self, sizes: &[Size]) -> Vec<Self> {
+        self.split(Axis::Vertical, 0, sizes)
+    }
+
+    /// Splits into children placed left to right.
+    pub fn columns(&self, sizes: &[Size]) -> Vec<Self> {
+        self.split(Axis::Horizontal, 0, sizes)
+    }
+
+    pub fn padded(self, insets: Insets) -> Self {
+        Self {
+            area: self.area.shrink(insets),
+            ..self
+        }
+    }
+
+    pub fn fill_area(&mut self, color: Rgb, area: Rect) -> Result<()> {
+        self.fill_blended(color, area, self.alpha)
+    }
+
+    /// Like [`fill_area`](Self::fill_area) with the corners rounded off, their edges
+    /// antialiased.
+    pub fn fill_rounded(&mut self, color: Rgb, area: Rect, radius: u32) -> Result<()> {
+        let radius = radius.min(area.width() / 2).min(area.height() / 2);
+
+        if radius == 0 {
+            return self.fill_area(color, area);
+        }
+
+        let middle = Rect::new(area.x(), area.y() + radius, area.width(), area.height() - 2 * radius);
+        self.fill_area(color, middle)?;
+
+        let r = radius as f32;
+
+        for row in 0..radius {
+            // where the circle crosses the middle of the row, from the edge of the area
+            let dy = r - row as f32 - 0.5;
+            let inset = r - (r * r - dy * dy).max(0.).sqrt();
+            let full = inset.ceil() as u32;
+            let coverage = full as f32 - inset;
+            let edge_alpha = (self.alpha as f32 * coverage).round() as u8;
+
+            for y in [area.y() + row, area.bottom() - 1 - row] {
+                self.fill_area(color, Rect::new(area.x() + full, y, area.width() - 2 * full, 1))?;
+
+                if full > 0 && edge_alpha > 0 {
+                    self.fill_blended(color, Rect::new(area.x() + full - 1, y, 1, 1), edge_alpha)?;
+                    self.fill_blended(color, Rect::new(area.right() - full, y, 1, 1), edge_alpha)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_blended(&mut self, color: Rgb, area: Rect, alpha: u8) -> Result<()> {
+        let area = self.clip.intersect(area);
+
+        if area.is_empty() {
+            return Ok(());
+        }
+
+        let canvas = &mut self.canvas.borrow_mut().canvas;
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(color.red(), color.green(), color.blue(), alpha));
+        canvas.fill_rect(Some(area.sdl())).map_err(error::draw)?;
+        Ok(())
+    }
+
+    /// Draws a string through the text cache, suited for text that stays the same across
+    /// frames.
+    pub fn write_text(&mut self, content: &str, font: &FontHandle<'_>, color: Rgb, area: Rect) -> Result<Rect> {
+        self.write_text_aligned(content, font, color, area, Align::Start)
+    }
+
+    /// Like [`write_text`](Self::write_text), placing the text horizontally within `area`.
+    pub fn write_text_aligned(
+        &mut self,
+        content: &str,
+        font: &FontHandle<'_>,
+        color: Rgb,
+        area: Rect,
+        align: Align,
+    ) -> Result<Rect> {
+        let area = self.area.intersect(area);
+        let clip = self.clip.intersect(area);
+
+        if clip.is_empty() || content.is_empty() {
+            return Ok(area.with_size(0, area.height()));
+        }
+
+        let label = Label { text: content, elide: Elide::End, highlighted: &[] };
+        let elided = elide::elide(&label, area.width(), &mut |text| font.width(text))?;
+        let content = elided.text.as_str();
+
+        let mut target = self.canvas.borrow_mut();
+        let Target { canvas, text, .. } = &mut *target;
+
+        let texture = text.get_or_render(content, font, color)?;
+        texture.set_alpha_mod(self.alpha);
+        let query = texture.query();
+
+        let dst = area.align(query.width, query.height, align, Align::Center);
+
+        canvas.set_clip_rect(Some(clip.sdl()));
+        let copied = canvas.copy(
+            texture,
+            Some(SdlRect::new(0, 0, dst.width(), dst.height())),
+            Some(dst.sdl()),
+        );
+        canvas.set_clip_rect(None);
+        copied.map_err(error::draw)?;
+
+        Ok(dst)
+    }
+
+    /// Draws a label through the text cache, shortened to fit `area` and with its
+    /// highlighted characters in `highlight`.
+    pub fn write_label(&mut self, label: &Label, font: &FontHandle<'_>, color: Rgb, highlight: Rgb, area: Rect) -> Result<Rect> {
+        let area = self.area.intersect(area);
+        let elided = elide::elide(label, area.width(), &mut |text| font.width(text))?;
+
+        // runs of characters in the same colour, each drawn right after the previous one
+        let mut spans: Vec<(bool, String)> = Vec::new();
+
+        for (i, ch) in elided.text.chars().enumerate() {
+            let highlighted = elided.highlighted.binary_search(&i).is_ok();
+
+            match spans.last_mut() {
+                Some((last, span)) if *last == highlighted => span.push(ch),
+                _ => spans.push((highlighted, ch.to_string())),
+            }
+        }
+
+        let mut rest = area;
+
+        for (highlighted, span) in spans {
+            let color = if highlighted { highlight } else { color };
+            let drawn = self.write_text(&span, font, color, rest)?;
+            rest = rest.cutoff_x(drawn.right().saturating_sub(rest.x()));
+        }
+
+        Ok(area.with_size(rest.x() - area.x(), area.height()))
+    }
+
+    /// Draws a string glyph by glyph from the atlas, suited for text that changes often.
+    pub fn write_glyphs(&mut self, content: &str, font: &FontHandle<'_>, color: Rgb, area: Rect) -> Result<Rect> {
+        let area = self.area.intersect(area);
+        let clip = self.clip.intersect(area);
+
+        let height = font.font().height().max(0) as u32;
+        let line = area.align(area.width(), height, Align::Start, Align::Center);
+
+        if clip.is_empty() {
+            return Ok(line.with_size(0, line.height()));
+        }
+
+        let mut target = self.canvas.borrow_mut();
+        let Target { canvas, glyphs, .. } = &mut *target;
+
+        canvas.set_clip_rect(Some(clip.sdl()));
+        let color = Color::RGBA(color.red(), color.green(), color.blue(), self.alpha);
+        let drawn = glyphs.draw(canvas, content, font, color, (line.x_signed(), line.y_signed()), area.right() as i32);
+        canvas.set_clip_rect(None);
+
+        Ok(line.with_size(drawn?, line.height()))
+    }
+
+    /// Width of a string drawn by [`write_glyphs`](Self::write_glyphs).
+    pub fn measure_glyphs(&self, content: &str, font: &FontHandle<'_>) -> Result<u32> {
+        self.canvas.borrow_mut().glyphs.measure(content, font)
+    }
+
+    /// Draws the icon centered in `area`, returns whether the icon was found.
+    pub fn draw_icon(&mut self, name: &str, size: u32, area: Rect) -> Result<bool> {
+        let area = self.area.intersect(area);
+        let clip = self.clip.intersect(area);
+
+        // nothing of it would show, whether it exists doesn't matter
+        if clip.is_empty() {
+            return Ok(true);
+        }
+
+        let mut target = self.canvas.borrow_mut();
+        let Target { canvas, icons, .. } = &mut *target;
+
+        let Some(texture) = icons.get(name, size) else {
+            return Ok(false);
+        };
+
+        texture.set_alpha_mod(self.alpha);
+        let query = texture.query();
+
+        // larger images are scaled down to the requested size, keeping their aspect ratio
+        let longest = query.width.max(query.height).max(1);
+        let scaled = |n: u32| (n as u64 * size.min(longest) as u64 / longest as u64) as u32;
+        let dst = area.align(scaled(query.width), scaled(query.height), Align::Center, Align::Center);
+
+        canvas.set_clip_rect(Some(clip.sdl()));
+        let copied = canvas.copy(texture, None, Some(dst.sdl()));
+        canvas.set_clip_rect(None);
+        copied.map_err(error::draw)?;
+
+        Ok(true)
+    }
+
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+}
@@ -0,0 +1,113 @@
+use std::path::PathBuf;
+use clap::{Parser, Subcommand};
+use config::Length;
+use crate::ipc::Request;
+
+/// Application launcher for Linux
+#[derive(Debug, Parser)]
+#[command(name = "launchpad", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub action: Option<Action>,
+    /// Configuration file to use instead of `$XDG_CONFIG_HOME/launchpad/config.toml`
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Source to start in
+    #[arg(long, value_name = "NAME")]
+    pub mode: Option<String>,
+    /// Built-in theme or a theme file from `<config dir>/themes`
+    #[arg(long, value_name = "NAME")]
+    pub theme: Option<String>,
+    /// Window width in pixels, or a percentage of the display like 60%
+    #[arg(long)]
+    pub width: Option<Length>,
+    /// Window height in pixels, or a percentage of the display like 60%
+    #[arg(long)]
+    pub height: Option<Length>,
+    /// Maximum number of results shown
+    #[arg(long)]
+    pub lines: Option<u32>,
+    /// Initial text of the prompt
+    #[arg(long, value_name = "TEXT")]
+    pub query: Option<String>,
+    /// Print the chosen item to stdout instead of running it
+    #[arg(long)]
+    pub print: bool,
+    /// Print the effective configuration and exit
+    #[arg(long, conflicts_with = "check_config")]
+    pub dump_config: bool,
+    /// Validate the configuration and exit, non-zero if it has errors
+    #[arg(long)]
+    pub check_config: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Action {
+    /// Stay resident with the window hidden, for the other commands to show it instantly
+    Daemon,
+    /// Show the window of the running daemon
+    Show {
+        /// Source to show
+        #[arg(long, value_name = "NAME")]
+        mode: Option<String>,
+        /// Initial text of the prompt
+        #[arg(long, value_name = "TEXT")]
+        query: Option<String>,
+    },
+    /// Hide the window of the running daemon
+    Hide,
+    /// Show the window of the running daemon, or hide it if it's shown
+    Toggle {
+        /// Source to show
+        #[arg(long, value_name = "NAME")]
+        mode: Option<String>,
+    },
+    /// Stop the running daemon
+    Quit,
+}
+
+impl Action {
+    /// What to ask the running daemon, `None` for the daemon itself.
+    pub fn request(&self) -> Option<Request> {
+        match self {
+            Action::Daemon => None,
+            Action::Show { mode, query } => Some(Request::Show { mode: mode.clone(), query: query.clone() }),
+            Action::Hide => Some(Request::Hide),
+            Action::Toggle { mode } => Some(Request::Toggle { mode: mode.clone() }),
+            Action::Quit => Some(Request::Quit),
+        }
+    }
+}
+
+impl Cli {
+    /// Configuration builder with the command-line overrides applied.
+    pub fn config_builder(&self) -> config::Builder {
+        let mut builder = config::Config::builder();
+
+        if let Some(path) = &self.config {
+            builder = builder.file(path);
+        }
+
+        if let Some(mode) = &self.mode {
+            builder = builder.set("mode", mode.as_str());
+        }
+
+        if let Some(theme) = &self.theme {
+            builder = builder.set("theme", theme.as_str());
+        }
+
+        if let Some(width) = self.width {
+            builder = builder.set("window.width", width.to_string());
+        }
+
+        if let Some(height) = self.height {
+            builder = builder.set("window.height", height.to_string());
+        }
+
+        if let Some(lines) = self.lines {
+            builder = builder.set("layout.lines", lines);
+        }
+
+        builder
+    }
+}
@@ -0,0 +1,44 @@
+//! Copying to the clipboard through the tools of the session.
+//!
+//! The launcher exits right after, which would take an X11 selection it owns with it, so
+//! the text is handed to a tool that stays around to serve it.
+
+use std::env;
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+use crate::{error, Error, Result};
+
+/// Tools to try on Wayland, then on X11, with their arguments.
+const WAYLAND: &[&[&str]] = &[&["wl-copy"]];
+const X11: &[&[&str]] = &[&["xclip", "-selection", "clipboard"], &["xsel", "--clipboard", "--input"]];
+
+pub fn copy(text: &str) -> Result<()> {
+    let wayland = env::var_os("WAYLAND_DISPLAY").is_some_and(|v| !v.is_empty());
+    let tools = if wayland { WAYLAND.iter().chain(X11) } else { X11.iter().chain(WAYLAND) };
+
+    for tool in tools {
+        let spawned = Command::new(tool[0])
+            .args(&tool[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn();
+
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(error::launch(tool[0], e)),
+        };
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let written = stdin.write_all(text.as_bytes());
+        drop(stdin);
+
+        // the tools fork to keep serving the text, the first process ends once it's read
+        let status = child.wait();
+        written.and(status).map_err(|e| error::launch(tool[0], e))?;
+
+        return Ok(());
+    }
+
+    Err(Error::NoClipboard)
+}
@@ -0,0 +1,418 @@
+//! Applications from their desktop entries, following the freedesktop Desktop Entry
+//! specification.
+
+use std::collections::HashSet;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use crate::xdg;
+
+/// An application that can be shown and launched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesktopEntry {
+    /// Desktop file ID, like `org.gnome.Nautilus.desktop`.
+    pub id: String,
+    pub path: PathBuf,
+    pub name: String,
+    pub generic_name: Option<String>,
+    pub comment: Option<String>,
+    pub exec: String,
+    pub icon: Option<String>,
+    /// Runs in a terminal.
+    pub terminal: bool,
+    /// MIME types of the files it opens.
+    pub mime_types: Vec<String>,
+}
+
+impl DesktopEntry {
+    /// The application described by `text`, `None` when it isn't an application or it
+    /// shouldn't be shown.
+    pub fn parse(id: &str, path: &Path, text: &str) -> Option<Self> {
+        Self::read(id, path, text, true)
+    }
+
+    /// The application described by `text`, also when it isn't shown in menus and only
+    /// there to open files.
+    pub fn parse_handler(id: &str, path: &Path, text: &str) -> Option<Self> {
+        Self::read(id, path, text, false)
+    }
+
+    fn read(id: &str, path: &Path, text: &str, shown: bool) -> Option<Self> {
+        let locales = locales();
+        let desktops = current_desktops();
+        let group = main_group(text);
+
+        let value = |key: &str| group.iter().find(|(k, _)| *k == key).map(|(_, v)| unescape(v));
+        let localized = |key: &str| {
+            locales
+                .iter()
+                .find_map(|locale| value(&format!("{key}[{locale}]")))
+                .or_else(|| value(key))
+        };
+        let flag = |key: &str| value(key).is_some_and(|v| v == "true");
+        let listed = |key: &str| {
+            value(key).is_some_and(|v| v.split(';').any(|d| desktops.iter().any(|current| current == d)))
+        };
+
+        if value("Type").as_deref() != Some("Application") || flag("Hidden") {
+            return None;
+        }
+
+        let in_menus = !flag("NoDisplay") && (value("OnlyShowIn").is_none() || listed("OnlyShowIn")) && !listed("NotShowIn");
+
+        if shown && !in_menus {
+            return None;
+        }
+
+        if value("TryExec").is_some_and(|program| !is_installed(&program)) {
+            return None;
+        }
+
+        Some(Self {
+            id: id.to_owned(),
+            path: path.to_owned(),
+            name: localized("Name")?,
+            generic_name: localized("GenericName"),
+            comment: localized("Comment"),
+            exec: value("Exec")?,
+            icon: value("Icon").filter(|icon| !icon.is_empty()),
+            terminal: flag("Terminal"),
+            mime_types: value("MimeType")
+                .map(|types| types.split(';').filter(|t| !t.is_empty()).map(str::to_owned).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Shell command line launching the application without any files.
+    pub fn command_line(&self) -> Option<String> {
+        self.command_line_with(None)
+    }
+
+    /// Shell command line launching the application, opening `file` if there is one.
+    pub fn command_line_with(&self, file: Option<&Path>) -> Option<String> {
+        let mut args = Vec::new();
+
+        for arg in split_exec(&self.exec)? {
+            match arg.as_str() {
+                "%f" | "%F" => args.extend(file.map(|file| file.to_string_lossy().into_owned())),
+                "%u" | "%U" => args.extend(file.map(uri)),
+                "%i" => args.extend(self.icon.iter().flat_map(|icon| ["--icon".to_owned(), icon.clone()])),
+                _ => args.push(expand_field_codes(&arg, self, file)),
+            }
+        }
+
+        if args.is_empty() {
+            return None;
+        }
+
+        let command = args.iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(" ");
+
+        Some(if self.terminal {
+            format!("\"${{TERMINAL:-xterm}}\" -e {command}")
+        } else {
+            command
+        })
+    }
+}
+
+/// Every application installed, sorted by name. Entries in the user's data directory
+/// shadow the system ones with the same ID.
+pub fn applications() -> Vec<DesktopEntry> {
+    let mut entries = entries(DesktopEntry::parse);
+    entries.sort_by_cached_key(|entry| entry.name.to_lowercase());
+    entries
+}
+
+/// Every application that can open files, also those not shown in menus.
+pub fn handlers() -> Vec<DesktopEntry> {
+    entries(DesktopEntry::parse_handler)
+}
+
+/// The entries of every data directory that `parse` accepts, the first of each ID.
+fn entries(parse: fn(&str, &Path, &str) -> Option<DesktopEntry>) -> Vec<DesktopEntry> {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+
+    for dir in xdg::data_paths() {
+        let root = dir.join("applications");
+
+        for (id, path) in desktop_files(&root) {
+            // a hidden entry still shadows the ones after it
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+
+            entries.extend(parse(&id, &path, &text));
+        }
+    }
+
+    entries
+}
+
+/// Desktop files below `root` with their IDs, the path relative to `root` with slashes
+/// turned into dashes.
+fn desktop_files(root: &Path) -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_owned()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                pending.push(path);
+            } else if path.extension().is_some_and(|e| e == "desktop") {
+                let Ok(relative) = path.strip_prefix(root) else {
+                    continue;
+                };
+
+                let id = relative.to_string_lossy().replace('/', "-");
+                files.push((id, path));
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+/// Keys and raw values of the `[Desktop Entry]` group.
+fn main_group(text: &str) -> Vec<(&str, &str)> {
+    let mut in_group = false;
+    let mut pairs = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            in_group = line == "[Desktop Entry]";
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=').filter(|_| in_group) {
+            pairs.push((key.trim_end(), value.trim_start()));
+        }
+    }
+
+    pairs
+}
+
+/// Resolves the escapes of string values.
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+/// The arguments of an `Exec` value, `None` when its quoting is broken.
+fn split_exec(exec: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut chars = exec.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let Some(&first) = chars.peek() else {
+            return Some(args);
+        };
+
+        let mut arg = String::new();
+
+        if first == '"' {
+            chars.next();
+
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => arg.push(chars.next()?),
+                    c => arg.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                arg.push(c);
+            }
+        }
+
+        args.push(arg);
+    }
+}
+
+/// Replaces the field codes within an argument. Codes for files stand for `file`, or are
+/// dropped without one.
+fn expand_field_codes(arg: &str, entry: &DesktopEntry, file: Option<&Path>) -> String {
+    let mut result = String::new();
+    let mut chars = arg.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('%') => result.push('%'),
+            Some('c') => result.push_str(&entry.name),
+            Some('k') => result.push_str(&entry.path.to_string_lossy()),
+            Some('f' | 'F') => result.extend(file.map(|file| file.to_string_lossy())),
+            Some('u' | 'U') => result.extend(file.map(uri)),
+            _ => {}
+        }
+    }
+
+    result
+}
+
+/// The `file://` URI of `path`, with what isn't allowed in one percent-encoded.
+pub fn uri(path: &Path) -> String {
+    let mut uri = "file://".to_owned();
+
+    for byte in path.as_os_str().as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => uri.push(*byte as char),
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    uri
+}
+
+/// The local path a `file://` URI stands for, `None` for other URIs and those of files on
+/// other hosts.
+pub fn path_from_uri(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let (host, _) = rest.split_at(rest.find('/')?);
+
+    if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") && Some(host) != hostname().as_deref() {
+        return None;
+    }
+
+    let encoded = &rest.as_bytes()[host.len()..];
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+
+    while i < encoded.len() {
+        let hex = encoded.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+
+        match (encoded[i], hex) {
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                bytes.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    Some(PathBuf::from(OsString::from_vec(bytes)))
+}
+
+/// The name of this host.
+fn hostname() -> Option<String> {
+    let mut name = [0u8; 256];
+    // SAFETY: the buffer is valid for its length, which leaves room for the terminating nul
+    let result = unsafe { libc::gethostname(name.as_mut_ptr().cast(), name.len() - 1) };
+    let length = name.iter().position(|byte| *byte == 0)?;
+
+    (result == 0).then(|| String::from_utf8_lossy(&name[..length]).into_owned())
+}
+
+/// `arg` quoted for the shell where needed.
+pub fn quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c);
+
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Locale names to look for in localized keys, most specific first.
+fn locales() -> Vec<String> {
+    let Some(locale) = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|var| env::var(var).ok().filter(|v| !v.is_empty()))
+    else {
+        return Vec::new();
+    };
+
+    // lang_COUNTRY.ENCODING@MODIFIER, the encoding never appears in keys
+    let (locale, modifier) = match locale.split_once('@') {
+        Some((locale, modifier)) => (locale.to_owned(), Some(modifier.to_owned())),
+        None => (locale, None),
+    };
+    let locale = locale.split('.').next().unwrap_or_default();
+    let lang = locale.split('_').next().unwrap_or_default();
+
+    let mut names = Vec::new();
+
+    if let Some(modifier) = &modifier {
+        names.push(format!("{locale}@{modifier}"));
+    }
+
+    names.push(locale.to_owned());
+
+    if let Some(modifier) = &modifier {
+        names.push(format!("{lang}@{modifier}"));
+    }
+
+    names.push(lang.to_owned());
+    names.dedup();
+    names.retain(|name| !name.is_empty() && name != "C" && name != "POSIX");
+    names
+}
+
+pub fn current_desktops() -> Vec<String> {
+    env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|d| !d.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Whether `program` is an absolute path or found on `$PATH`.
+pub fn is_installed(program: &str) -> bool {
+    let path = Path::new(program);
+
+    if path.is_absolute() {
+        return path.is_file();
+    }
+
+    env::var_os("PATH").is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,44 @@
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use super::{hostname, path_from_uri, uri};
+
+#[test]
+fn encodes_what_a_uri_does_not_allow() {
+    assert_eq!(uri(Path::new("/home/user/notes.txt")), "file:///home/user/notes.txt");
+    assert_eq!(uri(Path::new("/home/user/Tom & Jerry #1.txt")), "file:///home/user/Tom%20%26%20Jerry%20%231.txt");
+    assert_eq!(uri(Path::new("/tmp/café")), "file:///tmp/caf%C3%A9");
+}
+
+#[test]
+fn decodes_local_paths() {
+    let path = |uri: &str| path_from_uri(uri).map(|path| path.display().to_string());
+
+    assert_eq!(path("file:///home/user/report%20final.odt").as_deref(), Some("/home/user/report final.odt"));
+    assert_eq!(path("file:///tmp/caf%C3%A9").as_deref(), Some("/tmp/café"));
+    assert_eq!(path("file:///tmp/100%").as_deref(), Some("/tmp/100%"));
+    assert_eq!(path("file:///tmp/50%zz").as_deref(), Some("/tmp/50%zz"));
+    assert_eq!(path("file://localhost/etc/hosts").as_deref(), Some("/etc/hosts"));
+    assert_eq!(path("file://elsewhere.example.com/etc/hosts"), None);
+    assert_eq!(path("file://localhost"), None);
+    assert_eq!(path("https://example.com/index.html"), None);
+
+    if let Some(host) = hostname() {
+        assert_eq!(path(&format!("file://{host}/etc/hosts")).as_deref(), Some("/etc/hosts"));
+    }
+}
+
+#[test]
+fn keeps_names_that_are_not_utf8() {
+    let name = OsStr::from_bytes(b"/tmp/caf\xe9.txt");
+
+    assert_eq!(path_from_uri("file:///tmp/caf%E9.txt"), Some(PathBuf::from(name)));
+    assert_eq!(uri(Path::new(name)), "file:///tmp/caf%E9.txt");
+}
+
+#[test]
+fn round_trips_paths() {
+    for path in ["/", "/home/user/a b/c%d", "/tmp/100% [draft] (v2)?.md", "/tmp/ünïcödé/日本語.txt", "/tmp/a\nb"] {
+        assert_eq!(path_from_uri(&uri(Path::new(path))), Some(PathBuf::from(path)), "{path}");
+    }
+}
@@ -0,0 +1,212 @@
+//! Shortening text to a width with an ellipsis, keeping the highlighted characters in view.
+
+use crate::Result;
+
+const ELLIPSIS: char = '…';
+
+/// Where text that doesn't fit is cut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Elide {
+    /// Cuts off the end, for names and prose.
+    #[default]
+    End,
+    /// Cuts out the middle and keeps the last component, for paths.
+    Middle,
+}
+
+impl Elide {
+    /// Middle for what looks like a path, end otherwise.
+    pub fn for_text(text: &str) -> Self {
+        if text.starts_with('/') || text.starts_with("~/") {
+            Elide::Middle
+        } else {
+            Elide::End
+        }
+    }
+}
+
+/// Text to draw on one line, along with the characters to draw in the highlight colour.
+#[derive(Debug, Clone, Copy)]
+pub struct Label<'a> {
+    pub text: &'a str,
+    pub elide: Elide,
+    /// Positions of the highlighted characters, like the ones the query matched.
+    pub highlighted: &'a [usize],
+}
+
+impl<'a> Label<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self { text, elide: Elide::for_text(text), highlighted: &[] }
+    }
+}
+
+/// What's left of a label after shortening it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elided {
+    pub text: String,
+    /// Positions of the highlighted characters in the shortened text.
+    pub highlighted: Vec<usize>,
+}
+
+/// The parts of the text kept: from `start` to `mid`, then from `resume` to `end`, with
+/// ellipses in between and at either end where anything was cut.
+#[derive(Debug, Clone, Copy)]
+struct Cut {
+    start: usize,
+    mid: usize,
+    resume: usize,
+    end: usize,
+}
+
+impl Cut {
+    fn window(start: usize, end: usize) -> Self {
+        Self { start, mid: end, resume: end, end }
+    }
+}
+
+/// Shortens `label` to at most `width` as measured by `measure`, unless it already fits.
+pub fn elide(label: &Label, width: u32, measure: &mut dyn FnMut(&str) -> Result<u32>) -> Result<Elided> {
+    let chars = label.text.chars().collect::<Vec<_>>();
+    let mut highlighted = label.highlighted.iter().copied().filter(|i| *i < chars.len()).collect::<Vec<_>>();
+    highlighted.sort_unstable();
+    highlighted.dedup();
+
+    let full = Cut::window(0, chars.len());
+
+    if measure(label.text)? <= width {
+        return Ok(apply(&chars, full, &highlighted));
+    }
+
+    let mut fits = |cut: Cut| -> Result<bool> { Ok(measure(&apply(&chars, cut, &highlighted).text)? <= width) };
+
+    let cut = match label.elide {
+        Elide::Middle => match middle(&chars, &highlighted, &mut fits)? {
+            Some(cut) => cut,
+            None => end(chars.len(), &highlighted, &mut fits)?,
+        },
+        Elide::End => end(chars.len(), &highlighted, &mut fits)?,
+    };
+
+    Ok(apply(&chars, cut, &highlighted))
+}
+
+/// As much of the start as fits, or a window ending at the last highlighted character
+/// when that would be cut off.
+fn end(len: usize, highlighted: &[usize], fits: &mut dyn FnMut(Cut) -> Result<bool>) -> Result<Cut> {
+    let end = last_fitting(0, len, |end| fits(Cut::window(0, end)))?;
+
+    let Some(last) = highlighted.last().map(|last| last + 1).filter(|last| *last > end) else {
+        return Ok(Cut::window(0, end));
+    };
+
+    let start = first_fitting(0, last, |start| fits(Cut::window(start, last)))?;
+    let end = last_fitting(last, len, |end| fits(Cut::window(start, end)))?;
+
+    Ok(Cut::window(start, end))
+}
+
+/// The start and the last path component with the middle cut out, `None` when the last
+/// component doesn't fit on its own. The cut goes between highlighted characters if
+/// there's room for that anywhere.
+fn middle(chars: &[char], highlighted: &[usize], fits: &mut dyn FnMut(Cut) -> Result<bool>) -> Result<Option<Cut>> {
+    let len = chars.len();
+    let Some(name) = chars.iter().rposition(|c| *c == '/').filter(|i| *i > 0) else {
+        return Ok(None);
+    };
+
+    // gaps between the highlighted characters in front of the last component, widest first
+    let before = highlighted.iter().copied().filter(|i| *i < name).collect::<Vec<_>>();
+    let bounds = [0].into_iter().chain(before.iter().map(|i| i + 1));
+    let mut gaps = bounds
+        .zip(before.iter().copied().chain([name]))
+        .filter(|(mid, resume)| mid < resume)
+        .collect::<Vec<_>>();
+    gaps.sort_by_key(|(mid, resume)| std::cmp::Reverse(resume - mid));
+
+    for (mid, resume) in gaps {
+        let cut = |mid, resume| Cut { start: 0, mid, resume, end: len };
+
+        if !fits(cut(mid, resume))? {
+            continue;
+        }
+
+        let mid = last_fitting(mid, resume, |mid| fits(cut(mid, resume)))?;
+        let resume = first_fitting(mid, resume, |resume| fits(cut(mid, resume)))?;
+
+        return Ok(Some(cut(mid, resume)));
+    }
+
+    Ok(None)
+}
+
+/// The shown text of `cut`, with the highlighted positions moved along.
+fn apply(chars: &[char], cut: Cut, highlighted: &[usize]) -> Elided {
+    let mut text = String::new();
+    let mut moved = Vec::new();
+    let mut push = |range: std::ops::Range<usize>, text: &mut String| {
+        for i in range {
+            if highlighted.binary_search(&i).is_ok() {
+                moved.push(text.chars().count());
+            }
+
+            text.push(chars[i]);
+        }
+    };
+
+    if cut.start > 0 {
+        text.push(ELLIPSIS);
+    }
+
+    push(cut.start..cut.mid, &mut text);
+
+    if cut.mid < cut.resume {
+        text.push(ELLIPSIS);
+    }
+
+    push(cut.resume..cut.end, &mut text);
+
+    if cut.end < chars.len() {
+        text.push(ELLIPSIS);
+    }
+
+    Elided { text, highlighted: moved }
+}
+
+/// The largest value in `low..=high` for which `fits` holds, given that it holds up to
+/// some point and no further. `low` when it never holds.
+fn last_fitting(low: usize, high: usize, mut fits: impl FnMut(usize) -> Result<bool>) -> Result<usize> {
+    let (mut low, mut high) = (low, high);
+
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+
+        if fits(mid)? {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    Ok(low)
+}
+
+/// The smallest value in `low..=high` for which `fits` holds, given that it holds from
+/// some point on. `high` when it never holds.
+fn first_fitting(low: usize, high: usize, mut fits: impl FnMut(usize) -> Result<bool>) -> Result<usize> {
+    let (mut low, mut high) = (low, high);
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+
+        if fits(mid)? {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    Ok(low)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,57 @@
+use crate::Result;
+use super::{elide, Elide, Elided, Label};
+
+/// Every character is 10 pixels wide, the ellipsis included.
+fn measure(text: &str) -> Result<u32> {
+    Ok(text.chars().count() as u32 * 10)
+}
+
+fn elided(text: &str, how: Elide, highlighted: &[usize], width: u32) -> Elided {
+    let label = Label { text, elide: how, highlighted };
+    elide(&label, width, &mut measure).expect("measured")
+}
+
+fn expected(text: &str, highlighted: &[usize]) -> Elided {
+    Elided { text: text.to_owned(), highlighted: highlighted.to_vec() }
+}
+
+#[test]
+fn leaves_text_that_fits() {
+    assert_eq!(elided("firefox", Elide::End, &[0, 1], 70), expected("firefox", &[0, 1]));
+    assert_eq!(elided("/usr/bin/env", Elide::Middle, &[], 500), expected("/usr/bin/env", &[]));
+}
+
+#[test]
+fn cuts_off_the_end() {
+    assert_eq!(elided("hello wonderful world", Elide::End, &[0, 1], 100), expected("hello won…", &[0, 1]));
+}
+
+#[test]
+fn shifts_to_keep_a_highlight_past_the_cut_in_view() {
+    // the 'r' at 17 would be cut off at the end, the window ends at it instead
+    assert_eq!(elided("abcdefghijklmnopqrst", Elide::End, &[2, 17], 100), expected("…klmnopqr…", &[8]));
+}
+
+#[test]
+fn cuts_out_the_middle_of_paths_keeping_the_file_name() {
+    let path = "/home/user/projects/launcher/README.md";
+
+    assert_eq!(elided(path, Elide::Middle, &[], 200), expected("/home/use…/README.md", &[]));
+    // the cut goes in front of the highlighted 'l' of "launcher", not through it
+    assert_eq!(elided(path, Elide::Middle, &[20, 29], 200), expected("/…launcher/README.md", &[2, 11]));
+}
+
+#[test]
+fn cuts_off_the_end_of_file_names_too_long_to_keep() {
+    assert_eq!(
+        elided("/tmp/a-file-name-much-too-long.txt", Elide::Middle, &[], 100),
+        expected("/tmp/a-fi…", &[]),
+    );
+}
+
+#[test]
+fn elides_paths_in_the_middle() {
+    assert_eq!(Elide::for_text("/etc/hosts"), Elide::Middle);
+    assert_eq!(Elide::for_text("~/notes.txt"), Elide::Middle);
+    assert_eq!(Elide::for_text("Text Editor"), Elide::End);
+}
@@ -12,6 +12,8 @@ pub enum Subsystem {
     Video,
     #[display(fmt = "event pump")]
     EventPump,
+    #[display(fmt = "events")]
+    Events,
     #[display(fmt = "ttf")]
     Ttf,
 }
@@ -40,6 +42,26 @@ pub enum Error {
         kind: AssetKind,
         explanation: String,
     },
+    #[error("Unknown mode '{0}'")]
+    UnknownMode(String),
+    #[error("Failed to launch '{command}': {source}")]
+    Launch {
+        command: String,
+        source: std::io::Error,
+    },
+    #[error("No clipboard tool found, install wl-copy, xclip or xsel")]
+    NoClipboard,
+    #[error("Nothing to open '{}' with, install xdg-open or set a default application", .0.display())]
+    NoHandler(std::path::PathBuf),
+    #[error("The configuration has {0} error(s), it's left unused")]
+    InvalidConfig(usize),
+    #[error("Another launchpad daemon is already running")]
+    AlreadyRunning,
+    #[error("Failed to listen on '{}': {source}", path.display())]
+    Listen {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
 }
 
 impl Error {
@@ -115,6 +137,13 @@ pub(crate) fn system_event_pump(explanation: impl ToString) -> Error {
     }
 }
 
+pub(crate) fn system_event(explanation: impl ToString) -> Error {
+    Error::SystemInit {
+        subsystem: Subsystem::Events,
+        explanation: explanation.to_string(),
+    }
+}
+
 pub(crate) fn asset_font(explanation: impl ToString) -> Error {
     Error::AssetLoad {
         kind: AssetKind::Font,
@@ -130,3 +159,16 @@ pub(crate) fn draw(inner: impl ToString) -> Error {
     Error::Draw(inner.to_string())
 }
 
+pub(crate) fn launch(command: impl ToString, source: std::io::Error) -> Error {
+    Error::Launch {
+        command: command.to_string(),
+        source,
+    }
+}
+
+pub(crate) fn listen(path: impl Into<std::path::PathBuf>, source: std::io::Error) -> Error {
+    Error::Listen {
+        path: path.into(),
+        source,
+    }
+}
@@ -0,0 +1,226 @@
+//! Fonts at any size, with the fallbacks for characters the configured font lacks.
+//!
+//! The TTF context lives for the whole process, so fonts borrow it for `'static` and can be
+//! loaded and dropped at any time.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use sdl2::ttf::{Font, Sdl2TtfContext};
+use library::{Face, FontLibrary};
+use crate::layout::Scale;
+use crate::{error, Result};
+
+static TTF: OnceLock<Sdl2TtfContext> = OnceLock::new();
+
+fn ttf() -> Result<&'static Sdl2TtfContext> {
+    if let Some(ctx) = TTF.get() {
+        return Ok(ctx);
+    }
+
+    let ctx = sdl2::ttf::init().map_err(error::system_ttf)?;
+    Ok(TTF.get_or_init(|| ctx))
+}
+
+/// The configured font with its fallbacks, loaded at each size as it's asked for.
+pub struct Fonts {
+    ctx: &'static Sdl2TtfContext,
+    library: FontLibrary,
+    config: config::Font,
+    scale: Scale,
+    /// The main face then the fallbacks, in the order they're tried.
+    faces: Vec<Face>,
+    /// Changes on every reload, so that text rendered with the old fonts isn't reused.
+    generation: u32,
+    chains: RefCell<HashMap<u16, Rc<FontChain>>>,
+    /// Slot of the face each character seen so far is drawn with.
+    coverage: RefCell<HashMap<char, u16>>,
+}
+
+/// One font per face, all at the same size.
+struct FontChain {
+    size: u16,
+    fonts: Vec<Font<'static, 'static>>,
+}
+
+impl Fonts {
+    pub fn init(config: &config::Font, scale: Scale) -> Result<Self> {
+        let mut library = FontLibrary::system();
+        let faces = resolve(&mut library, config)?;
+
+        let fonts = Self {
+            ctx: ttf()?,
+            library,
+            config: config.clone(),
+            scale,
+            faces,
+            generation: 0,
+            chains: RefCell::new(HashMap::new()),
+            coverage: RefCell::new(HashMap::new()),
+        };
+
+        // the configured sizes are loaded up front, so that a broken font fails right away
+        for size in [config.prompt_size, config.title_size, config.subtitle_size] {
+            fonts.sized(scale.pt(size))?;
+        }
+
+        Ok(fonts)
+    }
+
+    /// Switches to a changed font configuration. On failure the current fonts stay.
+    pub fn reload(&mut self, config: &config::Font) -> Result<()> {
+        if *config == self.config {
+            return Ok(());
+        }
+
+        let mut reloaded = Self::init(config, self.scale)?;
+        reloaded.generation = self.generation.wrapping_add(1);
+        *self = reloaded;
+
+        Ok(())
+    }
+
+    /// Makes the configured sizes refer to the new scale, fonts at the new sizes are loaded
+    /// as they're asked for.
+    pub fn set_scale(&mut self, scale: Scale) {
+        self.scale = scale;
+    }
+
+    pub fn prompt(&self) -> Result<FontHandle<'_>> {
+        self.sized(self.scale.pt(self.config.prompt_size))
+    }
+
+    pub fn title(&self) -> Result<FontHandle<'_>> {
+        self.sized(self.scale.pt(self.config.title_size))
+    }
+
+    pub fn subtitle(&self) -> Result<FontHandle<'_>> {
+        self.sized(self.scale.pt(self.config.subtitle_size))
+    }
+
+    /// The fonts at `size` points on screen, loading them the first time.
+    pub fn sized(&self, size: u16) -> Result<FontHandle<'_>> {
+        if let Some(chain) = self.chains.borrow().get(&size) {
+            return Ok(FontHandle { fonts: self, chain: Rc::clone(chain) });
+        }
+
+        let fonts = self.faces
+            .iter()
+            .map(|face| self.ctx.load_font_at_index(&face.path, face.index, size).map_err(error::asset_font))
+            .collect::<Result<_>>()?;
+
+        let chain = Rc::new(FontChain { size, fonts });
+        self.chains.borrow_mut().insert(size, Rc::clone(&chain));
+
+        Ok(FontHandle { fonts: self, chain })
+    }
+
+    /// The first face that has a glyph for `ch`, the main one if none has.
+    fn slot(&self, ch: char) -> u16 {
+        *self.coverage.borrow_mut().entry(ch).or_insert_with(|| {
+            self.faces
+                .iter()
+                .position(|face| self.library.covers(face, ch))
+                .unwrap_or(0) as u16
+        })
+    }
+}
+
+/// The faces for the configured family and its fallbacks that are installed.
+fn resolve(library: &mut FontLibrary, config: &config::Font) -> Result<Vec<Face>> {
+    let mut faces = Vec::new();
+
+    for family in [&config.family].into_iter().chain(&config.fallback) {
+        if let Some(face) = library.find(family) {
+            if !faces.contains(&face) {
+                faces.push(face);
+            }
+        } else if family == &config.family {
+            eprintln!("warning: font '{family}' not found, using the default font instead");
+
+            faces.extend(library.find("sans-serif").or_else(|| library.any()));
+        }
+    }
+
+    if faces.is_empty() {
+        return Err(error::asset_font("no fonts found on the system"));
+    }
+
+    Ok(faces)
+}
+
+/// Identifies a loaded font, as the key of rendered text caches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontKey {
+    generation: u32,
+    slot: u16,
+    size: u16,
+}
+
+/// A font at one size, along with its fallbacks.
+#[derive(Clone)]
+pub struct FontHandle<'a> {
+    fonts: &'a Fonts,
+    chain: Rc<FontChain>,
+}
+
+impl FontHandle<'_> {
+    /// Key of the whole chain, the fallbacks used follow from the text.
+    pub fn key(&self) -> FontKey {
+        self.key_for(0)
+    }
+
+    /// The main font, for the metrics of a line.
+    pub fn font(&self) -> &Font<'static, 'static> {
+        &self.chain.fonts[0]
+    }
+
+    /// The font to draw `ch` with.
+    pub fn for_char(&self, ch: char) -> (FontKey, &Font<'static, 'static>) {
+        let slot = self.fonts.slot(ch);
+        (self.key_for(slot), &self.chain.fonts[slot as usize])
+    }
+
+    /// Splits `text` into runs drawn with the same font.
+    pub fn runs<'t>(&self, text: &'t str) -> Vec<(&Font<'static, 'static>, &'t str)> {
+        let mut runs = Vec::new();
+        let mut start = 0;
+        let mut current = None;
+
+        for (index, ch) in text.char_indices() {
+            let slot = self.fonts.slot(ch);
+
+            if let Some(previous) = current.filter(|previous| *previous != slot) {
+                runs.push((&self.chain.fonts[previous as usize], &text[start..index]));
+                start = index;
+            }
+
+            current = Some(slot);
+        }
+
+        if let Some(slot) = current {
+            runs.push((&self.chain.fonts[slot as usize], &text[start..]));
+        }
+
+        runs
+    }
+
+    /// Width of `text` rendered in one piece.
+    pub fn width(&self, text: &str) -> Result<u32> {
+        self.runs(text).into_iter().try_fold(0, |width, (font, run)| {
+            let (run_width, _) = font.size_of(run).map_err(error::draw)?;
+            Ok(width + run_width)
+        })
+    }
+
+    fn key_for(&self, slot: u16) -> FontKey {
+        FontKey {
+            generation: self.fonts.generation,
+            slot,
+            size: self.chain.size,
+        }
+    }
+}
+
+pub mod library;
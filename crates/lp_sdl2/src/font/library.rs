@@ -0,0 +1,87 @@
+//! Finding font files by family name, in the directories and with the generic family
+//! aliases of the fontconfig configuration.
+
+use std::path::{Path, PathBuf};
+use fontdb::{Database, Family, Query, Source, ID};
+
+/// Families tried after the generic family of the fontconfig configuration, which only
+/// names the first of its preferred families even when that one isn't installed.
+const SANS_SERIF: &[&str] = &["DejaVu Sans", "Noto Sans", "Liberation Sans", "Cantarell", "Ubuntu", "Arial"];
+const SERIF: &[&str] = &["DejaVu Serif", "Noto Serif", "Liberation Serif", "Times New Roman"];
+const MONOSPACE: &[&str] = &["DejaVu Sans Mono", "Noto Sans Mono", "Liberation Mono", "Courier New"];
+
+/// A face in a font file, files like `.ttc` hold several.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Face {
+    pub path: PathBuf,
+    pub index: u32,
+    id: ID,
+}
+
+/// The fonts installed on the system.
+pub struct FontLibrary {
+    db: Database,
+}
+
+impl FontLibrary {
+    pub fn system() -> Self {
+        let mut db = Database::new();
+        db.load_system_fonts();
+
+        Self { db }
+    }
+
+    /// Finds the regular face of `family`. Besides family names, `family` may be a generic
+    /// family like `sans-serif` or the path of a font file.
+    pub fn find(&mut self, family: &str) -> Option<Face> {
+        if Path::new(family).is_absolute() {
+            let id = *self.db.load_font_source(Source::File(family.into())).first()?;
+            return self.face(id);
+        }
+
+        let (generic, common) = match family.to_ascii_lowercase().as_str() {
+            "sans-serif" | "sans" => (Family::SansSerif, SANS_SERIF),
+            "serif" => (Family::Serif, SERIF),
+            "monospace" | "mono" => (Family::Monospace, MONOSPACE),
+            _ => (Family::Name(family), &[][..]),
+        };
+
+        let families = [generic]
+            .into_iter()
+            .chain(common.iter().map(|name| Family::Name(name)))
+            .collect::<Vec<_>>();
+
+        let id = self.db.query(&Query {
+            families: &families,
+            ..Query::default()
+        })?;
+
+        self.face(id)
+    }
+
+    /// Any face at all, for when not even the generic families resolve.
+    pub fn any(&self) -> Option<Face> {
+        self.db.faces().find_map(|info| self.face(info.id))
+    }
+
+    /// Whether the face has a glyph for `ch`.
+    pub fn covers(&self, face: &Face, ch: char) -> bool {
+        self.db
+            .with_face_data(face.id, |data, index| {
+                ttf_parser::Face::parse(data, index).is_ok_and(|face| face.glyph_index(ch).is_some())
+            })
+            .unwrap_or(false)
+    }
+
+    fn face(&self, id: ID) -> Option<Face> {
+        let (source, index) = self.db.face_source(id)?;
+
+        let path = match source {
+            Source::File(path) | Source::SharedFile(path, _) => path,
+            // only files are ever loaded, SDL_ttf couldn't open anything else
+            Source::Binary(_) => return None,
+        };
+
+        Some(Face { path, index, id })
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::{BlendMode, Texture, TextureCreator};
+use config::Rgb;
+use theme::IconLookup;
+
+/// Icon textures by name and size. Icons that can't be found or decoded are remembered as
+/// missing too, so that the lookup isn't repeated every frame.
+pub struct IconCache<'a, C> {
+    creator: &'a TextureCreator<C>,
+    lookup: IconLookup,
+    textures: HashMap<(String, u32), Option<Texture<'a>>>,
+}
+
+impl<'a, C> IconCache<'a, C> {
+    pub fn new(creator: &'a TextureCreator<C>, theme: Option<&str>) -> Self {
+        Self {
+            creator,
+            lookup: IconLookup::new(theme),
+            textures: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, name: &str, size: u32) -> Option<&mut Texture<'a>> {
+        let key = (name.to_owned(), size);
+
+        if !self.textures.contains_key(&key) {
+            let texture = self.lookup
+                .find(name, size, 1)
+                .and_then(|path| decode::load(&path, size).ok())
+                .and_then(|image| self.upload(&image));
+
+            self.textures.insert(key.clone(), texture);
+        }
+
+        self.textures.get_mut(&key).and_then(Option::as_mut)
+    }
+
+    fn upload(&self, image: &decode::Image) -> Option<Texture<'a>> {
+        let mut texture = self.creator
+            .create_texture_static(PixelFormatEnum::RGBA32, image.width, image.height)
+            .ok()?;
+
+        texture.update(None, &image.pixels, image.width as usize * 4).ok()?;
+        texture.set_blend_mode(BlendMode::Blend);
+
+        Some(texture)
+    }
+}
+
+/// Background of the letter badge standing in for a missing icon, picked from the name so
+/// that an entry keeps its colour.
+pub fn badge_color(name: &str) -> Rgb {
+    const COLORS: &[Rgb] = &[
+        Rgb::new(181, 71, 71),
+        Rgb::new(196, 122, 46),
+        Rgb::new(148, 148, 50),
+        Rgb::new(72, 145, 83),
+        Rgb::new(52, 140, 150),
+        Rgb::new(66, 103, 178),
+        Rgb::new(123, 86, 173),
+        Rgb::new(170, 73, 137),
+    ];
+
+    let hash = name.bytes().fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+    COLORS[hash as usize % COLORS.len()]
+}
+
+/// Letter shown on the badge.
+pub fn badge_letter(name: &str) -> String {
+    name.chars()
+        .find(|c| c.is_alphanumeric())
+        .map(|c| c.to_uppercase().collect())
+        .unwrap_or_else(|| "?".to_owned())
+}
+
+pub mod decode;
+pub mod theme;
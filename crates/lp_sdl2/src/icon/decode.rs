@@ -0,0 +1,159 @@
+//! Decoding of the image formats icon themes use into RGBA pixels.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+use resvg::{tiny_skia, usvg};
+
+/// Non-premultiplied RGBA pixels, row by row.
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Decodes the file according to its extension. SVGs are rasterized at `size`.
+pub fn load(path: &Path, size: u32) -> Result<Image, String> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("png") => png(path),
+        Some("svg") | Some("svgz") => svg(path, size),
+        Some("xpm") => xpm(&fs::read_to_string(path).map_err(|e| e.to_string())?),
+        _ => Err(format!("unsupported image format '{}'", path.display())),
+    }
+}
+
+fn png(path: &Path) -> Result<Image, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+    let buffer = &buffer[..info.buffer_size()];
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buffer.to_vec(),
+        png::ColorType::Rgb => buffer.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], u8::MAX]).collect(),
+        png::ColorType::GrayscaleAlpha => buffer.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|&g| [g, g, g, u8::MAX]).collect(),
+        png::ColorType::Indexed => return Err("indexed colours weren't expanded".to_owned()),
+    };
+
+    Ok(Image {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+
+fn svg(path: &Path, size: u32) -> Result<Image, String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let tree = usvg::Tree::from_data(&data, &usvg::Options::default()).map_err(|e| e.to_string())?;
+
+    let mut pixmap = tiny_skia::Pixmap::new(size, size).ok_or("empty icon size")?;
+    let scale = size as f32 / tree.size().width().max(tree.size().height());
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let c = p.demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect();
+
+    Ok(Image {
+        width: size,
+        height: size,
+        pixels,
+    })
+}
+
+/// XPM images are C source with the header, colours and pixel rows as string literals.
+fn xpm(source: &str) -> Result<Image, String> {
+    let strings = source
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .collect::<Vec<_>>();
+
+    let (header, rest) = strings.split_first().ok_or("missing XPM header")?;
+    let values = header
+        .split_whitespace()
+        .map(|v| v.parse::<usize>().map_err(|_| format!("invalid XPM header '{header}'")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let [width, height, colors, cpp, ..] = values[..] else {
+        return Err(format!("invalid XPM header '{header}'"));
+    };
+
+    if rest.len() < colors + height || cpp == 0 {
+        return Err("truncated XPM image".to_owned());
+    }
+
+    let palette = rest[..colors]
+        .iter()
+        .map(|line| {
+            let key = line.get(..cpp).ok_or("truncated XPM colour")?;
+            let mut words = line[cpp..].split_whitespace();
+            let mut color = None;
+
+            // entries are pairs of a context and a colour, the `c` (colour display) one is used
+            while let (Some(context), Some(value)) = (words.next(), words.next()) {
+                if context == "c" {
+                    color = Some(value);
+                }
+            }
+
+            Ok((key, xpm_color(color.unwrap_or("None"))))
+        })
+        .collect::<Result<HashMap<_, _>, String>>()?;
+
+    let mut pixels = Vec::with_capacity(width * height * 4);
+
+    for row in &rest[colors..colors + height] {
+        for x in 0..width {
+            let key = row.get(x * cpp..(x + 1) * cpp).ok_or("truncated XPM row")?;
+            pixels.extend_from_slice(palette.get(key).unwrap_or(&[0; 4]));
+        }
+    }
+
+    Ok(Image {
+        width: width as u32,
+        height: height as u32,
+        pixels,
+    })
+}
+
+fn xpm_color(value: &str) -> [u8; 4] {
+    let Some(hex) = value.strip_prefix('#') else {
+        return match value.to_ascii_lowercase().as_str() {
+            "black" => [0, 0, 0, 255],
+            "white" => [255, 255, 255, 255],
+            "red" => [255, 0, 0, 255],
+            "green" => [0, 255, 0, 255],
+            "blue" => [0, 0, 255, 255],
+            "gray" | "grey" => [190, 190, 190, 255],
+            _ => [0; 4],
+        };
+    };
+
+    // #rgb, #rrggbb and #rrrrggggbbbb all have three equally long channels
+    let digits = hex.len() / 3;
+
+    if !hex.is_ascii() || digits == 0 || digits > 4 || hex.len() % 3 != 0 {
+        return [0; 4];
+    }
+
+    let channel = |i: usize| {
+        let value = u32::from_str_radix(&hex[i * digits..(i + 1) * digits], 16).unwrap_or(0);
+        let max = (1u32 << (4 * digits)) - 1;
+        (value * 255 / max) as u8
+    };
+
+    [channel(0), channel(1), channel(2), u8::MAX]
+}
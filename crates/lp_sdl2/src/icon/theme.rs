@@ -0,0 +1,258 @@
+//! Icon lookup following the freedesktop Icon Theme specification.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::xdg;
+
+const EXTENSIONS: &[&str] = &["png", "svg", "xpm"];
+const FALLBACK_THEME: &str = "hicolor";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirectoryKind {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+#[derive(Debug, Clone)]
+struct Directory {
+    path: String,
+    size: u32,
+    scale: u32,
+    kind: DirectoryKind,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+impl Directory {
+    fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+
+        match self.kind {
+            DirectoryKind::Fixed => self.size == size,
+            DirectoryKind::Scalable => (self.min_size..=self.max_size).contains(&size),
+            DirectoryKind::Threshold => {
+                (self.size.saturating_sub(self.threshold)..=self.size + self.threshold).contains(&size)
+            }
+        }
+    }
+
+    fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let wanted = size * scale;
+
+        let (min, max) = match self.kind {
+            DirectoryKind::Fixed => (self.size, self.size),
+            DirectoryKind::Scalable => (self.min_size, self.max_size),
+            DirectoryKind::Threshold => (
+                self.size.saturating_sub(self.threshold),
+                self.size + self.threshold,
+            ),
+        };
+
+        if wanted < min * self.scale {
+            min * self.scale - wanted
+        } else {
+            wanted.saturating_sub(max * self.scale)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Theme {
+    /// Every `<base dir>/<theme name>` that exists.
+    roots: Vec<PathBuf>,
+    inherits: Vec<String>,
+    directories: Vec<Directory>,
+}
+
+impl Theme {
+    fn parse(roots: Vec<PathBuf>, index: &str) -> Self {
+        let mut sections = HashMap::<&str, HashMap<&str, &str>>::new();
+        let mut current = None;
+
+        for line in index.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                current = Some(name);
+                sections.entry(name).or_default();
+            } else if let (Some(section), Some((key, value))) = (current, line.split_once('=')) {
+                sections.entry(section).or_default().insert(key.trim(), value.trim());
+            }
+        }
+
+        let header = sections.get("Icon Theme").cloned().unwrap_or_default();
+        let list = |key: &str| {
+            header.get(key)
+                .map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_owned).collect())
+                .unwrap_or_else(Vec::new)
+        };
+
+        let directories = list("Directories")
+            .into_iter()
+            .chain(list("ScaledDirectories"))
+            .filter_map(|path| {
+                let section = sections.get(path.as_str())?;
+                let number = |key: &str| section.get(key).and_then(|v| v.parse::<u32>().ok());
+                let size = number("Size")?;
+
+                let kind = match section.get("Type").copied() {
+                    Some("Fixed") => DirectoryKind::Fixed,
+                    Some("Scalable") => DirectoryKind::Scalable,
+                    _ => DirectoryKind::Threshold,
+                };
+
+                Some(Directory {
+                    path,
+                    size,
+                    scale: number("Scale").unwrap_or(1),
+                    kind,
+                    min_size: number("MinSize").unwrap_or(size),
+                    max_size: number("MaxSize").unwrap_or(size),
+                    threshold: number("Threshold").unwrap_or(2),
+                })
+            })
+            .collect();
+
+        Self {
+            roots,
+            inherits: list("Inherits"),
+            directories,
+        }
+    }
+}
+
+/// Resolves icon names to files, remembering the parsed themes.
+pub struct IconLookup {
+    base_dirs: Vec<PathBuf>,
+    theme: String,
+    themes: HashMap<String, Option<Theme>>,
+}
+
+impl IconLookup {
+    /// Lookup in `theme`, defaulting to the GTK theme of the user or `hicolor`.
+    pub fn new(theme: Option<&str>) -> Self {
+        let theme = theme
+            .map(str::to_owned)
+            .or_else(gtk_icon_theme)
+            .unwrap_or_else(|| FALLBACK_THEME.to_owned());
+
+        Self {
+            base_dirs: base_dirs(),
+            theme,
+            themes: HashMap::new(),
+        }
+    }
+
+    /// Finds the file for `name` closest to `size` pixels at `scale`. `name` may also be an
+    /// absolute path.
+    pub fn find(&mut self, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        if Path::new(name).is_absolute() {
+            return Path::new(name).is_file().then(|| PathBuf::from(name));
+        }
+
+        let mut visited = HashSet::new();
+        let theme = self.theme.clone();
+
+        self.find_in_theme(&theme, name, size, scale, &mut visited)
+            .or_else(|| self.find_in_theme(FALLBACK_THEME, name, size, scale, &mut visited))
+            .or_else(|| self.find_unthemed(name))
+    }
+
+    fn find_in_theme(
+        &mut self,
+        theme_name: &str,
+        name: &str,
+        size: u32,
+        scale: u32,
+        visited: &mut HashSet<String>,
+    ) -> Option<PathBuf> {
+        if !visited.insert(theme_name.to_owned()) {
+            return None;
+        }
+
+        let theme = self.theme(theme_name)?.clone();
+
+        if let Some(path) = find_helper(&theme, name, size, scale) {
+            return Some(path);
+        }
+
+        theme.inherits
+            .iter()
+            .find_map(|parent| self.find_in_theme(parent, name, size, scale, visited))
+    }
+
+    /// Icons directly in the base directories, like `/usr/share/pixmaps`.
+    fn find_unthemed(&self, name: &str) -> Option<PathBuf> {
+        self.base_dirs.iter().find_map(|dir| {
+            EXTENSIONS
+                .iter()
+                .map(|ext| dir.join(format!("{name}.{ext}")))
+                .find(|path| path.is_file())
+        })
+    }
+
+    fn theme(&mut self, name: &str) -> Option<&Theme> {
+        if !self.themes.contains_key(name) {
+            let roots = self.base_dirs
+                .iter()
+                .map(|dir| dir.join(name))
+                .filter(|dir| dir.is_dir())
+                .collect::<Vec<_>>();
+
+            let theme = roots
+                .iter()
+                .find_map(|root| fs::read_to_string(root.join("index.theme")).ok())
+                .map(|index| Theme::parse(roots.clone(), &index));
+
+            self.themes.insert(name.to_owned(), theme);
+        }
+
+        self.themes.get(name).and_then(Option::as_ref)
+    }
+}
+
+fn find_helper(theme: &Theme, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+    let candidates = || theme.directories.iter().flat_map(|dir| {
+        theme.roots.iter().flat_map(move |root| {
+            EXTENSIONS.iter().map(move |ext| (dir, root.join(&dir.path).join(format!("{name}.{ext}"))))
+        })
+    });
+
+    candidates()
+        .find(|(dir, path)| dir.matches_size(size, scale) && path.is_file())
+        .or_else(|| {
+            candidates()
+                .filter(|(_, path)| path.is_file())
+                .min_by_key(|(dir, _)| dir.size_distance(size, scale))
+        })
+        .map(|(_, path)| path)
+}
+
+/// Base directories in the order the specification gives them.
+fn base_dirs() -> Vec<PathBuf> {
+    xdg::home()
+        .map(|home| home.join(".icons"))
+        .into_iter()
+        .chain(xdg::data_paths().into_iter().map(|dir| dir.join("icons")))
+        .chain([PathBuf::from("/usr/share/pixmaps")])
+        .collect()
+}
+
+/// `gtk-icon-theme-name` from the GTK 3 settings of the user.
+fn gtk_icon_theme() -> Option<String> {
+    let settings = config::config_dir()?.parent()?.join("gtk-3.0/settings.ini");
+
+    fs::read_to_string(settings)
+        .ok()?
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|(key, _)| key.trim() == "gtk-icon-theme-name")
+        .map(|(_, value)| value.trim().trim_matches('"').to_owned())
+}
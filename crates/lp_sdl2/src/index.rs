@@ -0,0 +1,501 @@
+//! The files below the roots of the `files` mode, kept in an index on disk for a fast start
+//! and up to date through inotify.
+//!
+//! A background thread loads the index, walks the roots anew and then watches every
+//! directory it walked. A change within a directory lists that directory again, and walks
+//! the directories that appeared in it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::WalkBuilder;
+use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask};
+use config::Files;
+use crate::xdg;
+
+/// How long events are gathered after the first, so that a burst of them, like from
+/// unpacking an archive, is handled at once.
+const SETTLE: Duration = Duration::from_millis(200);
+/// How long a steady stream of events is gathered at most before what changed so far is
+/// published.
+const MAX_SETTLE: Duration = Duration::from_secs(2);
+/// How often the thread checks whether it's still needed while nothing changes.
+const CHECK_STOP: Duration = Duration::from_secs(1);
+/// The index on disk is written at most this often while files keep changing.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+/// First line of the index, followed by the settings it was built with. An index written
+/// with other settings is ignored.
+const HEADER: &str = "launchpad files 1";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+/// The indexing thread, stopped when this is dropped.
+pub struct Index {
+    updates: Receiver<Vec<Entry>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Index {
+    /// Starts indexing, `wake` is called whenever there are new entries.
+    pub fn spawn(settings: Files, wake: impl Fn() + Send + 'static) -> Self {
+        let (sender, updates) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+
+        thread::spawn(move || Indexer::new(settings, sender, wake, stopped).run());
+
+        Self { updates, stop }
+    }
+
+    /// The entries as they are now, if they changed since the last call.
+    pub fn latest(&self) -> Option<Vec<Entry>> {
+        self.updates.try_iter().last()
+    }
+}
+
+impl Drop for Index {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Root {
+    path: PathBuf,
+    excluded: Override,
+}
+
+struct Indexer<W> {
+    settings: Files,
+    roots: Vec<Root>,
+    entries: BTreeMap<PathBuf, bool>,
+    inotify: Option<Inotify>,
+    /// The directory each watch is on.
+    watches: HashMap<WatchDescriptor, PathBuf>,
+    sender: Sender<Vec<Entry>>,
+    wake: W,
+    stop: Arc<AtomicBool>,
+    saved: Option<Instant>,
+    /// Warned about already.
+    full: bool,
+    unwatched: bool,
+}
+
+impl<W: Fn()> Indexer<W> {
+    fn new(settings: Files, sender: Sender<Vec<Entry>>, wake: W, stop: Arc<AtomicBool>) -> Self {
+        let inotify = Inotify::init()
+            .map_err(|e| eprintln!("warning: files won't be kept up to date, inotify failed: {e}"))
+            .ok();
+
+        Self {
+            roots: roots(&settings),
+            settings,
+            entries: BTreeMap::new(),
+            inotify,
+            watches: HashMap::new(),
+            sender,
+            wake,
+            stop,
+            saved: None,
+            full: false,
+            unwatched: false,
+        }
+    }
+
+    fn run(mut self) {
+        if let Some(entries) = index_path().and_then(|path| load(&path, &self.settings)) {
+            self.entries = entries;
+            self.publish();
+        }
+
+        self.rescan();
+        self.publish();
+        self.save();
+
+        while !self.stop.load(Ordering::Relaxed) {
+            let Some(changes) = self.changes() else {
+                continue;
+            };
+
+            self.apply(changes);
+            self.publish();
+
+            if self.saved.is_none_or(|saved| saved.elapsed() >= SAVE_INTERVAL) {
+                self.save();
+            }
+        }
+
+        self.save();
+    }
+
+    /// Hands the entries to the mode.
+    fn publish(&mut self) {
+        let entries = self.entries.iter().map(|(path, is_dir)| Entry { path: path.clone(), is_dir: *is_dir }).collect();
+
+        if self.sender.send(entries).is_err() {
+            self.stop.store(true, Ordering::Relaxed);
+            return;
+        }
+
+        (self.wake)();
+    }
+
+    fn save(&mut self) {
+        if let Some(Err(e)) = index_path().map(|path| save(&path, &self.settings, &self.entries)) {
+            eprintln!("warning: failed to save the file index: {e}");
+        }
+
+        self.saved = Some(Instant::now());
+    }
+
+    /// Walks every root from scratch.
+    fn rescan(&mut self) {
+        self.entries.clear();
+
+        for index in 0..self.roots.len() {
+            let path = self.roots[index].path.clone();
+            self.watch(&path);
+            self.walk(index, &path, false);
+        }
+    }
+
+    fn apply(&mut self, changes: Changes) {
+        match changes {
+            Changes::Overflow => self.rescan(),
+            Changes::Dirs(dirs) => {
+                for dir in dirs {
+                    self.refresh(&dir);
+                }
+            }
+        }
+    }
+
+    /// Adds what's below `path` in the `root`th root, and `path` itself with `include_self`.
+    fn walk(&mut self, root: usize, path: &Path, include_self: bool) {
+        for entry in self.walker(root, path, None).flatten() {
+            if entry.depth() == 0 && !include_self {
+                continue;
+            }
+
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+
+            if !self.insert(entry.path().to_owned(), is_dir) {
+                return;
+            }
+
+            if is_dir {
+                self.watch(entry.path());
+            }
+        }
+    }
+
+    /// Lists `dir` again, for the changes within it.
+    fn refresh(&mut self, dir: &Path) {
+        let Some(root) = self.roots.iter().position(|root| dir.starts_with(&root.path)) else {
+            return;
+        };
+
+        if !dir.is_dir() {
+            self.remove(dir);
+            return;
+        }
+
+        let listed = self
+            .walker(root, dir, Some(1))
+            .flatten()
+            .filter(|entry| entry.depth() == 1)
+            .map(|entry| (entry.path().to_owned(), entry.file_type().is_some_and(|t| t.is_dir())))
+            .collect::<BTreeMap<_, _>>();
+
+        let gone = self
+            .below(dir)
+            .filter(|path| path.parent() == Some(dir) && !listed.contains_key(*path))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for path in gone {
+            self.remove(&path);
+        }
+
+        for (path, is_dir) in listed {
+            match self.entries.get(&path) {
+                Some(known) if *known == is_dir => {}
+                Some(_) => {
+                    self.remove(&path);
+                    self.add(root, path, is_dir);
+                }
+                None => self.add(root, path, is_dir),
+            }
+        }
+    }
+
+    fn add(&mut self, root: usize, path: PathBuf, is_dir: bool) {
+        if is_dir {
+            self.walk(root, &path, true);
+        } else {
+            self.insert(path, false);
+        }
+    }
+
+    /// Adds an entry, unless there are as many as there may be already.
+    fn insert(&mut self, path: PathBuf, is_dir: bool) -> bool {
+        if self.entries.len() >= self.settings.max_files as usize {
+            if !self.full {
+                eprintln!("warning: files.max_files ({}) reached, the other files are left out", self.settings.max_files);
+                self.full = true;
+            }
+
+            return false;
+        }
+
+        self.entries.insert(path, is_dir);
+        true
+    }
+
+    /// Removes `path` and everything below it.
+    fn remove(&mut self, path: &Path) {
+        let below = self.below(path).cloned().collect::<Vec<_>>();
+
+        for path in below {
+            self.entries.remove(&path);
+        }
+
+        self.entries.remove(path);
+        // the kernel drops the watches of deleted directories, those moved away are left
+        // to report changes no longer asked about
+        self.watches.retain(|_, dir| !dir.starts_with(path));
+    }
+
+    /// The entries below `path`, which sort right after it.
+    fn below<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a PathBuf> {
+        self.entries
+            .range::<Path, _>((std::ops::Bound::Excluded(path), std::ops::Bound::Unbounded))
+            .map(|(path, _)| path)
+            .take_while(move |below| below.starts_with(path))
+    }
+
+    fn watch(&mut self, dir: &Path) {
+        let Some(inotify) = &mut self.inotify else {
+            return;
+        };
+
+        let mask = WatchMask::CREATE
+            | WatchMask::DELETE
+            | WatchMask::MOVED_FROM
+            | WatchMask::MOVED_TO
+            | WatchMask::DELETE_SELF
+            | WatchMask::ONLYDIR;
+
+        match inotify.watches().add(dir, mask) {
+            Ok(watch) => {
+                self.watches.insert(watch, dir.to_owned());
+            }
+            Err(e) if !self.unwatched => {
+                eprintln!("warning: can't watch '{}' and further directories for changes: {e}", dir.display());
+                self.unwatched = true;
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// Waits for changes, then for them to settle or [`MAX_SETTLE`] to pass. `None` when
+    /// nothing changed for a while or the thread is to stop.
+    fn changes(&mut self) -> Option<Changes> {
+        let Some(inotify) = &self.inotify else {
+            thread::sleep(CHECK_STOP);
+            return None;
+        };
+
+        if !readable(inotify, CHECK_STOP) {
+            return None;
+        }
+
+        let mut dirs = Vec::new();
+        let mut overflow = false;
+        let mut buffer = [0; 4096];
+        let settling = Instant::now();
+
+        while let Some(inotify) = &mut self.inotify {
+            if self.stop.load(Ordering::Relaxed) {
+                return None;
+            }
+
+            // the rest is read with the next changes
+            if settling.elapsed() >= MAX_SETTLE {
+                break;
+            }
+
+            match inotify.read_events(&mut buffer) {
+                Ok(events) => {
+                    for event in events {
+                        if event.mask.contains(EventMask::Q_OVERFLOW) {
+                            overflow = true;
+                        } else if event.mask.contains(EventMask::IGNORED) {
+                            self.watches.remove(&event.wd);
+                        } else if let Some(dir) = self.watches.get(&event.wd) {
+                            if !dirs.contains(dir) {
+                                dirs.push(dir.clone());
+                            }
+
+                            // the directory itself went away, its parent lists it no more
+                            if event.mask.contains(EventMask::DELETE_SELF) {
+                                dirs.extend(dir.parent().map(Path::to_owned));
+                            }
+                        }
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    if !readable(inotify, SETTLE) {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("warning: files are no longer kept up to date: {e}");
+                    self.inotify = None;
+                    break;
+                }
+            }
+        }
+
+        Some(if overflow { Changes::Overflow } else { Changes::Dirs(dirs) })
+    }
+
+    fn walker(&self, root: usize, path: &Path, max_depth: Option<usize>) -> ignore::Walk {
+        let gitignore = self.settings.gitignore;
+
+        WalkBuilder::new(path)
+            .hidden(!self.settings.hidden)
+            .ignore(gitignore)
+            .git_ignore(gitignore)
+            .git_global(gitignore)
+            .git_exclude(gitignore)
+            .parents(gitignore)
+            .overrides(self.roots[root].excluded.clone())
+            .follow_links(false)
+            .max_depth(max_depth)
+            .build()
+    }
+}
+
+enum Changes {
+    /// Too much changed to be told, everything is walked anew.
+    Overflow,
+    /// Directories whose entries changed.
+    Dirs(Vec<PathBuf>),
+}
+
+/// Whether there are events to read within `timeout`.
+fn readable(inotify: &Inotify, timeout: Duration) -> bool {
+    let mut fd = libc::pollfd { fd: inotify.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+    // SAFETY: `fd` is a single valid pollfd for the duration of the call
+    unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) > 0 }
+}
+
+/// The roots of the settings that exist, with `~` expanded and their exclusions.
+fn roots(settings: &Files) -> Vec<Root> {
+    let home = xdg::home();
+
+    settings
+        .roots
+        .iter()
+        .filter_map(|root| {
+            let path = match root.strip_prefix('~') {
+                Some(rest) => home.as_ref()?.join(rest.trim_start_matches('/')),
+                None => PathBuf::from(root),
+            };
+
+            if !path.is_dir() {
+                eprintln!("warning: files.roots has '{root}', which isn't a directory");
+                return None;
+            }
+
+            let mut excluded = OverrideBuilder::new(&path);
+
+            for glob in &settings.exclude {
+                if let Err(e) = excluded.add(&format!("!{glob}")) {
+                    eprintln!("warning: files.exclude has an invalid glob: {e}");
+                }
+            }
+
+            let excluded = excluded.build().unwrap_or_else(|_| Override::empty());
+            Some(Root { path, excluded })
+        })
+        .collect()
+}
+
+/// Where the index is kept.
+fn index_path() -> Option<PathBuf> {
+    xdg::cache_home().map(|dir| dir.join("launchpad/files.index"))
+}
+
+fn header(settings: &Files) -> String {
+    format!("{HEADER} {}", serde_json::to_string(settings).expect("settings serialize"))
+}
+
+/// The index at `path`, if it was built with `settings`. Lines are `d` for a directory or
+/// `f` for anything else, a space and the path.
+fn load(path: &Path, settings: &Files) -> Option<BTreeMap<PathBuf, bool>> {
+    let data = fs::read(path).ok()?;
+    let mut lines = data.split(|byte| *byte == b'\n');
+
+    if lines.next()? != header(settings).as_bytes() {
+        return None;
+    }
+
+    let entries = lines
+        .filter_map(|line| {
+            let is_dir = match line.get(..2)? {
+                b"d " => true,
+                b"f " => false,
+                _ => return None,
+            };
+
+            Some((PathBuf::from(OsString::from_vec(line[2..].to_vec())), is_dir))
+        })
+        .collect();
+
+    Some(entries)
+}
+
+fn save(path: &Path, settings: &Files, entries: &BTreeMap<PathBuf, bool>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    // written aside and moved over, so that a reader never sees half of it
+    let partial = path.with_extension("partial");
+    let mut file = BufWriter::new(fs::File::create(&partial)?);
+    writeln!(file, "{}", header(settings))?;
+
+    for (entry, is_dir) in entries {
+        let bytes = entry.as_os_str().as_bytes();
+
+        // a name with a line break can't be told apart from two
+        if bytes.contains(&b'\n') {
+            continue;
+        }
+
+        file.write_all(if *is_dir { b"d " } else { b"f " })?;
+        file.write_all(bytes)?;
+        file.write_all(b"\n")?;
+    }
+
+    file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(partial, path)
+}
+
+#[cfg(test)]
+mod tests;
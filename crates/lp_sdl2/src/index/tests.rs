@@ -0,0 +1,211 @@
+//! The index walking and following a temporary tree.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc;
+use std::sync::Arc;
+use tempfile::TempDir;
+use config::Files;
+use super::{load, save, Changes, Indexer};
+
+fn nothing() {}
+
+/// A temporary directory with `paths` in it, those ending in `/` being directories.
+fn tree(paths: &[&str]) -> TempDir {
+    let dir = TempDir::new().expect("temporary directory");
+
+    for path in paths {
+        create(dir.path(), path);
+    }
+
+    dir
+}
+
+fn create(root: &Path, path: &str) {
+    let full = root.join(path);
+
+    if path.ends_with('/') {
+        fs::create_dir_all(full).expect("directory created");
+    } else {
+        fs::create_dir_all(full.parent().expect("file has a parent")).expect("directory created");
+        fs::write(full, "").expect("file written");
+    }
+}
+
+fn settings(root: &Path) -> Files {
+    Files {
+        roots: vec![root.display().to_string()],
+        ..Files::default()
+    }
+}
+
+fn indexer(settings: Files) -> Indexer<fn()> {
+    let (sender, _) = mpsc::channel();
+    Indexer::new(settings, sender, nothing, Arc::new(AtomicBool::new(false)))
+}
+
+/// The entries relative to `root`, directories ending in `/`.
+fn entries(indexer: &Indexer<fn()>, root: &Path) -> Vec<String> {
+    indexer
+        .entries
+        .iter()
+        .map(|(path, is_dir)| {
+            let relative = path.strip_prefix(root).expect("entry below the root").display();
+            if *is_dir { format!("{relative}/") } else { relative.to_string() }
+        })
+        .collect()
+}
+
+/// Waits for the changes made to the tree and applies them.
+fn follow(indexer: &mut Indexer<fn()>) {
+    let changes = indexer.changes().expect("changes were seen");
+    assert!(matches!(changes, Changes::Dirs(_)), "no overflow expected");
+    indexer.apply(changes);
+}
+
+#[test]
+fn walks_the_roots_without_exclusions() {
+    let dir = tree(&[
+        "notes.txt",
+        "docs/report.md",
+        "src/main.rs",
+        "src/__pycache__/main.pyc",
+        "web/node_modules/react/index.js",
+        ".config/app.toml",
+    ]);
+
+    let mut indexer = indexer(settings(dir.path()));
+    indexer.rescan();
+
+    assert_eq!(
+        entries(&indexer, dir.path()),
+        ["docs/", "docs/report.md", "notes.txt", "src/", "src/main.rs", "web/"],
+    );
+}
+
+#[test]
+fn takes_hidden_files_and_custom_exclusions() {
+    let dir = tree(&["notes.txt", "build/out.o", ".config/app.toml"]);
+
+    let mut indexer = indexer(Files {
+        hidden: true,
+        exclude: vec!["*.o".to_owned()],
+        ..settings(dir.path())
+    });
+    indexer.rescan();
+
+    assert_eq!(entries(&indexer, dir.path()), [".config/", ".config/app.toml", "build/", "notes.txt"]);
+}
+
+#[test]
+fn leaves_out_what_git_ignores() {
+    let dir = tree(&[".git/", "target/debug/app", "src/lib.rs"]);
+    fs::write(dir.path().join(".gitignore"), "target/\n").expect("gitignore written");
+
+    let mut indexer = indexer(settings(dir.path()));
+    indexer.rescan();
+    assert_eq!(entries(&indexer, dir.path()), ["src/", "src/lib.rs"]);
+
+    let mut indexer = self::indexer(Files { gitignore: false, ..settings(dir.path()) });
+    indexer.rescan();
+    assert_eq!(entries(&indexer, dir.path()), ["src/", "src/lib.rs", "target/", "target/debug/", "target/debug/app"]);
+}
+
+#[test]
+fn stops_at_max_files() {
+    let dir = tree(&["a", "b", "c", "d"]);
+
+    let mut indexer = indexer(Files { max_files: 3, ..settings(dir.path()) });
+    indexer.rescan();
+
+    assert_eq!(indexer.entries.len(), 3);
+}
+
+#[test]
+fn below_takes_only_what_is_inside() {
+    let mut indexer = indexer(Files { roots: Vec::new(), ..Files::default() });
+
+    for path in ["/a", "/a/b", "/a/b/c", "/a/d", "/a b", "/ab", "/ab/e", "/b"] {
+        indexer.entries.insert(PathBuf::from(path), true);
+    }
+
+    let below = |path: &str| indexer.below(Path::new(path)).map(|p| p.display().to_string()).collect::<Vec<_>>();
+
+    assert_eq!(below("/a"), ["/a/b", "/a/b/c", "/a/d"]);
+    assert_eq!(below("/a/b"), ["/a/b/c"]);
+    assert_eq!(below("/ab"), ["/ab/e"]);
+    assert!(below("/b").is_empty());
+    assert!(below("/c").is_empty());
+}
+
+#[test]
+fn follows_created_deleted_and_renamed_files() {
+    let dir = tree(&["notes.txt", "docs/report.md", "docs/old/draft.md"]);
+    let root = dir.path();
+
+    let mut indexer = indexer(settings(root));
+    indexer.rescan();
+
+    create(root, "todo.txt");
+    create(root, "music/album/track.flac");
+    follow(&mut indexer);
+    assert_eq!(
+        entries(&indexer, root),
+        [
+            "docs/",
+            "docs/old/",
+            "docs/old/draft.md",
+            "docs/report.md",
+            "music/",
+            "music/album/",
+            "music/album/track.flac",
+            "notes.txt",
+            "todo.txt",
+        ],
+    );
+
+    fs::remove_file(root.join("notes.txt")).expect("file removed");
+    fs::remove_dir_all(root.join("docs/old")).expect("directory removed");
+    follow(&mut indexer);
+    assert_eq!(
+        entries(&indexer, root),
+        ["docs/", "docs/report.md", "music/", "music/album/", "music/album/track.flac", "todo.txt"],
+    );
+
+    fs::rename(root.join("docs"), root.join("papers")).expect("directory renamed");
+    fs::rename(root.join("todo.txt"), root.join("music/todo.txt")).expect("file moved");
+    follow(&mut indexer);
+    assert_eq!(
+        entries(&indexer, root),
+        ["music/", "music/album/", "music/album/track.flac", "music/todo.txt", "papers/", "papers/report.md"],
+    );
+
+    // the renamed directory is watched under its new name
+    create(root, "papers/summary.md");
+    follow(&mut indexer);
+    assert!(entries(&indexer, root).contains(&"papers/summary.md".to_owned()));
+}
+
+#[test]
+fn saves_and_loads_the_index() {
+    let dir = tree(&[]);
+    let path = dir.path().join("cache/files.index");
+    let settings = settings(Path::new("/home/user"));
+
+    let entries = [("/home/user/docs", true), ("/home/user/docs/report.md", false), ("/home/user/odd\nname", false)]
+        .into_iter()
+        .map(|(path, is_dir)| (PathBuf::from(path), is_dir))
+        .collect::<BTreeMap<_, _>>();
+
+    save(&path, &settings, &entries).expect("index saved");
+
+    let mut expected = entries.clone();
+    expected.remove(Path::new("/home/user/odd\nname"));
+    assert_eq!(load(&path, &settings), Some(expected));
+
+    // an index built with other settings doesn't hold what these would find
+    assert_eq!(load(&path, &Files { hidden: true, ..settings }), None);
+    assert_eq!(load(&dir.path().join("missing.index"), &Files::default()), None);
+}
@@ -0,0 +1,225 @@
+//! Talking to the resident launcher started with `launchpad daemon`.
+//!
+//! Clients connect to a Unix socket in `$XDG_RUNTIME_DIR` and send a single request, the
+//! daemon answers with a single response. Both are one line of JSON carrying the protocol
+//! [`VERSION`], requests from another version are refused rather than misread.
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+use sdl2::event::EventSender;
+use sdl2::EventSubsystem;
+use serde::{Deserialize, Serialize};
+use crate::{error, Result};
+
+/// Version of the requests and responses, bumped whenever either changes incompatibly.
+pub const VERSION: u32 = 1;
+/// How long either side waits for the other before giving up.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Shows the window, in `mode` and with `query` typed in if given.
+    Show {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mode: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        query: Option<String>,
+    },
+    Hide,
+    /// Shows the window if it's hidden, hides it otherwise.
+    Toggle {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mode: Option<String>,
+    },
+    /// Stops the daemon.
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Response {
+    Ok,
+    Error { message: String },
+}
+
+/// A request that came in over the socket, delivered through the SDL event queue so that
+/// the event loop wakes up for it.
+pub struct Remote {
+    pub request: Request,
+    reply: Sender<Response>,
+}
+
+impl Remote {
+    pub fn reply(self, response: Response) {
+        // the client may have given up waiting already
+        let _ = self.reply.send(response);
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    #[serde(flatten)]
+    body: &'a T,
+}
+
+/// Path of the socket the daemon listens on.
+pub fn socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("launchpad.sock"),
+        None => {
+            // SAFETY: getuid can't fail and has no memory safety requirements
+            let uid = unsafe { libc::getuid() };
+            env::temp_dir().join(format!("launchpad-{uid}.sock"))
+        }
+    }
+}
+
+/// The listening end, owned by the daemon. Only one daemon can hold it at a time.
+pub struct Server {
+    path: PathBuf,
+    /// Locked for as long as the daemon runs.
+    _lock: File,
+}
+
+impl Server {
+    /// Takes the single-instance lock and starts accepting requests, which arrive as
+    /// [`Remote`] events.
+    pub fn start(events: &EventSubsystem) -> Result<Self> {
+        let path = socket_path();
+        let (lock, listener) = bind(&path)?;
+
+        events.register_custom_event::<Remote>().map_err(error::system_event)?;
+        let sender = events.event_sender();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(e) = serve(stream, &sender) {
+                    eprintln!("warning: failed to answer a request: {e}");
+                }
+            }
+        });
+
+        Ok(Self { path, _lock: lock })
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Takes the single-instance lock next to `path`, then listens at `path`.
+fn bind(path: &Path) -> Result<(File, UnixListener)> {
+    let lock = lock(&path.with_extension("lock"))?;
+
+    // holding the lock, whatever is left at the path is from a daemon that died
+    let _ = fs::remove_file(path);
+    let listener = UnixListener::bind(path).map_err(|e| error::listen(path, e))?;
+
+    Ok((lock, listener))
+}
+
+fn lock(path: &Path) -> Result<File> {
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| error::listen(path, e))?;
+
+    // SAFETY: the descriptor belongs to `file`, which is open for the duration of the call
+    let locked = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+
+    if locked != 0 {
+        let e = io::Error::last_os_error();
+
+        return Err(match e.kind() {
+            ErrorKind::WouldBlock => error::Error::AlreadyRunning,
+            _ => error::listen(path, e),
+        });
+    }
+
+    Ok(file)
+}
+
+/// Answers the one request of a connection.
+fn serve(stream: UnixStream, sender: &EventSender) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let response = match parse(&line) {
+        Ok(request) => {
+            let (reply, response) = mpsc::channel();
+
+            match sender.push_custom_event(Remote { request, reply }) {
+                Ok(()) => response.recv_timeout(TIMEOUT).unwrap_or_else(|_| Response::Error {
+                    message: "the launcher didn't answer in time".to_owned(),
+                }),
+                Err(message) => Response::Error { message },
+            }
+        }
+        Err(message) => Response::Error { message },
+    };
+
+    write_line(&stream, &response)
+}
+
+fn parse(line: &str) -> std::result::Result<Request, String> {
+    let value = serde_json::from_str::<serde_json::Value>(line).map_err(|e| format!("malformed request: {e}"))?;
+
+    match value.get("version").and_then(serde_json::Value::as_u64) {
+        Some(version) if version == VERSION as u64 => {}
+        Some(version) => return Err(format!("unsupported protocol version {version}, expected {VERSION}")),
+        None => return Err("the request has no protocol version".to_owned()),
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("malformed request: {e}"))
+}
+
+fn write_line<T: Serialize>(mut stream: &UnixStream, body: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(&Envelope { version: VERSION, body })?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+/// Sends `request` to the running daemon and waits for its response.
+pub fn send(request: &Request) -> io::Result<Response> {
+    let path = socket_path();
+    let stream = UnixStream::connect(&path).map_err(|e| match e.kind() {
+        ErrorKind::NotFound | ErrorKind::ConnectionRefused => io::Error::new(
+            e.kind(),
+            format!("no launcher is running at '{}', start one with `launchpad daemon`", path.display()),
+        ),
+        _ => e,
+    })?;
+
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    write_line(&stream, request)?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let value = serde_json::from_str::<serde_json::Value>(&line)?;
+
+    match value.get("version").and_then(serde_json::Value::as_u64) {
+        Some(version) if version == VERSION as u64 => Ok(serde_json::from_value(value)?),
+        _ => Err(io::Error::new(ErrorKind::InvalidData, "the launcher speaks another protocol version")),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,88 @@
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::{UnixListener, UnixStream};
+use tempfile::TempDir;
+use crate::error::Error;
+use super::{bind, lock, parse, write_line, Request, Response, VERSION};
+
+/// What `body` looks like on the wire, without the line break.
+fn line<T: serde::Serialize>(body: &T) -> String {
+    let (client, server) = UnixStream::pair().expect("socket pair");
+    write_line(&client, body).expect("line written");
+
+    let mut line = String::new();
+    BufReader::new(&server).read_line(&mut line).expect("line read");
+    assert!(line.ends_with('\n'), "one line per message");
+    line.trim_end().to_owned()
+}
+
+#[test]
+fn requests_round_trip() {
+    let requests = [
+        Request::Show { mode: Some("files".to_owned()), query: Some("report \"q3\"\n".to_owned()) },
+        Request::Show { mode: None, query: None },
+        Request::Hide,
+        Request::Toggle { mode: Some("apps".to_owned()) },
+        Request::Quit,
+    ];
+
+    for request in requests {
+        assert_eq!(parse(&line(&request)), Ok(request));
+    }
+
+    assert_eq!(line(&Request::Hide), format!(r#"{{"version":{VERSION},"type":"hide"}}"#));
+}
+
+#[test]
+fn responses_carry_the_version() {
+    let response = Response::Error { message: "no such mode".to_owned() };
+
+    assert_eq!(
+        line(&response),
+        format!(r#"{{"version":{VERSION},"status":"error","message":"no such mode"}}"#),
+    );
+}
+
+#[test]
+fn rejects_other_versions_and_malformed_requests() {
+    let other = format!(r#"{{"version":{},"type":"hide"}}"#, VERSION + 1);
+
+    assert!(parse(&other).is_err_and(|e| e.contains("unsupported protocol version")));
+    assert!(parse(r#"{"type":"hide"}"#).is_err_and(|e| e.contains("no protocol version")));
+    assert!(parse(&format!(r#"{{"version":{VERSION},"type":"explode"}}"#)).is_err());
+    assert!(parse("hide").is_err());
+}
+
+#[test]
+fn only_one_holds_the_lock() {
+    let dir = TempDir::new().expect("temporary directory");
+    let path = dir.path().join("launchpad.lock");
+
+    let first = lock(&path).expect("first lock taken");
+    assert!(matches!(lock(&path), Err(Error::AlreadyRunning)));
+
+    // the lock goes with the daemon, the file left behind doesn't keep the next one out
+    drop(first);
+    assert!(path.exists());
+    lock(&path).expect("lock taken again");
+}
+
+#[test]
+fn listens_in_place_of_a_dead_daemon() {
+    let dir = TempDir::new().expect("temporary directory");
+    let path = dir.path().join("launchpad.sock");
+
+    // a daemon that died leaves its socket behind, nobody listening on it
+    drop(UnixListener::bind(&path).expect("stale socket"));
+    assert!(UnixStream::connect(&path).is_err());
+
+    let (_lock, listener) = bind(&path).expect("listening");
+    assert!(matches!(bind(&path), Err(Error::AlreadyRunning)));
+
+    let client = UnixStream::connect(&path).expect("connected");
+    let (server, _) = listener.accept().expect("accepted");
+    write_line(&server, &Response::Ok).expect("response written");
+
+    let mut line = String::new();
+    BufReader::new(&client).read_line(&mut line).expect("response read");
+    assert_eq!(line, format!("{{\"version\":{VERSION},\"status\":\"ok\"}}\n"));
+}
@@ -0,0 +1,288 @@
+//! Rectangle arithmetic for laying out the UI. Every operation saturates instead of
+//! underflowing, so a child that doesn't fit ends up empty rather than wrapping around.
+
+use std::env;
+use sdl2::rect::Rect as SdlRect;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Rect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+impl From<Rect> for SdlRect {
+    fn from(value: Rect) -> Self {
+        SdlRect::new(value.x() as _, value.y() as _, value.width(), value.height())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// How much of the parent a child takes along the split axis.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Size {
+    /// Exactly this many pixels.
+    Fixed(u32),
+    /// This fraction of the parent.
+    Ratio(f32),
+    /// A share of whatever the fixed and proportional children left, by weight.
+    Fill(u32),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Start,
+    Center,
+    End,
+}
+
+impl Align {
+    /// Offset of a child of length `inner` in a parent of length `outer`.
+    fn offset(self, outer: u32, inner: u32) -> u32 {
+        let free = outer.saturating_sub(inner);
+
+        match self {
+            Align::Start => 0,
+            Align::Center => free / 2,
+            Align::End => free,
+        }
+    }
+}
+
+/// Space around the four sides of a rectangle, for padding and margins.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Insets {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl Insets {
+    pub const fn uniform(n: u32) -> Self {
+        Self { top: n, right: n, bottom: n, left: n }
+    }
+
+    pub const fn symmetric(vertical: u32, horizontal: u32) -> Self {
+        Self { top: vertical, right: horizontal, bottom: vertical, left: horizontal }
+    }
+}
+
+/// Factor from logical sizes, like the ones in the configuration, to pixels on screen.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Scale(f32);
+
+impl Scale {
+    pub const ONE: Scale = Scale(1.);
+
+    /// Anything but a positive factor is taken as no scaling.
+    pub fn new(factor: f32) -> Self {
+        Scale(if factor.is_finite() && factor > 0. { factor } else { 1. })
+    }
+
+    /// `GDK_SCALE` or `QT_SCALE_FACTOR`, for desktops that scale applications themselves
+    /// instead of through a high-DPI drawable.
+    pub fn from_env() -> Option<Self> {
+        ["GDK_SCALE", "QT_SCALE_FACTOR"]
+            .into_iter()
+            .find_map(|var| env::var(var).ok()?.trim().parse::<f32>().ok())
+            .filter(|factor| factor.is_finite() && *factor > 0.)
+            .map(Scale)
+    }
+
+    pub fn factor(self) -> f32 {
+        self.0
+    }
+
+    pub fn px(self, logical: u32) -> u32 {
+        (logical as f32 * self.0).round() as u32
+    }
+
+    /// Font size in points, never rounded down to nothing.
+    pub fn pt(self, logical: u16) -> u16 {
+        (logical as f32 * self.0).round().clamp(1., u16::MAX as f32) as u16
+    }
+}
+
+impl Rect {
+    pub fn new(x: u32, y: u32, w: u32, h: u32) -> Rect {
+        Rect { x, y, w, h }
+    }
+
+    pub fn offset_x(self, offset: u32) -> Rect {
+        Rect::new(self.x.saturating_add(offset), self.y, self.w, self.h)
+    }
+
+    pub fn offset_y(self, offset: u32) -> Rect {
+        Rect::new(self.x, self.y.saturating_add(offset), self.w, self.h)
+    }
+
+    pub fn cutoff_x(self, offset: u32) -> Rect {
+        let offset = offset.min(self.w);
+        Rect::new(self.x + offset, self.y, self.w - offset, self.h)
+    }
+
+    pub fn cutoff_y(self, offset: u32) -> Rect {
+        let offset = offset.min(self.h);
+        Rect::new(self.x, self.y + offset, self.w, self.h - offset)
+    }
+
+    pub fn cutoff_width(self, offset: u32) -> Rect {
+        Rect::new(self.x, self.y, self.w.saturating_sub(offset), self.h)
+    }
+
+    pub fn cutoff_height(self, offset: u32) -> Rect {
+        Rect::new(self.x, self.y, self.w, self.h.saturating_sub(offset))
+    }
+
+    pub fn with_size(self, width: u32, height: u32) -> Rect {
+        Rect::new(self.x, self.y, width, height)
+    }
+
+    /// Removes `insets` from the sides, for padding inside a rectangle or a margin
+    /// around its content.
+    pub fn shrink(self, insets: Insets) -> Rect {
+        self.cutoff_x(insets.left)
+            .cutoff_y(insets.top)
+            .cutoff_width(insets.right)
+            .cutoff_height(insets.bottom)
+    }
+
+    /// Places a child of the given size inside, clamped to fit.
+    pub fn align(self, width: u32, height: u32, horizontal: Align, vertical: Align) -> Rect {
+        let (width, height) = (width.min(self.w), height.min(self.h));
+
+        Rect::new(
+            self.x + horizontal.offset(self.w, width),
+            self.y + vertical.offset(self.h, height),
+            width,
+            height,
+        )
+    }
+
+    /// Splits along `axis` into one rectangle per entry of `sizes`, with `gap` pixels
+    /// between them. Children that don't fit anymore are empty.
+    pub fn split(self, axis: Axis, gap: u32, sizes: &[Size]) -> Vec<Rect> {
+        let extent = match axis {
+            Axis::Horizontal => self.w,
+            Axis::Vertical => self.h,
+        };
+
+        let gaps = gap.saturating_mul(sizes.len().saturating_sub(1) as u32);
+        let available = extent.saturating_sub(gaps);
+
+        let mut lengths = sizes
+            .iter()
+            .map(|size| match *size {
+                Size::Fixed(n) => n,
+                Size::Ratio(r) => (available as f32 * r.clamp(0., 1.)).round() as u32,
+                Size::Fill(_) => 0,
+            })
+            .collect::<Vec<_>>();
+
+        let used = lengths.iter().fold(0u32, |a, b| a.saturating_add(*b));
+        let leftover = available.saturating_sub(used);
+        let weights = sizes.iter().map(|s| match s {
+            Size::Fill(w) => *w,
+            _ => 0,
+        });
+        let total_weight = weights.clone().sum::<u32>();
+
+        if total_weight > 0 {
+            let mut distributed = 0;
+            let last_fill = sizes.iter().rposition(|s| matches!(s, Size::Fill(w) if *w > 0));
+
+            for (index, weight) in weights.enumerate().filter(|(_, w)| *w > 0) {
+                let share = if Some(index) == last_fill {
+                    // the last one absorbs the rounding error
+                    leftover - distributed
+                } else {
+                    (leftover as u64 * weight as u64 / total_weight as u64) as u32
+                };
+
+                lengths[index] = share;
+                distributed += share;
+            }
+        }
+
+        let mut cursor = 0u32;
+
+        lengths
+            .into_iter()
+            .map(|length| {
+                let start = cursor.min(extent);
+                let length = length.min(extent - start);
+                cursor = start.saturating_add(length).saturating_add(gap);
+
+                match axis {
+                    Axis::Horizontal => Rect::new(self.x + start, self.y, length, self.h),
+                    Axis::Vertical => Rect::new(self.x, self.y + start, self.w, length),
+                }
+            })
+            .collect()
+    }
+
+    /// Overlapping part of both rectangles, empty if they don't overlap.
+    pub fn intersect(self, other: Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right()).max(x);
+        let bottom = self.bottom().min(other.bottom()).max(y);
+
+        Rect::new(x, y, right - x, bottom - y)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+
+    pub fn contains(self, x: u32, y: u32) -> bool {
+        (self.x..self.right()).contains(&x) && (self.y..self.bottom()).contains(&y)
+    }
+
+    pub fn x(self) -> u32 {
+        self.x
+    }
+
+    pub fn y(self) -> u32 {
+        self.y
+    }
+
+    pub fn x_signed(self) -> i32 {
+        self.x as _
+    }
+
+    pub fn y_signed(self) -> i32 {
+        self.y as _
+    }
+
+    pub fn right(self) -> u32 {
+        self.x.saturating_add(self.w)
+    }
+
+    pub fn bottom(self) -> u32 {
+        self.y.saturating_add(self.h)
+    }
+
+    pub fn width(self) -> u32 {
+        self.w
+    }
+
+    pub fn height(self) -> u32 {
+        self.h
+    }
+
+    pub fn sdl(self) -> SdlRect {
+        SdlRect::new(self.x as _, self.y as _, self.w, self.h)
+    }
+}
+
+#[cfg(test)]
+mod tests;
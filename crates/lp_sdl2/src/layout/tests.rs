@@ -0,0 +1,71 @@
+use super::{Align, Axis, Insets, Rect, Size};
+
+#[test]
+fn splits_by_fixed_proportional_and_fill_sizes() {
+    let rect = Rect::new(10, 20, 100, 30);
+
+    assert_eq!(
+        rect.split(Axis::Horizontal, 0, &[Size::Fixed(20), Size::Ratio(0.5), Size::Fill(1)]),
+        [Rect::new(10, 20, 20, 30), Rect::new(30, 20, 50, 30), Rect::new(80, 20, 30, 30)],
+    );
+}
+
+#[test]
+fn splits_vertically_with_gaps_and_weights() {
+    let rect = Rect::new(0, 0, 50, 100);
+
+    assert_eq!(
+        rect.split(Axis::Vertical, 10, &[Size::Fill(1), Size::Fixed(20), Size::Fill(2)]),
+        [Rect::new(0, 0, 50, 20), Rect::new(0, 30, 50, 20), Rect::new(0, 60, 50, 40)],
+    );
+}
+
+#[test]
+fn gives_the_rounding_error_to_the_last_fill() {
+    let widths = Rect::new(0, 0, 10, 10)
+        .split(Axis::Horizontal, 0, &[Size::Fill(1), Size::Fill(1), Size::Fill(1)])
+        .into_iter()
+        .map(Rect::width)
+        .collect::<Vec<_>>();
+
+    assert_eq!(widths, [3, 3, 4]);
+}
+
+#[test]
+fn empties_children_that_do_not_fit() {
+    assert_eq!(
+        Rect::new(0, 0, 50, 10).split(Axis::Horizontal, 0, &[Size::Fixed(40), Size::Fixed(40), Size::Fill(1)]),
+        [Rect::new(0, 0, 40, 10), Rect::new(40, 0, 10, 10), Rect::new(50, 0, 0, 10)],
+    );
+    assert_eq!(
+        Rect::new(0, 0, 10, 10).split(Axis::Vertical, 20, &[Size::Fill(1), Size::Fill(1)]),
+        [Rect::new(0, 0, 10, 0), Rect::new(0, 10, 10, 0)],
+    );
+}
+
+#[test]
+fn insets_larger_than_the_rect_leave_it_empty() {
+    let shrunk = Rect::new(5, 5, 10, 10).shrink(Insets::uniform(20));
+
+    assert_eq!(shrunk, Rect::new(15, 15, 0, 0));
+    assert!(shrunk.is_empty());
+    assert_eq!(Rect::new(0, 0, 10, 10).cutoff_x(30), Rect::new(10, 0, 0, 10));
+    assert_eq!(Rect::new(0, 0, 10, 10).cutoff_y(30), Rect::new(0, 10, 10, 0));
+}
+
+#[test]
+fn clips_to_the_overlap() {
+    let rect = Rect::new(0, 0, 10, 10);
+
+    assert_eq!(rect.intersect(Rect::new(5, 5, 10, 10)), Rect::new(5, 5, 5, 5));
+    assert_eq!(rect.intersect(Rect::new(2, 3, 4, 5)), Rect::new(2, 3, 4, 5));
+    assert!(rect.intersect(Rect::new(20, 20, 5, 5)).is_empty());
+}
+
+#[test]
+fn aligns_children_clamped_to_fit() {
+    let rect = Rect::new(0, 0, 100, 50);
+
+    assert_eq!(rect.align(20, 10, Align::Center, Align::End), Rect::new(40, 40, 20, 10));
+    assert_eq!(rect.align(200, 10, Align::End, Align::Start), Rect::new(0, 0, 100, 10));
+}
@@ -1,464 +1,1494 @@
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::render::Canvas;
-use sdl2::video::Window;
-use sdl2::ttf::{Font, Sdl2TtfContext};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
 use sdl2::pixels::Color;
-use sdl2::rect::Rect as SdlRect;
-use fuzzer::{MatchOwned, Fuzzer};
-use config::Rgb;
-use std::time::{Instant, Duration};
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext, WindowPos};
+use fuzzer::MatchOwned;
+use config::{Command, Config, PreviewPosition, Rgb, Severity};
+use clap::Parser;
+use anim::{Easing, Spring, Tween};
+use canvas::{Output, Target, VirtualCanvas};
+use elide::{Elide, Label};
+use font::Fonts;
+use cli::{Action, Cli};
+use ipc::{Remote, Request, Response, Server};
+use layout::{Align, Axis, Insets, Rect, Scale, Size};
+use modes::{Activation, Modes, Row};
+use placement::Display;
+use preview::{Body, Preview, PreviewState, Wake};
+use std::time::{Instant, Duration, SystemTime};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::thread;
 use std::mem;
 use std::cell::{RefMut, RefCell};
+use std::collections::HashMap;
+use std::process::{self, ExitCode};
 use std::rc::Rc;
+use std::sync::{Mutex, PoisonError};
 
 fn sdl_color(rgb: Rgb) -> Color {
     Color::RGB(rgb.red(), rgb.green(), rgb.blue())
 }
 
-fn main() -> anyhow::Result<()> {
-    let app = App::init()?;
+fn main() -> anyhow::Result<ExitCode> {
+    let cli = Cli::parse();
 
-    app.run()?;
+    if let Some(request) = cli.action.as_ref().and_then(Action::request) {
+        return match ipc::send(&request)? {
+            Response::Ok => Ok(ExitCode::SUCCESS),
+            Response::Error { message } => {
+                eprintln!("error: {message}");
+                Ok(ExitCode::FAILURE)
+            }
+        };
+    }
 
-    Ok(())
-}
+    if cli.check_config {
+        return Ok(check_config(&cli));
+    }
 
-#[derive(Debug, Copy, Clone)]
-pub struct Rect {
-    x: u32,
-    y: u32,
-    w: u32,
-    h: u32,
-}
+    let config = cli.config_builder().build()?;
 
-impl From<Rect> for SdlRect {
-    fn from(value: Rect) -> Self {
-        SdlRect::new(value.x() as _, value.y() as _, value.width(), value.height())
+    if cli.dump_config {
+        print!("{}", config.to_toml());
+        return Ok(ExitCode::SUCCESS);
     }
-}
 
-impl Rect {
-    pub fn new(x: u32, y: u32, w: u32, h: u32) -> Rect {
-        Rect { x, y, w, h }
-    }
+    validate(&config)?;
 
-    pub fn offset_x(self, offset: u32) -> Rect {
-        Rect::new(self.x + offset, self.y, self.w, self.h)
-    }
+    let options = Options {
+        query: cli.query.clone().unwrap_or_default(),
+        print: cli.print,
+        daemon: matches!(cli.action, Some(Action::Daemon)),
+    };
 
-    pub fn offset_y(self, offset: u32) -> Rect {
-        Rect::new(self.x, self.y + offset, self.w, self.h)
+    if options.daemon {
+        // the window is only hidden when closed, the daemon goes on
+        sdl2::hint::set("SDL_QUIT_ON_LAST_WINDOW_CLOSE", "0");
     }
 
-    pub fn cutoff_x(self, offset: u32) -> Rect {
-        Rect::new(self.x + offset, self.y, self.w - offset, self.h)
-    }
+    let screen = Screen::open(&config.window)?;
+    // the app's textures borrow the texture creator, which has to outlive it
+    let creator = screen.texture_creator();
+    let mut app = App::init(screen, &creator, config, options)?;
+    app.watch_config(cli.config_builder());
 
-    pub fn cutoff_y(self, offset: u32) -> Rect {
-        Rect::new(self.x, self.y + offset, self.w, self.h - offset)
+    if app.options.daemon {
+        app.serve()?;
     }
 
-    pub fn cutoff_width(self, offset: u32) -> Rect {
-        Rect::new(self.x, self.y, self.w.saturating_sub(offset), self.h)
-    }
+    let code = app.run()?;
 
-    pub fn cutoff_height(self, offset: u32) -> Rect {
-        Rect::new(self.x, self.y, self.w, self.h.saturating_sub(offset))
-    }
+    Ok(ExitCode::from(code))
+}
 
-    pub fn with_size(self, width: u32, height: u32) -> Rect {
-        Rect::new(self.x, self.y, width, height)
-    }
+fn check_config(cli: &Cli) -> ExitCode {
+    let config = match cli.config_builder().build() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    pub fn x(self) -> u32 {
-        self.x
-    }
+    let diagnostics = diagnose(&config);
 
-    pub fn y(self) -> u32 {
-        self.y
-    }
-    
-    pub fn x_signed(self) -> i32 {
-        self.x as _
+    for diagnostic in &diagnostics {
+        eprintln!("{diagnostic}");
     }
 
-    pub fn y_signed(self) -> i32 {
-        self.y as _
+    if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
     }
+}
 
-    pub fn width(self) -> u32 {
-        self.w
-    }
+/// What's wrong with `config`, also with the modes it names.
+fn diagnose(config: &Config) -> Vec<config::Diagnostic> {
+    let mut diagnostics = config.validate();
+    diagnostics.extend(modes::validate(config));
+    diagnostics
+}
 
-    pub fn height(self) -> u32 {
-        self.h
+/// Prints what's wrong with `config`, failing if any of it is an error, like a spring that
+/// never settles.
+fn validate(config: &Config) -> Result<()> {
+    let diagnostics = diagnose(config);
+
+    for diagnostic in &diagnostics {
+        eprintln!("{diagnostic}");
     }
 
-    pub fn sdl(self) -> SdlRect {
-        SdlRect::new(self.x as _, self.y as _, self.w, self.h)
+    match diagnostics.iter().filter(|d| d.severity == Severity::Error).count() {
+        0 => Ok(()),
+        errors => Err(Error::InvalidConfig(errors)),
     }
 }
 
-pub struct Fonts {
-    ctx: Sdl2TtfContext,
-    default_24: Font<'static, 'static>,
-    default_32: Font<'static, 'static>,
+/// Behaviour that comes from the command line rather than the configuration.
+#[derive(Debug, Default)]
+pub struct Options {
+    pub query: String,
+    pub print: bool,
+    /// Stays resident with the window hidden, see [`ipc`].
+    pub daemon: bool,
 }
 
-impl Fonts {
-    pub fn init() -> Result<Self> {
-        let ctx = sdl2::ttf::init().map_err(error::system_ttf)?;
+/// Everything on screen that animates, stepped once per frame.
+pub struct Motion {
+    cursor: Spring,
+    highlight: Spring,
+    /// Offset of the result list in pixels.
+    scroll: Spring,
+    /// Fade-in of the result rows that just appeared, keyed by name.
+    appearing: HashMap<String, Tween>,
+    window: Tween,
+    reduce_motion: bool,
+}
 
-        let load = |name: &str, size: u16| unsafe {
-            Ok(mem::transmute::<
-                Font<'_, 'static>,
-                Font<'static, 'static>,
-            >(ctx.load_font(name, size).map_err(error::asset_font)?))
-        };
+impl Motion {
+    const APPEAR_DURATION: Duration = Duration::from_millis(160);
+    const WINDOW_FADE_DURATION: Duration = Duration::from_millis(120);
 
-        let default_24 = load("default_font.ttf", 24)?;
-        let default_32 = load("default_font.ttf", 32)?;
+    pub fn new(config: &config::Animation) -> Self {
+        let spring = || Spring::with_params(0., config.stiffness, config.damping);
 
-        Ok(Self {
-            ctx,
-            default_24,
-            default_32,
-        })
+        Self {
+            cursor: spring(),
+            highlight: spring(),
+            scroll: spring(),
+            appearing: HashMap::new(),
+            window: Tween::new(0., 1., Self::WINDOW_FADE_DURATION, Easing::QuadOut),
+            reduce_motion: config.reduce_motion,
+        }
     }
 
-    pub fn default_24(&self) -> &Font<'_, 'static> {
-        unsafe { mem::transmute(&self.default_24) }
+    pub fn step(&mut self, dt: Duration) {
+        if self.reduce_motion {
+            return self.finish();
+        }
+
+        [&mut self.cursor, &mut self.highlight, &mut self.scroll].into_iter().for_each(|s| s.step(dt));
+        self.appearing.values_mut().chain([&mut self.window]).for_each(|t| t.step(dt));
     }
 
-    pub fn default_32(&self) -> &Font<'_, 'static> {
-        unsafe { mem::transmute(&self.default_32) }
+    /// Ends every animation where it's going.
+    pub fn finish(&mut self) {
+        [&mut self.cursor, &mut self.highlight, &mut self.scroll].into_iter().for_each(Spring::snap);
+        self.appearing.values_mut().chain([&mut self.window]).for_each(Tween::finish);
     }
-}
 
-/// Super simple spring
-#[derive(Clone)]
-pub struct Spring {
-    target: f64,
-    value: f64,
-    velocity: f64,
-}
+    /// Starts the fade-in of rows that weren't shown on the previous frame.
+    pub fn track_rows<'a>(&mut self, names: impl IntoIterator<Item = &'a str>) {
+        let names = names.into_iter().collect::<Vec<_>>();
 
-impl Spring {
-    pub fn new(value: f64) -> Self {
-        Self {
-            target: value,
-            value,
-            velocity: 0.0,
+        self.appearing.retain(|name, _| names.contains(&name.as_str()));
+
+        for name in names {
+            self.appearing
+                .entry(name.to_owned())
+                .or_insert_with(|| Tween::new(0., 1., Self::APPEAR_DURATION, Easing::CubicOut));
         }
     }
 
-    pub fn update_target(&mut self, new_value: f64) {
-        self.target = new_value;
+    pub fn row_opacity(&self, name: &str) -> f64 {
+        self.appearing.get(name).map_or(1., Tween::value)
     }
 
-    pub fn simulate(&mut self) {
-        self.velocity = self.velocity * 0.1 + (self.target - self.value) * 0.4;
-        self.value += self.velocity;
+    pub fn is_settled(&self) -> bool {
+        self.cursor.is_settled()
+            && self.highlight.is_settled()
+            && self.scroll.is_settled()
+            && self.window.is_settled()
+            && self.appearing.values().all(Tween::is_settled)
     }
-
-    pub fn value(&self) -> f64 {
-        self.value }
 }
 
-
 pub struct Resources {
-    styling: (),
-    fuzzer: Fuzzer<String>,
+    config: Config,
+    query: String,
+    modes: Modes,
+    /// Kept to rebuild the modes, which wake the event loop with results from the background.
+    events: sdl2::EventSubsystem,
     fonts: Fonts,
-    cursor: RefCell<Spring>,
+    motion: RefCell<Motion>,
+    scale: Scale,
+    selected: usize,
+    /// Byte offset of the caret in the query.
+    caret: usize,
+    /// Index of the first result in view.
+    scroll: usize,
+    /// Rows scrolled on a touchpad that don't add up to a whole one yet.
+    wheel: f32,
 }
 
 impl Resources {
-    pub fn new() -> Result<Self> {
+    pub fn new(config: Config, scale: Scale, events: sdl2::EventSubsystem) -> Result<Self> {
         Ok(Self {
-            styling: (),
-            fuzzer: Fuzzer::new(vec![
-                                "docs".to_owned(),
-                                "obsidian".to_owned(),
-                                "two".to_owned(),
-                                "three".to_owned(),
-                                "four".to_owned(),
-            ]),
-            fonts: Fonts::init()?,
-            cursor: RefCell::new(Spring::new(0.)),
+            motion: RefCell::new(Motion::new(&config.animation)),
+            fonts: Fonts::init(&config.font, scale)?,
+            scale,
+            query: String::new(),
+            modes: Modes::new(&config, &events)?,
+            events,
+            config,
+            selected: 0,
+            caret: 0,
+            scroll: 0,
+            wheel: 0.,
         })
     }
 
-    pub fn styling(&self) {
-        self.styling
+    /// Applies a changed configuration, keeping the query and the current mode.
+    pub fn reload(&mut self, config: Config) -> Result<()> {
+        let mut modes = Modes::new(&config, &self.events)?;
+        self.fonts.reload(&config.font)?;
+
+        // the mode may be gone from the new configuration
+        let _ = modes.switch_to(self.modes.current());
+        self.modes = modes;
+        self.config = config;
+
+        Ok(())
+    }
+
+    /// Switches to the mode called `mode`.
+    pub fn set_mode(&mut self, mode: &str) -> Result<()> {
+        self.modes.switch_to(mode)
+    }
+
+    /// Starts over with `query` typed in, and the animations of a newly opened window.
+    pub fn reset(&mut self, query: &str) {
+        self.modes.close_menu();
+        self.query.clear();
+        self.query.push_str(query);
+
+        self.caret = query.len();
+        self.selected = 0;
+        self.scroll = 0;
+        self.wheel = 0.;
+        self.motion = RefCell::new(Motion::new(&self.config.animation));
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
     }
 
     pub fn prompt_content(&self) -> &str {
-        self.fuzzer.input()
+        &self.query
     }
 
     pub fn fonts(&self) -> &Fonts {
         &self.fonts
     }
 
-    pub fn cursor_spring(&self) -> RefMut<Spring> {
-        self.cursor.borrow_mut()
+    /// Logical pixels to pixels on screen.
+    pub fn px(&self, logical: u32) -> u32 {
+        self.scale.px(logical)
+    }
+
+    pub fn set_scale(&mut self, scale: Scale) {
+        self.scale = scale;
+        self.fonts.set_scale(scale);
+    }
+
+    pub fn motion(&self) -> RefMut<'_, Motion> {
+        self.motion.borrow_mut()
     }
+
+    /// Whether frames have to keep coming for an animation to play out.
+    pub fn is_animating(&self) -> bool {
+        !self.motion.borrow().is_settled()
+    }
+}
+
+pub trait Render {
+    fn render<T: Output>(&self, canvas: &mut VirtualCanvas<'_, T>, resources: &Resources) -> Result<()>;
 }
 
-pub struct VirtualCanvas {
-    canvas: Rc<RefCell<Canvas<Window>>>,
-    area: Rect,
+struct Prompt {
+    selected: usize,
+    /// Number of results.
+    count: usize,
 }
 
-impl VirtualCanvas {
-    pub fn new(canvas: Rc<RefCell<Canvas<Window>>>, area: Rect) -> Self {
-        Self { canvas, area }
+impl Prompt {
+    const MARKER: &'static str = ">";
+    const PAD: u32 = 10;
+    /// Space around the name of a tab, within its background.
+    const TAB_PAD: u32 = 8;
+
+    /// Where the query is written within the prompt `area`.
+    fn text_area(area: Rect, resources: &Resources) -> Result<Rect> {
+        let font = resources.fonts().prompt()?;
+        let (marker, _) = font.font().size_of(Self::MARKER).map_err(error::draw)?;
+
+        Ok(area
+            .shrink(Insets::symmetric(0, resources.px(4)))
+            .cutoff_x(marker + resources.px(Self::PAD)))
     }
+}
 
-    pub fn root(canvas: Rc<RefCell<Canvas<Window>>>) -> Self {
-        let v = canvas.borrow().viewport();
+impl Render for Prompt {
+    fn render<T: Output>(&self, canvas: &mut VirtualCanvas<'_, T>, resources: &Resources) -> Result<()> {
+        let content = resources.prompt_content();
+        let font = &resources.fonts().prompt()?;
 
-        Self {
-            area: Rect::new(v.x as _, v.y as _, v.width(), v.height()),
-            canvas,
+        let px = |n| resources.px(n);
+
+        let colors = &resources.config().colors;
+
+        canvas.fill_area(colors.prompt_background, canvas.area())?;
+
+        let mut inner = canvas.child(canvas.area()).padded(Insets::symmetric(0, px(4)));
+        let prompt_area = inner.write_text(Self::MARKER, font, colors.text, inner.area())?;
+        let mut text_area = Self::text_area(canvas.area(), resources)?;
+
+        if self.count > 0 {
+            let counter = format!("{} of {}", self.selected + 1, self.count);
+            let subtitle = &resources.fonts().subtitle()?;
+            let counter_area = inner.write_text_aligned(&counter, subtitle, colors.subtitle, inner.area(), Align::End)?;
+            text_area = text_area.cutoff_width(counter_area.width() + px(Self::PAD));
         }
-    }
 
-    pub fn subdivide_exact(self, height: u32) -> Option<(Self, Self)> {
-        let VirtualCanvas { canvas, area } = self;
+        let tabs = resources.modes.tabs().collect::<Vec<_>>();
+
+        // a single mode needs no tabs
+        if tabs.len() > 1 {
+            let subtitle = &resources.fonts().subtitle()?;
+            let active = resources.modes.active(&resources.query);
+            let height = subtitle.font().height() as u32 + px(Self::TAB_PAD);
+            let mut right = text_area.right();
+
+            for name in tabs.iter().rev() {
+                let width = subtitle.width(name)? + 2 * px(Self::TAB_PAD);
+                let tab = Rect::new(right.saturating_sub(width), text_area.y(), width, text_area.height())
+                    .align(width, height, Align::Center, Align::Center);
+
+                let color = if *name == active {
+                    inner.fill_rounded(colors.selection, tab, px(resources.config().layout.corner_radius))?;
+                    colors.text
+                } else {
+                    colors.subtitle
+                };
+
+                inner.write_text_aligned(name, subtitle, color, tab, Align::Center)?;
+                right = tab.x().saturating_sub(px(Self::TAB_PAD / 2));
+            }
 
-        area.height()
-            .ge(&height)
-            .then(|| {
-                let area_subdivided = area.with_size(area.width(), height);
-                let area_remaining = area.cutoff_y(height);
-                (Self::new(Rc::clone(&canvas), area_subdivided), Self::new(canvas, area_remaining))
-            })
-    }
+            text_area = text_area.cutoff_width(text_area.right().saturating_sub(right) + px(Self::PAD));
+        }
 
-    pub fn subdivide_up_to(self, max_height: u32) -> (Self, Option<Self>) {
-        let VirtualCanvas { canvas, area } = self;
+        if !content.is_empty() {
+            inner.write_glyphs(content, font, colors.text, text_area)?;
+        }
 
-        let area_subdivided = if area.height() >= max_height {
-            area.with_size(area.width(), max_height)
-        } else {
-            area
-        };
+        let caret = inner.measure_glyphs(&content[..resources.caret], font)?.min(text_area.width());
 
-        let area_remaining = area.cutoff_y(max_height);
+        let mut motion = resources.motion();
+        motion.cursor.update_target(caret as f64);
 
-        let rem_canvas = area_remaining.height()
-            .ne(&0)
-            .then(|| Self::new(Rc::clone(&canvas), area_remaining));
+        let x = (text_area.x() as f64 + motion.cursor.value()).round() as u32;
+        let cursor = Rect::new(x.saturating_sub(px(1)), prompt_area.y(), px(2).max(1), prompt_area.height());
+        inner.fill_area(colors.cursor, cursor)?;
 
-        (Self::new(canvas, area_subdivided), rem_canvas)
+        Ok(())
     }
+}
+
+struct Choice {
+    matched: MatchOwned<Command>,
+}
+
+impl Render for Choice {
+    fn render<T: Output>(&self, canvas: &mut VirtualCanvas<'_, T>, resources: &Resources) -> Result<()> {
+        let font = &resources.fonts().title()?;
+        let colors = &resources.config().colors;
+
+        let item = &self.matched.item;
+        let icons = &resources.config().icons;
+
+        let px = |n| resources.px(n);
+        let icon_size = px(icons.size);
+
+        let inner = canvas.child(canvas.area()).padded(Insets::symmetric(0, px(4)));
+        let icon_slot = if icons.enabled { inner.area().height() } else { 0 };
+        let mut columns = inner.split(Axis::Horizontal, px(8), &[Size::Fixed(icon_slot), Size::Fill(1)]).into_iter();
+        let (mut icon, mut title) = (columns.next().expect("icon"), columns.next().expect("title"));
+
+        if icons.enabled {
+            let found = match &item.icon {
+                Some(name) => icon.draw_icon(name, icon_size, icon.area())?,
+                None => false,
+            };
+
+            if !found {
+                let badge = icon.area().align(icon_size, icon_size, Align::Center, Align::Center);
+                icon.fill_rounded(icon::badge_color(&item.name), badge, resources.px(resources.config().layout.corner_radius))?;
+                icon.write_text_aligned(&icon::badge_letter(&item.name), font, Rgb::WHITE, badge, Align::Center)?;
+            }
+        }
+
+        let subtitle = item.subtitle().filter(|_| !resources.config().layout.compact);
+        let name = Label { text: &item.name, elide: Elide::End, highlighted: &self.matched.indices };
+
+        match subtitle {
+            Some(subtitle) => {
+                let mut lines = title.rows(&[Size::Fill(3), Size::Fill(2)]).into_iter();
+                let (mut first, mut second) = (lines.next().expect("title"), lines.next().expect("subtitle"));
+
+                first.write_label(&name, font, colors.text, colors.highlight, first.area())?;
+                let font = &resources.fonts().subtitle()?;
+                second.write_label(&Label::new(subtitle), font, colors.subtitle, colors.subtitle, second.area())?;
+            }
+            None => {
+                title.write_label(&name, font, colors.text, colors.highlight, title.area())?;
+            }
+        }
 
-    pub fn fill_area(&mut self, color: Rgb, area: Rect) -> Result<()> {
-        let mut canvas = self.canvas.borrow_mut();
-        canvas.set_draw_color(sdl_color(color));
-        canvas.fill_rect(Some(area.sdl())).map_err(error::draw)?;
         Ok(())
     }
+}
 
-    pub fn write_text(&mut self, content: &str, font: &Font<'_, 'static>, color: Rgb, area: Rect) -> Result<Rect> {
-        let surface = font
-            .render(content)
-            .blended(sdl_color(color))
-            .map_err(error::draw)?;
+/// The name of a source above its results, in a combined mode.
+struct Heading {
+    name: String,
+}
 
-        let mut canvas = self.canvas.borrow_mut();
+impl Render for Heading {
+    fn render<T: Output>(&self, canvas: &mut VirtualCanvas<'_, T>, resources: &Resources) -> Result<()> {
+        let font = &resources.fonts().subtitle()?;
+        let colors = &resources.config().colors;
+        let area = canvas.area().shrink(Insets::symmetric(0, resources.px(8)));
 
-        let creator = canvas.texture_creator();
-        let texture = surface.as_texture(&creator).map_err(error::draw)?;
-        let query = texture.query();
-        let (width, height) = (query.width, query.height);
+        canvas.write_text(&self.name.to_uppercase(), font, colors.subtitle, area)?;
+        Ok(())
+    }
+}
 
-        let hdelta = area.height() as i32 - height as i32;
-        let dst = Rect::new(
-            area.x(),
-            area.y().overflowing_add_signed(hdelta / 2).0,
-            width.min(area.width()),
-            height.min(area.height()),
-        );
+/// Where the view is in the whole result list, along the right edge.
+struct Scrollbar {
+    /// Pixels scrolled past.
+    offset: f64,
+    /// Pixels of the list in view.
+    visible: u32,
+    /// Pixels the whole list would take.
+    total: u32,
+}
 
-        canvas.copy(
-            &texture,
-            Some(SdlRect::new(0, 0, area.width(), area.height())),
-            Some(dst.sdl()),
-        ).map_err(error::draw)?;
+impl Render for Scrollbar {
+    fn render<T: Output>(&self, canvas: &mut VirtualCanvas<'_, T>, resources: &Resources) -> Result<()> {
+        let px = |n| resources.px(n);
+        let area = canvas.area();
 
-        Ok(dst)
-    }
+        let track = Rect::new(area.right().saturating_sub(px(6)), area.y() + px(2), px(4), area.height().saturating_sub(px(4)));
+        let length = (track.height() as u64 * self.visible as u64 / self.total.max(1) as u64) as u32;
+        let length = length.max(px(16)).min(track.height());
+
+        let scrollable = self.total.saturating_sub(self.visible).max(1) as f64;
+        let position = (self.offset / scrollable).clamp(0., 1.) * (track.height() - length) as f64;
+        let thumb = track.offset_y(position.round() as u32).with_size(track.width(), length);
 
-    pub fn area(&self) -> Rect {
-        self.area
+        let mut scrollbar = canvas.child(track).with_opacity(0.6);
+        scrollbar.fill_rounded(resources.config().colors.subtitle, thumb, px(2))
     }
 }
 
-pub trait Render {
-    fn render(&self, canvas: &mut VirtualCanvas, resources: &Resources) -> Result<()>;
+struct PreviewPane<'a> {
+    preview: Option<&'a Preview>,
 }
 
-struct Prompt;
+impl Render for PreviewPane<'_> {
+    fn render<T: Output>(&self, canvas: &mut VirtualCanvas<'_, T>, resources: &Resources) -> Result<()> {
+        let colors = &resources.config().colors;
+        let font = &resources.fonts().subtitle()?;
+        let line_height = font.font().recommended_line_spacing().max(1) as u32;
 
-impl Render for Prompt {
-    fn render(&self, canvas: &mut VirtualCanvas, resources: &Resources) -> Result<()> {
-        let content = resources.prompt_content();
-        let font = resources.fonts().default_32();
+        let radius = resources.px(resources.config().layout.corner_radius);
+        let pane = canvas.area().shrink(Insets::uniform(resources.px(4)));
+        canvas.fill_rounded(colors.prompt_background, pane, radius)?;
 
-        const PROMPT_PAD: u32 = 10;
+        let Some(preview) = self.preview else {
+            return Ok(());
+        };
 
-        canvas.fill_area(Rgb::new(32, 30, 35), canvas.area())?;
-        let prompt_area = canvas.write_text(">", font, Rgb::ALMOST_WHITE, canvas.area().cutoff_x(4))?;
-        let min_right = prompt_area.x() + prompt_area.width() + PROMPT_PAD;
-        let mut more_right = 0;
+        let mut inner = canvas.child(canvas.area()).padded(Insets::uniform(resources.px(8)));
+        let mut area = inner.area();
 
-        if !content.is_empty() {
-            let text_area = canvas.area().cutoff_x(prompt_area.width() + PROMPT_PAD);
-            let area = canvas.write_text(content, font, Rgb::ALMOST_WHITE, text_area)?;
-            more_right = area.width();
+        let write_line = |inner: &mut VirtualCanvas<'_, T>, area: &mut Rect, line: &str, color: Rgb| -> Result<()> {
+            inner.write_label(&Label::new(line), font, color, color, area.with_size(area.width(), line_height))?;
+            *area = area.cutoff_y(line_height);
+            Ok(())
+        };
+
+        for line in &preview.header {
+            write_line(&mut inner, &mut area, line, colors.subtitle)?;
         }
 
-        let mut spring = resources.cursor_spring();
-        spring.update_target(more_right as f64);
-        spring.simulate();
+        if !preview.header.is_empty() {
+            area = area.cutoff_y(line_height / 2);
+        }
 
-        let cursor = Rect::new(min_right + spring.value() as u32 - 1, prompt_area.y(), 2, prompt_area.height());
-        canvas.fill_area(Rgb::WHITE, cursor)?;
+        match &preview.body {
+            Body::Empty => {}
+            Body::Text(lines) => {
+                for line in lines {
+                    if area.is_empty() {
+                        break;
+                    }
+
+                    write_line(&mut inner, &mut area, line, colors.text)?;
+                }
+            }
+            Body::Image(path) => {
+                let size = area.width().min(area.height());
+                inner.draw_icon(&path.to_string_lossy(), size, area)?;
+            }
+            Body::Pending => write_line(&mut inner, &mut area, "…", colors.subtitle)?,
+            Body::Failed(message) => write_line(&mut inner, &mut area, message, colors.subtitle)?,
+        }
 
         Ok(())
     }
 }
 
-struct Choice {
-    matched: MatchOwned<String>,
+/// The configuration file, checked for changes while the launcher is open.
+struct ConfigWatch {
+    builder: config::Builder,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    checked: Instant,
 }
 
-impl Render for Choice {
-    fn render(&self, canvas: &mut VirtualCanvas, resources: &Resources) -> Result<()> {
-        let font = resources.fonts().default_24();
+impl ConfigWatch {
+    const INTERVAL: Duration = Duration::from_secs(1);
 
-        let name = &self.matched.item;
+    fn new(builder: config::Builder) -> Option<Self> {
+        let path = builder.file_path()?;
 
-        canvas.write_text(name, font, Rgb::ALMOST_WHITE, canvas.area().cutoff_x(4))?;
+        Some(Self {
+            modified: modified(&path),
+            builder,
+            path,
+            checked: Instant::now(),
+        })
+    }
 
-        Ok(())
+    /// Builds the configuration again if the file changed since the last check.
+    fn poll(&mut self) -> Option<config::Result<Config>> {
+        if self.checked.elapsed() < Self::INTERVAL {
+            return None;
+        }
+
+        self.checked = Instant::now();
+        let modified = modified(&self.path);
+
+        if modified == self.modified {
+            return None;
+        }
+
+        self.modified = modified;
+        Some(self.builder.clone().build())
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// The configured scale, else the one of a high-DPI drawable, else the one from the
+/// environment.
+fn detect_scale(config: &config::Window, backing: f32) -> Scale {
+    match config.scale {
+        Some(scale) => Scale::new(scale),
+        None if backing > 1. => Scale::new(backing),
+        None => Scale::from_env().unwrap_or(Scale::ONE),
     }
 }
 
-pub struct App {
+/// Where the parts of the last frame were drawn, to tell what the pointer is on.
+#[derive(Default)]
+struct HitAreas {
+    prompt: Rect,
+    /// Where the query starts within the prompt.
+    text: Rect,
+    /// The results shown, by index.
+    rows: Vec<(usize, Rect)>,
+}
+
+/// What the app draws to, set up ahead of the app so that the texture creator its textures
+/// borrow can be made before it.
+pub struct Screen<T: Output = Window> {
     context: sdl2::Sdl,
-    resources: Resources,
-    canvas: Rc<RefCell<Canvas<Window>>>,
+    canvas: Canvas<T>,
+    vsync: bool,
+    scale: Scale,
 }
 
-impl App {
-    pub fn init() -> Result<Self> {
+impl<T: Output> Screen<T> {
+    pub fn texture_creator(&self) -> TextureCreator<T::TextureContext> {
+        T::texture_creator(&self.canvas)
+    }
+}
+
+impl Screen<Window> {
+    /// Opens the window, hidden until the app has placed it.
+    pub fn open(config: &config::Window) -> Result<Self> {
         let context = sdl2::init().map_err(error::system_core)?;
-        let canvas = context.video()
-            .map_err(error::system_video)?
-            .window("Board", 800, 600)
-            .position_centered()
+        let video = context.video().map_err(error::system_video)?;
+        let display = Display::find(&video, config.position)?;
+        let (width, height) = display.window_size(config, 1.);
+
+        let canvas = video
+            .window("Board", width, height)
+            .set_window_flags(placement::window_flags(config))
             .build()
             .map_err(error::window_init)?
             .into_canvas()
+            .present_vsync()
             .build()
             .map_err(error::window_init)?;
 
-        Ok(App {
-            context,
-            resources: Resources::new()?,
-            canvas: Rc::new(RefCell::new(canvas)),
-        })
+        let vsync_flag = sdl2::sys::SDL_RendererFlags::SDL_RENDERER_PRESENTVSYNC as u32;
+        let vsync = canvas.info().flags & vsync_flag != 0;
+
+        let backing = Window::backing_scale(&canvas).unwrap_or(1.);
+        let scale = detect_scale(config, backing);
+
+        Ok(Self { context, canvas, vsync, scale })
     }
+}
 
-    pub fn run(mut self) -> Result<i32> {
+pub struct App<'a, T: Output = Window> {
+    context: sdl2::Sdl,
+    resources: Resources,
+    options: Options,
+    preview: PreviewState,
+    watch: Option<ConfigWatch>,
+    /// Accepts the requests of clients while running as a daemon.
+    server: Option<Server>,
+    /// Whether the window is on screen, in daemon mode it's hidden in between uses.
+    shown: bool,
+    canvas: Rc<RefCell<Target<'a, T>>>,
+    /// Height of the window showing all it can, `None` without a window to resize.
+    max_height: Option<u32>,
+    hits: HitAreas,
+    /// Whether the window had the focus yet, it may lose it before getting it first.
+    focused: bool,
+    /// Whether the state changed since the last frame was presented.
+    damaged: bool,
+    vsync: bool,
+    /// When the last animation frame was drawn, `None` while idle.
+    last_frame: Option<Instant>,
+}
+
+enum Flow {
+    Continue,
+    /// The launcher is done, with the exit code of the process. A daemon hides instead.
+    Exit(u8),
+    /// The process ends, even as a daemon.
+    Stop,
+}
+
+impl<'a> App<'a, Window> {
+    /// The app on the window of `screen`, drawing with `creator`, the screen's.
+    pub fn init(
+        screen: Screen,
+        creator: &'a TextureCreator<WindowContext>,
+        config: Config,
+        options: Options,
+    ) -> Result<Self> {
+        let mut app = App::with_screen(screen, creator, config, options)?;
+        app.place_window()?;
+
+        if app.options.daemon {
+            app.shown = false;
+        } else {
+            app.canvas.borrow_mut().canvas().window_mut().show();
+        }
+
+        Ok(app)
+    }
+
+    /// Sizes the window and moves it to the configured position.
+    fn place_window(&mut self) -> Result<()> {
+        let video = self.context.video().map_err(error::system_video)?;
+        let config = &self.resources.config.window;
+        let display = Display::find(&video, config.position)?;
+
+        let mut target = self.canvas.borrow_mut();
+        let backing = Window::backing_scale(target.canvas()).unwrap_or(1.);
+
+        // without a high-DPI drawable doing it, the window itself is made larger
+        let size = display.window_size(config, self.resources.scale.factor() / backing);
+        let (x, y) = display.window_origin(config.position, size);
+        let window = target.canvas().window_mut();
+
+        window.set_size(size.0, size.1).map_err(error::window_init)?;
+        window.set_position(WindowPos::Positioned(x), WindowPos::Positioned(y));
+        self.max_height = Some(size.1);
+
+        Ok(())
+    }
+
+    /// Starts taking requests from clients, see [`ipc`].
+    pub fn serve(&mut self) -> Result<()> {
+        let events = self.context.event().map_err(error::system_event)?;
+        self.server = Some(Server::start(&events)?);
+        Ok(())
+    }
+
+    fn handle_remote(&mut self, remote: Remote) -> Result<Flow> {
+        let result = match &remote.request {
+            Request::Show { mode, query } => self.show(mode.as_deref(), query.as_deref()),
+            Request::Toggle { mode } if !self.shown => self.show(mode.as_deref(), None),
+            Request::Hide | Request::Toggle { .. } => {
+                self.hide();
+                Ok(())
+            }
+            Request::Quit => {
+                remote.reply(Response::Ok);
+                return Ok(Flow::Stop);
+            }
+        };
+
+        remote.reply(match result {
+            Ok(()) => Response::Ok,
+            Err(e) => Response::Error { message: e.to_string() },
+        });
+
+        Ok(Flow::Continue)
+    }
+
+    /// Brings the window up afresh, like a newly started launcher.
+    fn show(&mut self, mode: Option<&str>, query: Option<&str>) -> Result<()> {
+        if let Some(mode) = mode {
+            self.resources.set_mode(mode)?;
+        }
+
+        self.resources.reset(query.unwrap_or_default());
+        self.place_window()?;
+
+        let mut target = self.canvas.borrow_mut();
+        let window = target.canvas().window_mut();
+        window.show();
+        window.raise();
+
+        self.shown = true;
+        self.damaged = true;
+        self.last_frame = None;
+
+        Ok(())
+    }
+
+    fn hide(&mut self) {
+        self.canvas.borrow_mut().canvas().window_mut().hide();
+        // stops the preview command of the selected item, and what the modes run
+        self.preview.select(None);
+        self.resources.modes.stop();
+
+        // nothing is drawn while hidden, what was left to draw would keep the loop spinning
+        self.resources.motion().finish();
+        self.shown = false;
+        self.focused = false;
+        self.damaged = false;
+    }
+
+    pub fn run(mut self) -> Result<u8> {
         let mut pump = self.context.event_pump().map_err(error::system_event_pump)?;
 
         'main: loop {
             let start = Instant::now();
 
-            for ev in pump.poll_iter() {
-                use Event::*;
+            // nothing will change on screen until an event comes in, so block on it instead
+            // of drawing identical frames, or any at all while hidden
+            let first = if self.shown && (self.damaged || self.resources.is_animating()) {
+                None
+            } else {
+                pump.wait_event_timeout(self.idle_timeout())
+            };
+
+            for ev in first.into_iter().chain(pump.poll_iter()) {
+                let flow = match ev.as_user_event_type::<Remote>() {
+                    Some(remote) => self.handle_remote(remote)?,
+                    None => self.handle_event(ev)?,
+                };
+
+                match flow {
+                    Flow::Continue => {}
+                    Flow::Exit(code) if self.server.is_none() => break 'main Ok(code),
+                    Flow::Exit(_) => self.hide(),
+                    Flow::Stop => break 'main Ok(0),
+                }
+            }
+
+            self.reload_config();
+
+            if !self.shown {
+                self.last_frame = None;
+                continue;
+            }
+
+            self.update_modes();
+            self.update_preview();
+            self.fit_height()?;
+
+            if !self.damaged && !self.resources.is_animating() {
+                self.last_frame = None;
+                continue;
+            }
+
+            // coming out of idle, pretend a single frame went by
+            let dt = self.last_frame.map_or(Duration::from_secs_f64(1. / 60.), |last| last.elapsed());
+            self.last_frame = Some(start);
+            self.draw_frame(dt)?;
+
+            // with vsync, presenting already paces the frames
+            if !self.vsync {
+                let time = start.elapsed();
+                let sixty_fps = Duration::from_secs_f64(1. / 60.);
 
-                if matches!(ev, Quit { .. } | KeyDown { keycode: Some(Keycode::Escape), .. }) {
-                    println!("Quitting");
-                    break 'main Ok(0);
+                if sixty_fps > time {
+                    thread::sleep(sixty_fps - time);
                 }
+            }
+        }
+    }
 
-                if let KeyDown { keycode: Some(kc), .. } = ev {
-                    let name = kc.name();
-                    let mut chars = name.chars().peekable();
-                    let first = chars.next().expect("non-empty name");
-                    let input = self.resources.fuzzer.input_mut();
-
-                    if first.is_alphanumeric() && chars.peek().is_none() {
-                        input.push(first.to_ascii_lowercase());
-                    } else if matches!(kc, Keycode::Space) {
-                        input.push(' ');
-                    } else if matches!(kc, Keycode::Backspace) {
-                        let _ = input.pop();
-                    }
+    /// Shrinks the window to the prompt and the rows shown with `auto_height`, grows it
+    /// back up to its full height otherwise.
+    fn fit_height(&mut self) -> Result<()> {
+        let Some(max_height) = self.max_height else {
+            return Ok(());
+        };
+
+        let count = self.match_count();
+        let config = &self.resources.config;
+        // a preview below the list needs the room whatever the number of rows
+        let preview_below = config.preview.enabled && config.preview.position == PreviewPosition::Bottom;
+
+        let height = if config.window.auto_height && !preview_below {
+            let row_height = self.resources.px(config.layout.effective_row_height());
+            let rows = count.min(config.layout.lines as usize) as u32;
+            let content = self.resources.px(Self::PROMPT_HEIGHT) + rows * row_height;
+            let backing = Window::backing_scale(self.canvas.borrow_mut().canvas()).unwrap_or(1.);
+
+            ((content as f32 / backing).ceil() as u32).clamp(1, max_height)
+        } else {
+            max_height
+        };
+
+        let mut target = self.canvas.borrow_mut();
+        let window = target.canvas().window_mut();
+        let (width, current) = window.size();
+
+        if height != current {
+            window.set_size(width, height).map_err(error::window_init)?;
+            self.damaged = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Registers `Wake` with SDL once per process: the registration outlives the SDL context, and
+/// registering again fails, so every app after the first one reuses it.
+fn register_wake(events: &sdl2::EventSubsystem) -> Result<()> {
+    static REGISTERED: Mutex<bool> = Mutex::new(false);
+
+    let mut registered = REGISTERED.lock().unwrap_or_else(PoisonError::into_inner);
+    if !*registered {
+        events.register_custom_event::<Wake>().map_err(error::system_event)?;
+        *registered = true;
+    }
+    Ok(())
+}
+
+impl<'a, T: Output> App<'a, T> {
+    /// How long to block for events while idle, before checking the state again.
+    const IDLE_TIMEOUT_MS: u32 = 1000;
+    const PROMPT_HEIGHT: u32 = 64;
+
+    fn with_screen(
+        screen: Screen<T>,
+        creator: &'a TextureCreator<T::TextureContext>,
+        config: Config,
+        mut options: Options,
+    ) -> Result<Self> {
+        let Screen { context, canvas, vsync, scale } = screen;
+        let events = context.event().map_err(error::system_event)?;
+        register_wake(&events)?;
+        let preview = PreviewState::new(&config.preview, events.clone());
+
+        let icon_theme = config.icons.theme.clone();
+        let mut resources = Resources::new(config, scale, events)?;
+        resources.query = mem::take(&mut options.query);
+        resources.caret = resources.query.len();
+
+        Ok(App {
+            context,
+            resources,
+            options,
+            preview,
+            watch: None,
+            server: None,
+            shown: true,
+            canvas: Rc::new(RefCell::new(Target::new(canvas, creator, icon_theme.as_deref())?)),
+            max_height: None,
+            hits: HitAreas::default(),
+            focused: false,
+            damaged: true,
+            vsync,
+            last_frame: None,
+        })
+    }
+
+    /// Advances the animations by `dt` and draws the next frame.
+    fn draw_frame(&mut self, dt: Duration) -> Result<()> {
+        self.resources.motion().step(dt);
+        self.damaged = false;
+        self.render()
+    }
+
+    fn handle_event(&mut self, ev: Event) -> Result<Flow> {
+        use Event::*;
+
+        match ev {
+            // a daemon only quits when signalled, closing the window just hides it
+            Quit { .. } if self.options.daemon => return Ok(Flow::Stop),
+            Quit { .. } | Window { win_event: WindowEvent::Close, .. } => return Ok(Flow::Exit(1)),
+            // with a menu open, Escape only closes the menu
+            KeyDown { keycode: Some(Keycode::Escape), .. } if !self.resources.modes.in_menu() => {
+                return Ok(Flow::Exit(1));
+            }
+            Window { win_event: WindowEvent::SizeChanged(..), .. } => {
+                self.update_scale();
+                self.damaged = true;
+            }
+            Window { win_event: WindowEvent::Exposed | WindowEvent::Shown, .. } => {
+                self.damaged = true;
+            }
+            Window { win_event: WindowEvent::FocusGained, .. } => {
+                self.focused = true;
+            }
+            // clicking outside the window takes the focus away too
+            Window { win_event: WindowEvent::FocusLost, .. }
+                if self.focused && self.resources.config.mouse.close_on_focus_loss =>
+            {
+                return Ok(Flow::Exit(1));
+            }
+            KeyDown { keycode: Some(kc), keymod, .. } => return self.handle_key(kc, keymod),
+            TextInput { text, .. } => self.insert(&text),
+            MouseMotion { x, y, .. } => self.hover(x, y),
+            MouseButtonDown { mouse_btn: MouseButton::Left, clicks, x, y, .. } => return self.click(x, y, clicks),
+            MouseWheel { y, precise_y, direction, .. } => {
+                let flip = if direction == MouseWheelDirection::Flipped { -1 } else { 1 };
+                self.wheel(y * flip, precise_y * flip as f32);
+            }
+            // background work finished, it's picked up once the events are handled
+            ev if ev.as_user_event_type::<Wake>().is_some() => {}
+            _ => {}
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    fn handle_key(&mut self, kc: Keycode, keymod: Mod) -> Result<Flow> {
+        match kc {
+            Keycode::Return | Keycode::KpEnter if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                self.open_menu();
+                return Ok(Flow::Continue);
+            }
+            Keycode::Return | Keycode::KpEnter => {
+                return self.launch_selected(keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD));
+            }
+            Keycode::Escape => {
+                self.close_menu();
+                return Ok(Flow::Continue);
+            }
+            Keycode::Up | Keycode::Down | Keycode::PageUp | Keycode::PageDown => {
+                let selected = self.resources.selected;
+                let page = self.resources.config.layout.lines.max(1) as usize;
+
+                self.select(match kc {
+                    Keycode::Up => selected.saturating_sub(1),
+                    Keycode::Down => selected + 1,
+                    Keycode::PageUp => selected.saturating_sub(page),
+                    _ => selected + page,
+                });
+                return Ok(Flow::Continue);
+            }
+            Keycode::Left | Keycode::Right | Keycode::Home | Keycode::End => {
+                let input = &self.resources.query;
+                let caret = self.resources.caret;
+
+                self.resources.caret = match kc {
+                    Keycode::Left => input[..caret].char_indices().next_back().map_or(0, |(i, _)| i),
+                    Keycode::Right => input[caret..].chars().next().map_or(caret, |c| caret + c.len_utf8()),
+                    Keycode::Home => 0,
+                    _ => input.len(),
+                };
+                self.damaged = true;
+                return Ok(Flow::Continue);
+            }
+            Keycode::Tab => {
+                let offset = if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) { -1 } else { 1 };
+                self.close_menu();
+                self.resources.modes.cycle(offset);
+                self.query_changed();
+                return Ok(Flow::Continue);
+            }
+            _ => {}
+        }
+
+        let Resources { query, caret, .. } = &mut self.resources;
+
+        if matches!(kc, Keycode::Backspace) {
+            let Some((index, _)) = query[..*caret].char_indices().next_back() else {
+                return Ok(Flow::Continue);
+            };
+
+            query.remove(index);
+            *caret = index;
+        } else if matches!(kc, Keycode::Delete) {
+            if *caret == query.len() {
+                return Ok(Flow::Continue);
+            }
+
+            query.remove(*caret);
+        } else {
+            return Ok(Flow::Continue);
+        }
+
+        self.query_changed();
+
+        Ok(Flow::Continue)
+    }
+
+    /// Types `text` in at the caret.
+    fn insert(&mut self, text: &str) {
+        let Resources { query, caret, .. } = &mut self.resources;
+        query.insert_str(*caret, text);
+        *caret += text.len();
+
+        self.query_changed();
+    }
+
+    /// Lists the actions on the selected result in place of the results, with an empty
+    /// query to filter them.
+    fn open_menu(&mut self) {
+        let Resources { modes, query, selected, caret, .. } = &mut self.resources;
+
+        if !modes.open_menu(query, *selected) {
+            return;
+        }
+
+        query.clear();
+        *caret = 0;
+        self.query_changed();
+    }
+
+    /// Goes back from the actions to the results, with the query they were for.
+    fn close_menu(&mut self) {
+        let Resources { modes, query, caret, .. } = &mut self.resources;
+
+        let Some(previous) = modes.close_menu() else {
+            return;
+        };
+
+        *query = previous;
+        *caret = query.len();
+        self.query_changed();
+    }
+
+    /// Starts the results over from the top.
+    fn query_changed(&mut self) {
+        self.resources.selected = 0;
+        self.resources.scroll = 0;
+        self.damaged = true;
+    }
+
+    /// Moves the selection to `index`, or to the last result past the end. Headers are
+    /// passed over in the direction the selection moves.
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.match_count().saturating_sub(1));
+        let forward = index >= self.resources.selected;
+
+        self.resources.selected = self.nearest_result(index, forward);
+        self.damaged = true;
+    }
+
+    /// Moves the selection up by what the wheel turned. A notched wheel turns by whole
+    /// rows, a touchpad by fractions of one that add up until they make a row.
+    fn wheel(&mut self, rows: i32, precise: f32) {
+        let rows = if precise.fract() == 0. {
+            self.resources.wheel = 0.;
+            rows
+        } else {
+            self.resources.wheel += precise;
+            let whole = self.resources.wheel.trunc();
+            self.resources.wheel -= whole;
+            whole as i32
+        };
+
+        if rows != 0 {
+            self.select(self.resources.selected.saturating_add_signed(-rows as isize));
+        }
+    }
+
+    /// The first row from `index` on that isn't a header, looking the other way too once
+    /// the end of the list is reached.
+    fn nearest_result(&mut self, index: usize, forward: bool) -> usize {
+        let count = self.match_count();
+        let is_result = |app: &mut Self, i: usize| app.rows_from(i, 1).first().is_some_and(|row| !row.is_header());
+
+        let found = if forward {
+            (index..count).chain((0..index).rev()).find(|i| is_result(self, *i))
+        } else {
+            (0..=index).rev().chain(index + 1..count).find(|i| is_result(self, *i))
+        };
+
+        found.unwrap_or(index)
+    }
+
+    /// Acts on the selected result, the other way with `alternate`, or prints it with
+    /// `--print`.
+    fn launch_selected(&mut self, alternate: bool) -> Result<Flow> {
+        if self.options.print {
+            return Ok(match self.selected_match() {
+                Some(matched) => {
+                    println!("{}", matched.item.name);
+                    Flow::Exit(0)
                 }
+                None => Flow::Continue,
+            });
+        }
+
+        let Resources { modes, query, selected, .. } = &mut self.resources;
+
+        let activation = if alternate {
+            modes.activate_alternate(query, *selected)?
+        } else {
+            modes.activate(query, *selected)?
+        };
+
+        match activation {
+            Activation::Nothing => Ok(Flow::Continue),
+            Activation::Launch(command) => {
+                Self::launch(&command)?;
+                Ok(Flow::Exit(0))
+            }
+            Activation::Copy(text) => {
+                clipboard::copy(&text)?;
+                Ok(Flow::Exit(0))
+            }
+            Activation::Done => Ok(Flow::Exit(0)),
+        }
+    }
+
+    /// Selects the row under the pointer.
+    fn hover(&mut self, x: i32, y: i32) {
+        if !self.resources.config.mouse.hover_select {
+            return;
+        }
+
+        let (x, y) = self.pixel_position(x, y);
+
+        if let Some(row) = self.row_at(x, y).filter(|row| *row != self.resources.selected) {
+            self.resources.selected = row;
+            self.damaged = true;
+        }
+    }
+
+    /// Puts the caret where the prompt was clicked, or selects the clicked row and launches
+    /// it on a double click.
+    fn click(&mut self, x: i32, y: i32, clicks: u8) -> Result<Flow> {
+        let (x, y) = self.pixel_position(x, y);
+
+        if self.hits.prompt.contains(x, y) {
+            self.resources.caret = self.caret_at(x)?;
+            self.damaged = true;
+            return Ok(Flow::Continue);
+        }
+
+        let Some(row) = self.row_at(x, y) else {
+            return Ok(Flow::Continue);
+        };
+
+        self.resources.selected = row;
+        self.damaged = true;
+
+        if clicks >= 2 || self.resources.config.mouse.single_click {
+            return self.launch_selected(false);
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// Window coordinates of an event to pixels on the canvas.
+    fn pixel_position(&mut self, x: i32, y: i32) -> (u32, u32) {
+        let backing = T::backing_scale(self.canvas.borrow_mut().canvas()).unwrap_or(1.);
+        let scaled = |n: i32| (n.max(0) as f32 * backing) as u32;
+
+        (scaled(x), scaled(y))
+    }
+
+    /// Index of the result shown at `(x, y)`.
+    fn row_at(&self, x: u32, y: u32) -> Option<usize> {
+        self.hits.rows.iter().find(|(_, row)| row.contains(x, y)).map(|(index, _)| *index)
+    }
+
+    /// Byte offset of the character boundary in the query closest to `x`.
+    fn caret_at(&self, x: u32) -> Result<usize> {
+        let font = self.resources.fonts().prompt()?;
+        let canvas = VirtualCanvas::root(Rc::clone(&self.canvas));
+        let input = &self.resources.query;
+        let offset = x.saturating_sub(self.hits.text.x());
+
+        let mut closest = (0, u32::MAX);
+
+        for index in input.char_indices().map(|(i, _)| i).chain([input.len()]) {
+            let distance = canvas.measure_glyphs(&input[..index], &font)?.abs_diff(offset);
+
+            if distance < closest.1 {
+                closest = (index, distance);
             }
+        }
+
+        Ok(closest.0)
+    }
 
-            self.render()?;
+    /// Reloads the configuration from the file `builder` reads whenever it changes.
+    pub fn watch_config(&mut self, builder: config::Builder) {
+        self.watch = ConfigWatch::new(builder);
+    }
 
-            let time = start.elapsed();
-            let sixty_fps = Duration::from_secs_f64(1. / 60.);
+    fn reload_config(&mut self) {
+        let Some(result) = self.watch.as_mut().and_then(ConfigWatch::poll) else {
+            return;
+        };
 
-            if sixty_fps > time {
-                thread::sleep(sixty_fps - time);
+        // a broken edit shouldn't take the launcher down, the current configuration stays
+        let reloaded = match result {
+            Ok(config) => validate(&config)
+                .and_then(|()| self.resources.reload(config))
+                .map_err(anyhow::Error::from),
+            Err(e) => Err(e.into()),
+        };
+
+        match reloaded {
+            Ok(()) => {
+                self.update_scale();
+                self.damaged = true;
             }
+            Err(e) => eprintln!("error: failed to reload the configuration: {e}"),
         }
     }
 
+    /// Follows the scale of the display the window is on.
+    fn update_scale(&mut self) {
+        let Some(backing) = T::backing_scale(self.canvas.borrow_mut().canvas()) else {
+            return;
+        };
+
+        let scale = detect_scale(&self.resources.config.window, backing);
+
+        if scale != self.resources.scale {
+            self.resources.set_scale(scale);
+        }
+    }
+
+    /// Blocks for events at most until the preview command times out.
+    fn idle_timeout(&self) -> u32 {
+        self.preview
+            .time_left()
+            .map_or(Self::IDLE_TIMEOUT_MS, |left| (left.as_millis() as u32 + 1).min(Self::IDLE_TIMEOUT_MS))
+    }
+
+    /// Follows the selection with the preview, picking up the output of its command.
+    fn update_preview(&mut self) {
+        if !self.resources.config.preview.enabled {
+            return;
+        }
+
+        let selected = self.selected_match();
+        let changed = self.preview.select(selected.as_ref().map(|m| &m.item));
+
+        if changed | self.preview.poll() {
+            self.damaged = true;
+        }
+    }
+
+    /// Picks up the results the current mode got in the background.
+    fn update_modes(&mut self) {
+        let Resources { modes, query, .. } = &mut self.resources;
+
+        if modes.poll(query) {
+            self.damaged = true;
+        }
+    }
+
+    /// Number of results of the current mode.
+    fn match_count(&mut self) -> usize {
+        let Resources { modes, query, .. } = &mut self.resources;
+        modes.count(query)
+    }
+
+    /// Up to `count` rows starting at the `start`th one.
+    fn rows_from(&mut self, start: usize, count: usize) -> Vec<Row> {
+        let Resources { modes, query, .. } = &mut self.resources;
+        modes.results(query, start, count)
+    }
+
+    fn selected_match(&mut self) -> Option<MatchOwned<Command>> {
+        self.rows_from(self.resources.selected, 1).pop().and_then(Row::result)
+    }
+
+    /// Runs the command line of `command` in the background.
+    fn launch(command: &Command) -> Result<()> {
+        process::Command::new("sh")
+            .arg("-c")
+            .arg(command.exec())
+            .spawn()
+            .map_err(|e| error::launch(command.exec(), e))?;
+
+        Ok(())
+    }
+
     fn render(&mut self) -> Result<()> {
-        self.canvas.borrow_mut().set_draw_color(sdl_color(Rgb::ALMOST_BLACK));
-        self.canvas.borrow_mut().clear();
+        let background = self.resources.config.colors.background;
+        self.canvas.borrow_mut().canvas().set_draw_color(sdl_color(background));
+        self.canvas.borrow_mut().canvas().clear();
+
+        let count = self.match_count();
+        let selected = self.resources.selected.min(count.saturating_sub(1));
+        self.resources.selected = self.nearest_result(selected, true);
 
         let root = VirtualCanvas::root(Rc::clone(&self.canvas));
 
-        let (mut prompt, rest) = root.subdivide_exact(64).expect("enough space for prompt");
-        Prompt.render(&mut prompt, &self.resources)?;
+        let mut sections = root.rows(&[Size::Fixed(self.resources.px(Self::PROMPT_HEIGHT)), Size::Fill(1)]).into_iter();
+        let (mut prompt, body) = (sections.next().expect("prompt"), sections.next().expect("list"));
+        Prompt { selected: self.resources.selected, count }.render(&mut prompt, &self.resources)?;
 
-        let input_empty = self.resources.fuzzer.input().is_empty();
+        self.hits.prompt = prompt.area();
+        self.hits.text = Prompt::text_area(prompt.area(), &self.resources)?;
+        self.hits.rows.clear();
 
-        let mut rest = rest;
+        let preview = &self.resources.config.preview;
+        let mut list = if preview.enabled {
+            let sizes = [Size::Fill(1), Size::Ratio(preview.size)];
+            let mut parts = match preview.position {
+                PreviewPosition::Right => body.columns(&sizes),
+                PreviewPosition::Bottom => body.rows(&sizes),
+            }
+            .into_iter();
+            let (list, mut pane) = (parts.next().expect("list"), parts.next().expect("preview"));
 
-        let matches = self.resources
-            .fuzzer
-            .matches()
-            .take_while(|m| m.score > 0 || input_empty)
-            .map(|m| m.owned())
-            .collect::<Vec<_>>();
+            PreviewPane { preview: self.preview.preview() }.render(&mut pane, &self.resources)?;
+            list
+        } else {
+            body
+        };
 
-        for m in matches {
-            let choice = Choice { matched: m };
-            let (mut this, maybe_new_rest) = rest.subdivide_up_to(32);
+        let row_height = self.resources.px(self.resources.config.layout.effective_row_height()).max(1);
+        let area = list.area();
+        let page = ((area.height() / row_height) as usize).clamp(1, self.resources.config.layout.lines.max(1) as usize);
 
-            Render::render(&choice, &mut this, &self.resources)?;
+        // the view follows the selection, without scrolling past the last row
+        let selected = self.resources.selected;
+        let scroll = self.resources.scroll.clamp((selected + 1).saturating_sub(page), selected).min(count.saturating_sub(page));
+        self.resources.scroll = scroll;
 
-            if let Some(new_rest) = maybe_new_rest {
-                println!("continue");
-                rest = new_rest;
-            } else {
-                println!("break");
+        let offset = {
+            let mut motion = self.resources.motion();
+            motion.scroll.update_target((scroll * row_height as usize) as f64);
+            motion.scroll.value().max(0.)
+        };
+
+        // the row partly scrolled out at the top and the one partly scrolled in at the bottom
+        let first = (offset / row_height as f64) as usize;
+        let rows = self.rows_from(first, page + 1);
+        // where content at `y` in the whole list is on screen, before the top of the canvas if negative
+        let on_screen = |y: f64| area.y() as f64 + y - offset;
+
+        let window_opacity = {
+            let mut motion = self.resources.motion();
+            motion.track_rows(rows.iter().map(Row::name));
+            motion.highlight.update_target((selected * row_height as usize) as f64);
+
+            if rows.iter().any(|row| !row.is_header()) {
+                let y = on_screen(motion.highlight.value()).round();
+                let hidden = (-y).max(0.) as u32;
+                let highlight = Rect::new(area.x(), y.max(0.) as u32, area.width(), row_height.saturating_sub(hidden));
+                let radius = self.resources.px(self.resources.config.layout.corner_radius);
+                list.fill_rounded(self.resources.config.colors.selection, highlight, radius)?;
+            }
+
+            motion.window.value()
+        };
+
+        for (index, row) in (first..).zip(rows) {
+            let y = on_screen((index * row_height as usize) as f64).round();
+
+            if y >= area.bottom() as f64 {
                 break;
             }
+
+            // only with rows taller than the prompt above, the row is left out rather than drawn shifted
+            if y < 0. {
+                continue;
+            }
+
+            let mut canvas = list.overflowing(Rect::new(area.x(), y as u32, area.width(), row_height));
+            let opacity = self.resources.motion().row_opacity(row.name());
+            canvas = canvas.with_opacity(opacity);
+
+            match row {
+                Row::Result(matched) => {
+                    self.hits.rows.push((index, area.intersect(canvas.area())));
+                    Choice { matched }.render(&mut canvas, &self.resources)?;
+                }
+                Row::Header(name) => Heading { name }.render(&mut canvas, &self.resources)?,
+            }
+        }
+
+        if count > page {
+            Scrollbar { offset, visible: area.height(), total: count as u32 * row_height }.render(&mut list, &self.resources)?;
         }
 
-        self.canvas.borrow_mut().present();
+        let mut target = self.canvas.borrow_mut();
+        T::set_opacity(target.canvas(), window_opacity as f32);
+        target.canvas().present();
 
         Ok(())
     }
 }
 
 
-pub use error::Result;
+pub use error::{Error, Result};
 
+pub mod anim;
+pub mod cache;
+pub mod canvas;
+pub mod cli;
+pub mod clipboard;
+pub mod desktop;
+pub mod elide;
 pub mod error;
+pub mod font;
+pub mod icon;
+pub mod index;
+pub mod ipc;
+pub mod layout;
+pub mod mime;
+pub mod mimeapps;
+pub mod modes;
+pub mod placement;
+pub mod preview;
+pub mod recent;
+pub mod xdg;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,461 @@
+//! MIME types of files, from the freedesktop shared-mime-info database.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::sync::OnceLock;
+use crate::xdg;
+
+/// Type of directories, which the database doesn't cover.
+pub const DIRECTORY: &str = "inode/directory";
+/// Type of files that neither their name nor their contents tell anything about.
+const TEXT: &str = "text/plain";
+const BINARY: &str = "application/octet-stream";
+/// How much of a file is read to sniff its type, enough for nearly every magic rule.
+const SNIFF_LENGTH: u64 = 16 * 1024;
+/// Pattern of a type in `globs2` whose patterns of less important directories don't apply.
+const NO_GLOBS: &str = "__NOGLOBS__";
+
+/// The glob patterns of the database, which tell the type of a file by its name, and the
+/// magic rules, which tell it by its contents.
+#[derive(Default)]
+pub struct Database {
+    globs: Vec<Glob>,
+    magic: Vec<Magic>,
+    /// Canonical type of each alias.
+    aliases: HashMap<String, String>,
+    /// Types each type is a kind of, like `text/plain` for `text/x-csrc`.
+    parents: HashMap<String, Vec<String>>,
+}
+
+struct Glob {
+    weight: u32,
+    mime: String,
+    /// Lowercase, unless `case_sensitive`.
+    pattern: String,
+    case_sensitive: bool,
+}
+
+/// The rules of a type in the `magic` file, any of which at the top level identifies it.
+struct Magic {
+    priority: u32,
+    mime: String,
+    rules: Vec<Rule>,
+}
+
+/// A rule, which holds if `value` is within `range` bytes from `offset`, and if any of
+/// the rules nested right below it hold.
+struct Rule {
+    indent: u32,
+    offset: usize,
+    value: Vec<u8>,
+    mask: Option<Vec<u8>>,
+    range: usize,
+}
+
+impl Database {
+    /// The `mime` directories of the data directories, the most important first.
+    pub fn load() -> Self {
+        let mut database = Self::default();
+
+        // the less important ones first, so that the others take over their types
+        for dir in xdg::data_paths().iter().rev().map(|dir| dir.join("mime")) {
+            if let Ok(text) = fs::read_to_string(dir.join("globs2")) {
+                database.add_globs(&text);
+            }
+
+            if let Ok(data) = fs::read(dir.join("magic")) {
+                database.add_magic(&data);
+            }
+
+            for (alias, canonical) in pairs(&dir.join("aliases")) {
+                database.aliases.insert(alias, canonical);
+            }
+
+            for (child, parent) in pairs(&dir.join("subclasses")) {
+                database.parents.entry(child).or_default().push(parent);
+            }
+        }
+
+        database
+    }
+
+    /// Adds the patterns of a `globs2` file, which replace those known of their types. A
+    /// type's `__NOGLOBS__` pattern only drops the known ones.
+    fn add_globs(&mut self, text: &str) {
+        let globs = text.lines().filter(|line| !line.starts_with('#')).filter_map(parse_glob).collect::<Vec<_>>();
+        self.globs.retain(|known| globs.iter().all(|glob| glob.mime != known.mime));
+        self.globs.extend(globs.into_iter().filter(|glob| !glob.pattern.eq_ignore_ascii_case(NO_GLOBS)));
+    }
+
+    /// Adds the rules of a `magic` file, which replace those known of their types.
+    fn add_magic(&mut self, data: &[u8]) {
+        let magic = parse_magic(data);
+        self.magic.retain(|known| magic.iter().all(|magic| magic.mime != known.mime));
+        self.magic.extend(magic);
+        self.magic.sort_by_key(|magic| std::cmp::Reverse(magic.priority));
+    }
+
+    /// The type a file called `name` has going by its name. Of the patterns that match,
+    /// the one with the highest weight wins, and among those the longest.
+    pub fn by_name(&self, name: &str) -> Option<&str> {
+        self.by_name_all(name).first().copied()
+    }
+
+    /// Every type of the patterns with the highest weight and length that `name` matches.
+    fn by_name_all(&self, name: &str) -> Vec<&str> {
+        let lowercase = name.to_lowercase();
+        let matching = self
+            .globs
+            .iter()
+            .filter(|glob| matches(&glob.pattern, if glob.case_sensitive { name } else { &lowercase }))
+            .collect::<Vec<_>>();
+        let best = matching.iter().map(|glob| (glob.weight, glob.pattern.len())).max();
+
+        let mut types = Vec::new();
+
+        for glob in matching.into_iter().filter(|glob| Some((glob.weight, glob.pattern.len())) == best) {
+            if !types.contains(&glob.mime.as_str()) {
+                types.push(glob.mime.as_str());
+            }
+        }
+
+        types
+    }
+
+    /// The type of a file with the contents `data`, of the rule with the highest priority
+    /// that holds.
+    pub fn by_contents(&self, data: &[u8]) -> Option<&str> {
+        self.magic.iter().find(|magic| holds(&magic.rules, data)).map(|magic| magic.mime.as_str())
+    }
+
+    /// The type of the file at `path`, by its name, and by its contents when the name
+    /// tells nothing or leaves a choice.
+    pub fn guess(&self, path: &Path) -> Option<&str> {
+        if path.is_dir() {
+            return Some(DIRECTORY);
+        }
+
+        let by_name = path.file_name().map(|name| self.by_name_all(&name.to_string_lossy())).unwrap_or_default();
+
+        if let [only] = by_name[..] {
+            return Some(only);
+        }
+
+        let data = sniff(path).unwrap_or_default();
+
+        // a type of the name that's a kind of the one the contents tell is more precise
+        if let Some(by_contents) = self.by_contents(&data) {
+            match by_name.iter().find(|mime| self.is_a(mime, by_contents)) {
+                Some(mime) => return Some(mime),
+                None if by_name.is_empty() => return Some(by_contents),
+                None => {}
+            }
+        }
+
+        if let Some(mime) = by_name.first() {
+            return Some(mime);
+        }
+
+        let binary = data.contains(&0) || std::str::from_utf8(&data).is_err_and(|e| e.error_len().is_some());
+        Some(if binary { BINARY } else { TEXT })
+    }
+
+    /// The canonical type of `mime`, which may be an alias.
+    pub fn canonical<'a>(&'a self, mime: &'a str) -> &'a str {
+        self.aliases.get(mime).map_or(mime, String::as_str)
+    }
+
+    /// `mime` followed by the types it's a kind of, nearest first.
+    pub fn ancestors(&self, mime: &str) -> Vec<String> {
+        let mut types = vec![self.canonical(mime).to_owned()];
+        let mut i = 0;
+
+        while let Some(mime) = types.get(i) {
+            let mut parents = self.parents.get(mime).cloned().unwrap_or_default();
+
+            // every text is plain text and everything a stream of bytes, see the spec
+            if mime.starts_with("text/") {
+                parents.push(TEXT.to_owned());
+            }
+
+            if mime != BINARY && !mime.starts_with("inode/") {
+                parents.push(BINARY.to_owned());
+            }
+
+            for parent in parents {
+                if !types.contains(&parent) {
+                    types.push(parent);
+                }
+            }
+
+            i += 1;
+        }
+
+        // the most general types are only reached last
+        types.sort_by_key(|mime| [TEXT, BINARY].iter().position(|general| general == mime));
+        types
+    }
+
+    /// Whether `mime` is `of` or a kind of it.
+    fn is_a(&self, mime: &str, of: &str) -> bool {
+        self.ancestors(mime).iter().any(|ancestor| ancestor == self.canonical(of))
+    }
+}
+
+/// The database of the system, loaded the first time it's needed.
+pub fn database() -> &'static Database {
+    static DATABASE: OnceLock<Database> = OnceLock::new();
+    DATABASE.get_or_init(Database::load)
+}
+
+/// Name of the icon for files of type `mime`, like `application-pdf`.
+pub fn icon(mime: &str) -> String {
+    mime.replace('/', "-")
+}
+
+/// The start of the file at `path`.
+fn sniff(path: &Path) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    fs::File::open(path).ok()?.take(SNIFF_LENGTH).read_to_end(&mut data).ok()?;
+    Some(data)
+}
+
+/// The lines of two types each of the `aliases` or `subclasses` file at `path`.
+fn pairs(path: &Path) -> Vec<(String, String)> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    text.lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(first, second)| (first.to_owned(), second.trim().to_owned()))
+        .collect()
+}
+
+/// A line of `globs2`, `weight:type:pattern` with optional flags after.
+fn parse_glob(line: &str) -> Option<Glob> {
+    let mut fields = line.split(':');
+    let weight = fields.next()?.parse().ok()?;
+    let mime = fields.next()?.to_owned();
+    let pattern = fields.next()?;
+    let case_sensitive = fields.next().is_some_and(|flags| flags.split(',').any(|flag| flag == "cs"));
+    let pattern = if case_sensitive { pattern.to_owned() } else { pattern.to_lowercase() };
+
+    Some(Glob { weight, mime, pattern, case_sensitive })
+}
+
+/// The sections of the binary `magic` file. Each starts with a `[priority:type]` line,
+/// followed by rules of the form `[indent]>offset=length value[&mask][~word-size][+range]`,
+/// the length being two bytes big-endian. Parsing stops at anything unexpected.
+fn parse_magic(data: &[u8]) -> Vec<Magic> {
+    let Some(mut data) = data.strip_prefix(b"MIME-Magic\0\n") else {
+        return Vec::new();
+    };
+
+    let mut sections = Vec::new();
+
+    while let Some(rest) = data.strip_prefix(b"[") {
+        let Some(end) = rest.iter().position(|byte| *byte == b'\n') else {
+            break;
+        };
+        let header = String::from_utf8_lossy(&rest[..end]);
+        let Some((priority, mime)) = header.trim_end_matches(']').split_once(':') else {
+            break;
+        };
+
+        let mut magic = Magic { priority: priority.parse().unwrap_or(50), mime: mime.to_owned(), rules: Vec::new() };
+        data = &rest[end + 1..];
+
+        while !data.is_empty() && !data.starts_with(b"[") {
+            let Some((rule, rest)) = parse_rule(data) else {
+                sections.push(magic);
+                return sections;
+            };
+
+            magic.rules.extend(rule);
+            data = rest;
+        }
+
+        sections.push(magic);
+    }
+
+    sections
+}
+
+/// A rule and what follows it, `None` for the rule when it can't be used.
+fn parse_rule(data: &[u8]) -> Option<(Option<Rule>, &[u8])> {
+    let number = |data: &[u8]| -> (usize, usize) {
+        let digits = data.iter().take_while(|byte| byte.is_ascii_digit()).count();
+        let value = std::str::from_utf8(&data[..digits]).ok().and_then(|d| d.parse().ok()).unwrap_or(0);
+        (value, digits)
+    };
+
+    let (indent, digits) = number(data);
+    let data = data[digits..].strip_prefix(b">")?;
+    let (offset, digits) = number(data);
+    let data = data[digits..].strip_prefix(b"=")?;
+    let length = u16::from_be_bytes([*data.first()?, *data.get(1)?]) as usize;
+    let mut value = data.get(2..2 + length)?.to_vec();
+    let mut data = &data[2 + length..];
+
+    let mut mask = None;
+    let mut word_size = 1;
+    let mut range = 1;
+
+    loop {
+        match data.first()? {
+            b'&' => {
+                mask = Some(data.get(1..1 + length)?.to_vec());
+                data = &data[1 + length..];
+            }
+            b'~' => {
+                let (size, digits) = number(&data[1..]);
+                word_size = size;
+                data = &data[1 + digits..];
+            }
+            b'+' => {
+                let (length, digits) = number(&data[1..]);
+                range = length.max(1);
+                data = &data[1 + digits..];
+            }
+            b'\n' => break,
+            // an extension this parser doesn't know, the rule is skipped
+            _ => {
+                let end = data.iter().position(|byte| *byte == b'\n')?;
+                return Some((None, &data[end + 1..]));
+            }
+        }
+    }
+
+    // values of words are in big-endian order in the file
+    if cfg!(target_endian = "little") && matches!(word_size, 2 | 4) && length.is_multiple_of(word_size) {
+        for word in value.chunks_mut(word_size) {
+            word.reverse();
+        }
+
+        if let Some(mask) = &mut mask {
+            for word in mask.chunks_mut(word_size) {
+                word.reverse();
+            }
+        }
+    }
+
+    let rule = Rule { indent: indent as u32, offset, value, mask, range };
+    Some((Some(rule), &data[1..]))
+}
+
+/// Whether any rule at the top level of `rules` holds for `data`.
+fn holds(rules: &[Rule], data: &[u8]) -> bool {
+    let Some(indent) = rules.first().map(|rule| rule.indent) else {
+        return false;
+    };
+
+    // each rule at this level, with the deeper ones right after it
+    let mut i = 0;
+
+    while i < rules.len() {
+        let end = rules[i + 1..].iter().position(|rule| rule.indent <= indent).map_or(rules.len(), |end| i + 1 + end);
+        let children = &rules[i + 1..end];
+
+        if rules[i].indent == indent && rules[i].holds(data) && (children.is_empty() || holds(children, data)) {
+            return true;
+        }
+
+        i = end;
+    }
+
+    false
+}
+
+impl Rule {
+    fn holds(&self, data: &[u8]) -> bool {
+        (self.offset..self.offset + self.range).any(|start| {
+            let Some(window) = data.get(start..start + self.value.len()) else {
+                return false;
+            };
+
+            match &self.mask {
+                Some(mask) => window.iter().zip(&self.value).zip(mask).all(|((byte, value), mask)| byte & mask == value & mask),
+                None => window == self.value.as_slice(),
+            }
+        })
+    }
+}
+
+/// Whether `name` matches the shell glob `pattern`, of `*`, `?` and `[...]`.
+fn matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    // the position in the pattern after the last `*`, and in the name where it stopped
+    let mut star = None;
+    let (mut p, mut n) = (0, 0);
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+                continue;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+                continue;
+            }
+            Some('[') => {
+                if let Some(end) = class_matches(&pattern[p..], name[n]) {
+                    p += end;
+                    n += 1;
+                    continue;
+                }
+            }
+            Some(c) if *c == name[n] => {
+                p += 1;
+                n += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        // let the last `*` take one more char
+        let Some((after, taken)) = star else {
+            return false;
+        };
+
+        star = Some((after, taken + 1));
+        p = after;
+        n = taken + 1;
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Length of the `[...]` class at the start of `pattern` if `c` is in it.
+fn class_matches(pattern: &[char], c: char) -> Option<usize> {
+    let end = pattern.iter().skip(2).position(|c| *c == ']')? + 2;
+    let (negated, members) = match pattern[1] {
+        '!' | '^' => (true, &pattern[2..end]),
+        _ => (false, &pattern[1..end]),
+    };
+
+    let mut found = false;
+    let mut i = 0;
+
+    while i < members.len() {
+        if members.get(i + 1) == Some(&'-') && i + 2 < members.len() {
+            found |= (members[i]..=members[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= members[i] == c;
+            i += 1;
+        }
+    }
+
+    (found != negated).then_some(end + 1)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,294 @@
+use super::Database;
+
+/// A database of the `globs2` lines, given from the least to the most important directory.
+fn database(globs: &[&str]) -> Database {
+    let mut database = Database::default();
+
+    for text in globs {
+        database.add_globs(text);
+    }
+
+    database
+}
+
+#[test]
+fn prefers_the_longest_pattern() {
+    let database = database(&["\
+50:application/gzip:*.gz
+50:application/x-compressed-tar:*.tar.gz
+50:application/x-tar:*.tar
+"]);
+
+    assert_eq!(database.by_name("backup.tar.gz"), Some("application/x-compressed-tar"));
+    assert_eq!(database.by_name("notes.gz"), Some("application/gzip"));
+    assert_eq!(database.by_name("backup.tar"), Some("application/x-tar"));
+    assert_eq!(database.by_name("backup.tgz"), None);
+}
+
+#[test]
+fn prefers_the_highest_weight() {
+    let database = database(&["\
+50:text/x-readme:README*
+60:text/markdown:*.md
+"]);
+
+    assert_eq!(database.by_name("README.md"), Some("text/markdown"));
+    assert_eq!(database.by_name("README"), Some("text/x-readme"));
+}
+
+#[test]
+fn matches_literal_names() {
+    let database = database(&["\
+50:text/x-makefile:makefile
+50:text/x-makefile:GNUmakefile
+50:text/x-c++src:*.C:cs
+50:text/x-csrc:*.c
+"]);
+
+    assert_eq!(database.by_name("Makefile"), Some("text/x-makefile"));
+    assert_eq!(database.by_name("GNUmakefile"), Some("text/x-makefile"));
+    assert_eq!(database.by_name("Makefile.am"), None);
+    assert_eq!(database.by_name("main.C"), Some("text/x-c++src"));
+    assert_eq!(database.by_name("main.c"), Some("text/x-csrc"));
+}
+
+#[test]
+fn folds_the_case_of_patterns_and_names() {
+    let database = database(&["\
+50:text/plain:*.txt
+10:text/x-readme:README*
+50:application/pdf:*.PDF
+"]);
+
+    assert_eq!(database.by_name("README.TXT"), Some("text/plain"));
+    assert_eq!(database.by_name("readme"), Some("text/x-readme"));
+    assert_eq!(database.by_name("Report.Pdf"), Some("application/pdf"));
+}
+
+#[test]
+fn lets_more_important_directories_replace_patterns() {
+    let database = database(&[
+        "\
+50:application/x-old:*.old
+50:application/x-bak:*.bak
+50:text/plain:*.txt
+",
+        "\
+50:application/x-old:__NOGLOBS__
+50:text/plain:*.text
+",
+    ]);
+
+    assert_eq!(database.by_name("file.old"), None);
+    assert_eq!(database.by_name("__noglobs__"), None);
+    assert_eq!(database.by_name("file.bak"), Some("application/x-bak"));
+    assert_eq!(database.by_name("file.txt"), None);
+    assert_eq!(database.by_name("file.text"), Some("text/plain"));
+}
+
+/// A rule line of the `magic` file, `options` being what follows the value, like a mask.
+fn rule(indent: u32, offset: usize, value: &[u8], options: &[u8]) -> Vec<u8> {
+    let indent = if indent == 0 { String::new() } else { indent.to_string() };
+    let mut line = format!("{indent}>{offset}=").into_bytes();
+    line.extend((value.len() as u16).to_be_bytes());
+    line.extend(value);
+    line.extend(options);
+    line.push(b'\n');
+    line
+}
+
+/// A `magic` file of sections, each of a header like `50:image/png` and its rules.
+fn magic(sections: &[(&str, Vec<Vec<u8>>)]) -> Vec<u8> {
+    let mut data = b"MIME-Magic\0\n".to_vec();
+
+    for (header, rules) in sections {
+        data.extend(format!("[{header}]\n").bytes());
+        data.extend(rules.concat());
+    }
+
+    data
+}
+
+fn by_contents(magic: &[u8], data: &[u8]) -> Option<String> {
+    let mut database = Database::default();
+    database.add_magic(magic);
+    database.by_contents(data).map(str::to_owned)
+}
+
+#[test]
+fn matches_magic_values_at_their_offset() {
+    let magic = magic(&[
+        ("50:image/png", vec![rule(0, 0, b"\x89PNG", b"")]),
+        ("50:application/x-tar", vec![rule(0, 257, b"ustar", b"")]),
+    ]);
+
+    let mut tar = vec![0; 300];
+    tar[257..262].copy_from_slice(b"ustar");
+
+    assert_eq!(by_contents(&magic, b"\x89PNG\r\n\x1a\n").as_deref(), Some("image/png"));
+    assert_eq!(by_contents(&magic, &tar).as_deref(), Some("application/x-tar"));
+    assert_eq!(by_contents(&magic, b"x\x89PNG"), None);
+    assert_eq!(by_contents(&magic, b"\x89PN"), None);
+}
+
+#[test]
+fn searches_magic_ranges_and_applies_masks() {
+    let magic = magic(&[
+        ("50:application/x-ranged", vec![rule(0, 2, b"PK", b"+4")]),
+        ("50:application/x-masked", vec![rule(0, 0, b"\x00\x10", b"&\x00\xf0")]),
+    ]);
+
+    assert_eq!(by_contents(&magic, b".....PK").as_deref(), Some("application/x-ranged"));
+    assert_eq!(by_contents(&magic, b"......PK"), None);
+    assert_eq!(by_contents(&magic, b"\xff\x1a").as_deref(), Some("application/x-masked"));
+    assert_eq!(by_contents(&magic, b"\xff\x2a"), None);
+}
+
+#[test]
+fn swaps_magic_words_to_the_host_order() {
+    let magic = magic(&[("50:application/x-word", vec![rule(0, 0, b"\x12\x34", b"~2")])]);
+    let host: &[u8] = if cfg!(target_endian = "little") { b"\x34\x12" } else { b"\x12\x34" };
+
+    assert_eq!(by_contents(&magic, host).as_deref(), Some("application/x-word"));
+}
+
+#[test]
+fn needs_a_nested_magic_rule_to_hold_too() {
+    let magic = magic(&[
+        (
+            "50:audio/x-wav",
+            vec![rule(0, 0, b"RIFF", b""), rule(1, 8, b"WAVE", b""), rule(1, 8, b"WAV ", b"")],
+        ),
+        ("50:image/gif", vec![rule(0, 0, b"GIF87a", b""), rule(0, 0, b"GIF89a", b"")]),
+    ]);
+
+    assert_eq!(by_contents(&magic, b"RIFF....WAVEfmt ").as_deref(), Some("audio/x-wav"));
+    assert_eq!(by_contents(&magic, b"RIFF....WAV data").as_deref(), Some("audio/x-wav"));
+    assert_eq!(by_contents(&magic, b"RIFF....AVI LIST"), None);
+    assert_eq!(by_contents(&magic, b"GIF89a").as_deref(), Some("image/gif"));
+}
+
+#[test]
+fn prefers_the_magic_of_the_highest_priority() {
+    let magic = magic(&[
+        ("40:application/zip", vec![rule(0, 0, b"PK\x03\x04", b"")]),
+        ("60:application/epub+zip", vec![rule(0, 0, b"PK\x03\x04", b""), rule(1, 30, b"mimetype", b"")]),
+    ]);
+
+    let mut epub = b"PK\x03\x04".to_vec();
+    epub.resize(30, 0);
+    epub.extend(b"mimetypeapplication/epub+zip");
+
+    assert_eq!(by_contents(&magic, &epub).as_deref(), Some("application/epub+zip"));
+    assert_eq!(by_contents(&magic, b"PK\x03\x04").as_deref(), Some("application/zip"));
+}
+
+#[test]
+fn skips_what_the_magic_parser_does_not_know() {
+    let mut data = magic(&[
+        ("50:application/x-extended", vec![rule(0, 0, b"EXT", b"*future"), rule(0, 0, b"OLD", b"")]),
+        ("50:image/png", vec![rule(0, 0, b"\x89PNG", b"")]),
+    ]);
+
+    assert_eq!(by_contents(&data, b"EXT"), None);
+    assert_eq!(by_contents(&data, b"OLD").as_deref(), Some("application/x-extended"));
+    assert_eq!(by_contents(&data, b"\x89PNG").as_deref(), Some("image/png"));
+
+    // a cut off file keeps the sections before
+    data.truncate(data.len() - 3);
+    assert_eq!(by_contents(&data, b"OLD").as_deref(), Some("application/x-extended"));
+    assert_eq!(by_contents(&data, b"\x89PNG"), None);
+
+    assert_eq!(by_contents(b"not magic", b"OLD"), None);
+}
+
+#[test]
+fn matches_shell_globs() {
+    let cases = [
+        ("*.c", "main.c", true),
+        ("*.c", "main.cc", false),
+        ("*.c", ".c", true),
+        ("?.txt", "a.txt", true),
+        ("?.txt", "ab.txt", false),
+        ("*.[ch]", "lib.h", true),
+        ("*.[ch]", "lib.o", false),
+        ("*.[!ch]", "lib.o", true),
+        ("*.[0-9]", "ls.1", true),
+        ("*.[0-9]", "ls.n", false),
+        ("*~", "notes.txt~", true),
+        ("*.*.*", "a.b.c", true),
+        ("*.*.*", "a.b", false),
+        ("makefile", "makefile", true),
+        ("makefile", "makefile.am", false),
+        ("", "", true),
+        ("*", "", true),
+    ];
+
+    for (pattern, name, expected) in cases {
+        assert_eq!(super::matches(pattern, name), expected, "{pattern} against {name}");
+    }
+}
+
+fn family() -> Database {
+    let mut database = Database::default();
+
+    for (child, parent) in [
+        ("text/x-c++src", "text/x-csrc"),
+        ("image/svg+xml", "application/xml"),
+        ("application/xml", "text/plain"),
+        ("application/x-shellscript", "application/x-executable"),
+        ("application/x-shellscript", "text/plain"),
+    ] {
+        database.parents.entry(child.to_owned()).or_default().push(parent.to_owned());
+    }
+
+    database.aliases.insert("text/x-c".to_owned(), "text/x-csrc".to_owned());
+    database
+}
+
+#[test]
+fn lists_ancestors_nearest_first() {
+    let database = family();
+
+    assert_eq!(
+        database.ancestors("text/x-c++src"),
+        ["text/x-c++src", "text/x-csrc", "text/plain", "application/octet-stream"],
+    );
+    assert_eq!(
+        database.ancestors("image/svg+xml"),
+        ["image/svg+xml", "application/xml", "text/plain", "application/octet-stream"],
+    );
+    assert_eq!(
+        database.ancestors("application/x-shellscript"),
+        ["application/x-shellscript", "application/x-executable", "text/plain", "application/octet-stream"],
+    );
+    assert_eq!(database.ancestors("text/x-c"), ["text/x-csrc", "text/plain", "application/octet-stream"]);
+    assert_eq!(database.ancestors("image/png"), ["image/png", "application/octet-stream"]);
+    assert_eq!(database.ancestors("application/octet-stream"), ["application/octet-stream"]);
+    assert_eq!(database.ancestors("inode/directory"), ["inode/directory"]);
+
+    assert!(database.is_a("text/x-c++src", "text/x-c"));
+    assert!(!database.is_a("text/x-csrc", "text/x-c++src"));
+}
+
+#[test]
+fn guesses_by_name_then_contents() {
+    let mut database = family();
+    database.add_globs("50:text/x-csrc:*.c\n50:text/x-chdr:*.h\n50:text/x-c++hdr:*.h\n");
+    database.add_magic(&magic(&[("50:image/png", vec![rule(0, 0, b"\x89PNG", b"")])]));
+
+    let dir = tempfile::TempDir::new().expect("temporary directory");
+    let write = |name: &str, contents: &[u8]| {
+        let path = dir.path().join(name);
+        std::fs::write(&path, contents).expect("file written");
+        path
+    };
+
+    assert_eq!(database.guess(dir.path()), Some("inode/directory"));
+    assert_eq!(database.guess(&write("main.c", b"\x89PNG")), Some("text/x-csrc"));
+    assert_eq!(database.guess(&write("picture", b"\x89PNG\r\n")), Some("image/png"));
+    assert_eq!(database.guess(&write("lib.h", b"int f();")), Some("text/x-chdr"));
+    assert_eq!(database.guess(&write("notes", "plain text, ünïcode".as_bytes())), Some("text/plain"));
+    assert_eq!(database.guess(&write("data", b"\x00\x01\x02")), Some("application/octet-stream"));
+    assert_eq!(database.guess(&write("latin1", b"caf\xe9 au lait")), Some("application/octet-stream"));
+}
@@ -0,0 +1,123 @@
+//! Which applications open which types of files, from the `mimeapps.list` files of the
+//! freedesktop MIME Applications Associations specification and the `MimeType` key of
+//! desktop entries.
+
+use std::cell::OnceCell;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use crate::desktop::{self, DesktopEntry};
+use crate::{mime, xdg};
+
+/// The `mimeapps.list` files, most important first: the user's configuration, the
+/// system's, then those in the data directories, each desktop specific one before the
+/// general one.
+pub fn lists() -> Vec<PathBuf> {
+    let desktops = desktop::current_desktops().into_iter().map(|d| d.to_lowercase()).collect::<Vec<_>>();
+    let dirs = xdg::config_home()
+        .into_iter()
+        .chain(xdg::config_dirs())
+        .chain(xdg::data_paths().into_iter().map(|dir| dir.join("applications")));
+
+    dirs.flat_map(|dir| {
+        desktops
+            .iter()
+            .map(|desktop| dir.join(format!("{desktop}-mimeapps.list")))
+            .chain([dir.join("mimeapps.list")])
+            .collect::<Vec<_>>()
+    })
+    .collect()
+}
+
+/// The desktop file IDs listed for `mime` in the `group` of a list, in order.
+pub fn listed(text: &str, group: &str, mime: &str) -> Vec<String> {
+    let mut in_group = false;
+    let mut ids = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_group = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) == Some(group);
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| in_group) {
+            if key.trim() == mime {
+                ids.extend(value.split(';').map(str::trim).filter(|id| !id.is_empty()).map(str::to_owned));
+            }
+        }
+    }
+
+    ids
+}
+
+/// The applications that open files, scanned the first time they're asked for and kept
+/// from then on, like the applications of the `apps` mode.
+#[derive(Default)]
+pub struct Handlers {
+    installed: OnceCell<Vec<DesktopEntry>>,
+}
+
+impl Handlers {
+    /// The applications that open files of type `mime`, the default one first, by the
+    /// lists as they are now, see [`handlers`].
+    pub fn of(&self, mime: &str) -> Vec<DesktopEntry> {
+        let installed = self.installed.get_or_init(desktop::handlers);
+        let lists = lists().into_iter().filter_map(|path| fs::read_to_string(path).ok()).collect::<Vec<_>>();
+
+        handlers(&mime::database().ancestors(mime), &lists, installed)
+    }
+
+    /// The application that opens files of type `mime` by default.
+    pub fn default_for(&self, mime: &str) -> Option<DesktopEntry> {
+        self.of(mime).into_iter().next()
+    }
+}
+
+/// The `installed` applications that open files of the first of `types`, the default one
+/// first. The types it's a kind of follow it, so that a text editor opens C sources too.
+///
+/// For each type, the `lists` are gone through from the most important one. A list's
+/// `[Added Associations]` count unless a more important list has them under
+/// `[Removed Associations]`, and the applications whose `MimeType` has the type come
+/// after all of them. The default is the first installed of the
+/// `[Default Applications]`, or else the first of the others.
+pub fn handlers(types: &[String], lists: &[String], installed: &[DesktopEntry]) -> Vec<DesktopEntry> {
+    let entry = |id: &str| installed.iter().find(|entry| entry.id == id);
+
+    let mut default = None;
+    let mut handlers = Vec::<&DesktopEntry>::new();
+
+    for mime in types {
+        let mut removed = HashSet::new();
+        let mut found = Vec::new();
+
+        for text in lists {
+            if default.is_none() {
+                default = listed(text, "Default Applications", mime).iter().find_map(|id| entry(id));
+            }
+
+            found.extend(listed(text, "Added Associations", mime).into_iter().filter(|id| !removed.contains(id)));
+            removed.extend(listed(text, "Removed Associations", mime));
+        }
+
+        found.extend(
+            installed
+                .iter()
+                .filter(|entry| entry.mime_types.contains(mime) && !removed.contains(&entry.id))
+                .map(|entry| entry.id.clone()),
+        );
+
+        for entry in found.iter().filter_map(|id| entry(id)) {
+            if !handlers.iter().any(|known| known.id == entry.id) {
+                handlers.push(entry);
+            }
+        }
+    }
+
+    if let Some(default) = default {
+        handlers.retain(|entry| entry.id != default.id);
+        handlers.insert(0, default);
+    }
+
+    handlers.into_iter().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+use crate::desktop::DesktopEntry;
+use super::{handlers, listed};
+
+fn entry(id: &str, mime_types: &[&str]) -> DesktopEntry {
+    DesktopEntry {
+        id: id.to_owned(),
+        path: PathBuf::from(format!("/usr/share/applications/{id}")),
+        name: id.trim_end_matches(".desktop").to_owned(),
+        generic_name: None,
+        comment: None,
+        exec: format!("{} %f", id.trim_end_matches(".desktop")),
+        icon: None,
+        terminal: false,
+        mime_types: mime_types.iter().map(|mime| mime.to_string()).collect(),
+    }
+}
+
+/// The IDs of the handlers of the first of `types`.
+fn ids(types: &[&str], lists: &[&str], installed: &[DesktopEntry]) -> Vec<String> {
+    let types = types.iter().map(|mime| mime.to_string()).collect::<Vec<_>>();
+    let lists = lists.iter().map(|list| list.to_string()).collect::<Vec<_>>();
+
+    handlers(&types, &lists, installed).into_iter().map(|entry| entry.id).collect()
+}
+
+#[test]
+fn lists_the_ids_of_a_group() {
+    let text = "\
+[Default Applications]
+text/plain=gedit.desktop
+image/png = eog.desktop ; gimp.desktop;
+
+[Added Associations]
+text/plain=kate.desktop;vim.desktop;
+text/plain=nano.desktop
+";
+
+    assert_eq!(listed(text, "Default Applications", "text/plain"), ["gedit.desktop"]);
+    assert_eq!(listed(text, "Default Applications", "image/png"), ["eog.desktop", "gimp.desktop"]);
+    assert_eq!(listed(text, "Added Associations", "text/plain"), ["kate.desktop", "vim.desktop", "nano.desktop"]);
+    assert!(listed(text, "Added Associations", "image/png").is_empty());
+    assert!(listed(text, "Removed Associations", "text/plain").is_empty());
+}
+
+#[test]
+fn puts_the_first_installed_default_first() {
+    let installed = [entry("kate.desktop", &["text/plain"]), entry("gedit.desktop", &["text/plain"])];
+    let user = "[Default Applications]\ntext/plain=missing.desktop;gedit.desktop\n";
+    let system = "[Default Applications]\ntext/plain=kate.desktop\n";
+
+    assert_eq!(ids(&["text/plain"], &[user, system], &installed), ["gedit.desktop", "kate.desktop"]);
+    assert_eq!(ids(&["text/plain"], &[system, user], &installed), ["kate.desktop", "gedit.desktop"]);
+    assert_eq!(ids(&["text/plain"], &[], &installed), ["kate.desktop", "gedit.desktop"]);
+}
+
+#[test]
+fn lets_more_important_lists_remove_associations() {
+    let installed = [
+        entry("gimp.desktop", &[]),
+        entry("krita.desktop", &[]),
+        entry("eog.desktop", &["image/png"]),
+        entry("firefox.desktop", &["image/png"]),
+    ];
+    let user = "\
+[Removed Associations]
+image/png=krita.desktop;firefox.desktop
+
+[Added Associations]
+image/png=gimp.desktop
+";
+    let system = "\
+[Added Associations]
+image/png=krita.desktop;eog.desktop
+
+[Removed Associations]
+image/png=gimp.desktop
+";
+
+    // the user's additions stay, the system's removals only apply to what comes after
+    assert_eq!(ids(&["image/png"], &[user, system], &installed), ["gimp.desktop", "eog.desktop"]);
+}
+
+#[test]
+fn falls_back_to_the_mime_types_of_entries() {
+    let installed = [
+        entry("eog.desktop", &["image/png", "image/jpeg"]),
+        entry("gimp.desktop", &["image/png"]),
+        entry("gedit.desktop", &["text/plain"]),
+    ];
+    let list = "[Added Associations]\nimage/png=gimp.desktop;missing.desktop\n";
+
+    assert_eq!(ids(&["image/png"], &[list], &installed), ["gimp.desktop", "eog.desktop"]);
+    assert_eq!(ids(&["image/jpeg"], &[list], &installed), ["eog.desktop"]);
+    assert!(ids(&["video/mp4"], &[list], &installed).is_empty());
+}
+
+#[test]
+fn follows_with_the_handlers_of_ancestors() {
+    let installed = [
+        entry("gedit.desktop", &["text/plain"]),
+        entry("code.desktop", &["text/x-csrc", "text/plain"]),
+        entry("hexedit.desktop", &["application/octet-stream"]),
+    ];
+    let types = ["text/x-csrc", "text/plain", "application/octet-stream"];
+
+    assert_eq!(ids(&types, &[], &installed), ["code.desktop", "gedit.desktop", "hexedit.desktop"]);
+
+    // a default of a more general type is still the default
+    let list = "[Default Applications]\ntext/plain=gedit.desktop\n";
+    assert_eq!(ids(&types, &[list], &installed), ["gedit.desktop", "code.desktop", "hexedit.desktop"]);
+}
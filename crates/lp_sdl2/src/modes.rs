@@ -0,0 +1,385 @@
+//! Modes: the sources of results, shown as tabs in the prompt bar.
+//!
+//! Each mode has its own items, matcher and way of acting on the chosen result. One mode is
+//! current at a time and switched with Tab, while a query starting with the prefix of a mode
+//! goes to that mode whichever is current.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use sdl2::EventSubsystem;
+use fuzzer::MatchOwned;
+use config::{Command, Config};
+use crate::{Error, Result};
+
+/// Modes that are always there, plugins add their own.
+pub const BUILTIN: &[&str] = &["apps", "calc", "combi", "commands", "files", "recent", "run", "units"];
+
+/// A mode that more than one place queries, like a mode that the combi mode combines and
+/// that is a tab of its own as well.
+pub type Shared = Rc<RefCell<dyn Mode>>;
+
+/// A line of the result list.
+pub enum Row {
+    Result(MatchOwned<Command>),
+    /// Heading of the results below it, which come from the source it names.
+    Header(String),
+}
+
+impl Row {
+    pub fn result(self) -> Option<MatchOwned<Command>> {
+        match self {
+            Row::Result(matched) => Some(matched),
+            Row::Header(_) => None,
+        }
+    }
+
+    pub fn is_header(&self) -> bool {
+        matches!(self, Row::Header(_))
+    }
+
+    /// What the row shows first.
+    pub fn name(&self) -> &str {
+        match self {
+            Row::Result(matched) => &matched.item.name,
+            Row::Header(name) => name,
+        }
+    }
+}
+
+/// What activating a result comes down to.
+pub enum Activation {
+    /// There was no such result.
+    Nothing,
+    /// Run the command line of the item.
+    Launch(Command),
+    /// Put the text on the clipboard.
+    Copy(String),
+    /// The mode acted on it itself.
+    Done,
+}
+
+pub trait Mode {
+    /// Number of rows for `query`.
+    fn count(&mut self, query: &str) -> usize;
+
+    /// Up to `count` rows for `query`, starting at the `start`th one.
+    fn results(&mut self, query: &str, start: usize, count: usize) -> Vec<Row>;
+
+    /// How well the `rank`th result with `score` fits `query`, from 0 to 1 and comparable
+    /// across modes. By default, by rank alone.
+    fn relevance(&mut self, _query: &str, rank: usize, _score: i64) -> f64 {
+        1. / (rank + 1) as f64
+    }
+
+    /// Acts on the `index`th row for `query`.
+    fn activate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        Ok(match self.results(query, index, 1).pop().and_then(Row::result) {
+            Some(matched) => Activation::Launch(matched.item),
+            None => Activation::Nothing,
+        })
+    }
+
+    /// Acts on the `index`th row for `query` the other way, chosen with Shift+Enter. By
+    /// default, the same way.
+    fn activate_alternate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        self.activate(query, index)
+    }
+
+    /// Picks up results that came in since the last call, for modes that get them in the
+    /// background. Returns whether they changed.
+    fn poll(&mut self, _query: &str) -> bool {
+        false
+    }
+
+    /// A menu of other ways to act on the `index`th row for `query`, like the applications
+    /// that can open a file.
+    fn actions(&mut self, _query: &str, _index: usize) -> Option<Menu> {
+        None
+    }
+
+    /// Stops any background work, the mode isn't used until the next query comes in.
+    fn stop(&mut self) {}
+}
+
+/// Names of every mode the configuration can refer to.
+pub fn known(config: &Config) -> Vec<String> {
+    BUILTIN
+        .iter()
+        .map(|name| (*name).to_owned())
+        .chain(config.plugins.iter().map(|plugin| plugin.name.clone()))
+        .collect()
+}
+
+/// Finds problems with the modes the configuration refers to.
+pub fn validate(config: &Config) -> Vec<config::Diagnostic> {
+    let known = known(config);
+    let referred = [("mode", &config.mode)]
+        .into_iter()
+        .chain(config.modes.iter().map(|name| ("modes", name)))
+        .chain(config.prefixes.values().map(|name| ("prefixes", name)))
+        .chain(config.combi.modes.iter().map(|name| ("combi.modes", name)));
+
+    referred
+        .filter(|(_, name)| !known.contains(name))
+        .map(|(key, name)| {
+            config::Diagnostic::error(format!(
+                "{key} refers to the unknown mode '{name}', expected one of: {}",
+                known.join(", "),
+            ))
+        })
+        .collect()
+}
+
+/// Actions on a result, listed in place of the results.
+pub struct Menu {
+    /// Shown in place of the mode's tab.
+    pub title: String,
+    pub mode: Box<dyn Mode>,
+}
+
+struct OpenMenu {
+    menu: Menu,
+    /// The query when the menu was opened, which comes back when it's closed.
+    query: String,
+}
+
+struct Slot {
+    name: String,
+    /// Typed at the start of the query, switches to the mode for the rest of it.
+    prefixes: Vec<String>,
+    mode: Shared,
+    /// Slots whose modes this one queries, which keep running while it's used.
+    sources: Vec<usize>,
+}
+
+/// Every mode of the configuration, and which one the query goes to.
+pub struct Modes {
+    slots: Vec<Slot>,
+    /// Number of slots shown as tabs, the others are only reached through a prefix.
+    tabs: usize,
+    current: usize,
+    menu: Option<OpenMenu>,
+}
+
+impl Modes {
+    pub fn new(config: &Config, events: &EventSubsystem) -> Result<Self> {
+        let mut names = config.modes.clone();
+
+        if !names.contains(&config.mode) {
+            names.insert(0, config.mode.clone());
+        }
+
+        let tabs = names.len();
+
+        // the combi mode queries the slots of the modes it combines rather than its own
+        let combined = match names.iter().any(|name| name == "combi") {
+            true => config.combi.modes.iter().filter(|name| *name != "combi").collect::<Vec<_>>(),
+            false => Vec::new(),
+        };
+
+        for name in config.prefixes.values().chain(config.plugins.iter().map(|p| &p.name)).chain(combined.iter().copied()) {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+
+        let mut modes = names
+            .iter()
+            .map(|name| (name != "combi").then(|| build(name, config, events)).transpose())
+            .collect::<Result<Vec<_>>>()?;
+
+        let sources = combined
+            .iter()
+            .map(|name| names.iter().position(|known| known == *name).expect("combined modes have slots"))
+            .collect::<Vec<_>>();
+
+        if let Some(index) = names.iter().position(|name| name == "combi") {
+            let combined = sources
+                .iter()
+                .map(|source| (names[*source].clone(), Rc::clone(modes[*source].as_ref().expect("only combi is unbuilt"))))
+                .collect();
+
+            modes[index] = Some(shared(combi::CombiMode::new(&config.combi, combined)));
+        }
+
+        let slots = names
+            .into_iter()
+            .zip(modes)
+            .map(|(name, mode)| {
+                let mut prefixes = config
+                    .prefixes
+                    .iter()
+                    .filter(|(_, mode)| **mode == name)
+                    .map(|(prefix, _)| prefix.clone())
+                    .collect::<Vec<_>>();
+                prefixes.extend(config.plugins.iter().filter(|p| p.name == name).map(|p| p.prefix.clone()));
+
+                let sources = if name == "combi" { sources.clone() } else { Vec::new() };
+                Slot { mode: mode.expect("every mode is built"), name, prefixes, sources }
+            })
+            .collect::<Vec<_>>();
+
+        let current = slots.iter().position(|slot| slot.name == config.mode).unwrap_or_default();
+
+        Ok(Self { slots, tabs, current, menu: None })
+    }
+
+    /// Names of the modes shown as tabs.
+    pub fn tabs(&self) -> impl Iterator<Item = &str> {
+        self.slots[..self.tabs].iter().map(|slot| slot.name.as_str())
+    }
+
+    /// Name of the current mode.
+    pub fn current(&self) -> &str {
+        &self.slots[self.current].name
+    }
+
+    /// Switches to the mode called `name`.
+    pub fn switch_to(&mut self, name: &str) -> Result<()> {
+        self.current = self
+            .slots
+            .iter()
+            .position(|slot| slot.name == name)
+            .ok_or_else(|| Error::UnknownMode(name.to_owned()))?;
+
+        Ok(())
+    }
+
+    /// Switches to the tab `offset` tabs away from the current one, wrapping around.
+    pub fn cycle(&mut self, offset: isize) {
+        let tabs = self.tabs as isize;
+        // from a mode that isn't a tab, the first step lands on the first or last tab
+        let current = if self.current < self.tabs { self.current as isize } else if offset > 0 { -1 } else { tabs };
+
+        self.current = (current + offset).rem_euclid(tabs) as usize;
+    }
+
+    /// Index of the mode `query` goes to, and what's left of the query for it. The longest
+    /// prefix the query starts with wins, so that `>>` can go elsewhere than `>`.
+    pub fn route<'q>(&self, query: &'q str) -> (usize, &'q str) {
+        let prefixed = self
+            .slots
+            .iter()
+            .enumerate()
+            .flat_map(|(index, slot)| slot.prefixes.iter().map(move |prefix| (index, prefix)))
+            .filter(|(_, prefix)| query.starts_with(prefix.as_str()))
+            .max_by_key(|(_, prefix)| prefix.len());
+
+        match prefixed {
+            Some((index, prefix)) => (index, &query[prefix.len()..]),
+            None => (self.current, query),
+        }
+    }
+
+    /// Name of the mode `query` goes to, or the title of the open menu.
+    pub fn active(&self, query: &str) -> &str {
+        match &self.menu {
+            Some(open) => &open.menu.title,
+            None => &self.slots[self.route(query).0].name,
+        }
+    }
+
+    /// Runs `f` on the mode `query` goes to with what's left of the query for it, the open
+    /// menu taking the whole query.
+    fn with_target<R>(&mut self, query: &str, f: impl FnOnce(&mut dyn Mode, &str) -> R) -> R {
+        if let Some(open) = &mut self.menu {
+            return f(open.menu.mode.as_mut(), query);
+        }
+
+        let (index, text) = self.route(query);
+        f(&mut *self.slots[index].mode.borrow_mut(), text)
+    }
+
+    pub fn count(&mut self, query: &str) -> usize {
+        self.with_target(query, |mode, text| mode.count(text))
+    }
+
+    pub fn results(&mut self, query: &str, start: usize, count: usize) -> Vec<Row> {
+        self.with_target(query, |mode, text| mode.results(text, start, count))
+    }
+
+    pub fn activate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        self.with_target(query, |mode, text| mode.activate(text, index))
+    }
+
+    pub fn activate_alternate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        self.with_target(query, |mode, text| mode.activate_alternate(text, index))
+    }
+
+    /// Opens the menu of actions on the `index`th row for `query`, if it has one. Returns
+    /// whether it did, the query then goes to the menu until it's closed.
+    pub fn open_menu(&mut self, query: &str, index: usize) -> bool {
+        let Some(menu) = self.with_target(query, |mode, text| mode.actions(text, index)) else {
+            return false;
+        };
+
+        self.menu = Some(OpenMenu { menu, query: query.to_owned() });
+        true
+    }
+
+    /// Closes the open menu. Returns the query it was opened from.
+    pub fn close_menu(&mut self) -> Option<String> {
+        self.menu.take().map(|open| open.query)
+    }
+
+    pub fn in_menu(&self) -> bool {
+        self.menu.is_some()
+    }
+
+    /// Polls the mode the query goes to and stops the others, but for those it queries.
+    /// Returns whether the results changed.
+    pub fn poll(&mut self, query: &str) -> bool {
+        if let Some(open) = &mut self.menu {
+            return open.menu.mode.poll(query);
+        }
+
+        let (index, text) = self.route(query);
+        let target = &self.slots[index];
+
+        for (i, slot) in self.slots.iter().enumerate() {
+            if i != index && !target.sources.contains(&i) {
+                slot.mode.borrow_mut().stop();
+            }
+        }
+
+        target.mode.borrow_mut().poll(text)
+    }
+
+    pub fn stop(&mut self) {
+        for slot in &self.slots {
+            slot.mode.borrow_mut().stop();
+        }
+    }
+}
+
+/// Builds the mode called `name`, other than the combi mode, which [`Modes`] builds from the
+/// modes it combines.
+pub fn build(name: &str, config: &Config, events: &EventSubsystem) -> Result<Shared> {
+    if let Some(plugin) = config.plugins.iter().find(|plugin| plugin.name == name) {
+        return Ok(shared(plugin::PluginMode::new(plugin, events.clone())));
+    }
+
+    Ok(match name {
+        "apps" => shared(fuzzy::FuzzyMode::new(apps::items())),
+        "calc" => shared(calc::CalcMode::arithmetic()),
+        "commands" => shared(fuzzy::FuzzyMode::new(config.commands.clone())),
+        "files" => shared(files::FilesMode::new(config.files.clone(), events.clone())),
+        "recent" => shared(recent::RecentMode::load()),
+        "run" => shared(run::RunMode::from_path()),
+        "units" => shared(calc::CalcMode::units()),
+        other => return Err(Error::UnknownMode(other.to_owned())),
+    })
+}
+
+fn shared(mode: impl Mode + 'static) -> Shared {
+    Rc::new(RefCell::new(mode))
+}
+
+pub mod apps;
+pub mod calc;
+pub mod combi;
+pub mod files;
+pub mod fuzzy;
+pub mod plugin;
+pub mod recent;
+pub mod run;
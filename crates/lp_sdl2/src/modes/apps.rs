@@ -0,0 +1,19 @@
+//! Installed applications, from their desktop entries.
+
+use config::Command;
+use crate::desktop::{self, DesktopEntry};
+
+/// An item for every application that can be launched.
+pub fn items() -> Vec<Command> {
+    desktop::applications().into_iter().filter_map(|entry| item(&entry)).collect()
+}
+
+fn item(entry: &DesktopEntry) -> Option<Command> {
+    Some(Command {
+        name: entry.name.clone(),
+        description: entry.comment.clone().or_else(|| entry.generic_name.clone()).unwrap_or_default(),
+        exec: Some(entry.command_line()?),
+        icon: entry.icon.clone(),
+        path: Some(entry.path.clone()),
+    })
+}
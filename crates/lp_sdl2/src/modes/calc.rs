@@ -0,0 +1,115 @@
+//! The answer to the arithmetic or the unit conversion in the query, see [`calc`](::calc).
+
+use fuzzer::MatchOwned;
+use config::Command;
+use crate::Result;
+use super::{Activation, Mode, Row};
+
+/// Relevance of an answer that took no working out, like the value of `pi`, which shouldn't
+/// push aside what the query more likely was meant for.
+const TRIVIAL_RELEVANCE: f64 = 0.2;
+
+pub struct CalcMode {
+    evaluate: fn(&str) -> Option<Answer>,
+    /// The query last evaluated, and its answer.
+    last: Option<(String, Option<Answer>)>,
+}
+
+struct Answer {
+    /// What the result shows.
+    title: String,
+    /// What's copied.
+    value: String,
+    /// Shown below, before the hint that Enter copies.
+    detail: Option<String>,
+    computed: bool,
+}
+
+impl CalcMode {
+    pub fn arithmetic() -> Self {
+        Self { evaluate: arithmetic, last: None }
+    }
+
+    pub fn units() -> Self {
+        Self { evaluate: units, last: None }
+    }
+
+    fn answer(&mut self, query: &str) -> Option<&Answer> {
+        if self.last.as_ref().is_none_or(|(last, _)| last != query) {
+            self.last = Some((query.to_owned(), (self.evaluate)(query)));
+        }
+
+        self.last.as_ref().and_then(|(_, answer)| answer.as_ref())
+    }
+}
+
+impl Mode for CalcMode {
+    fn count(&mut self, query: &str) -> usize {
+        self.answer(query).is_some() as usize
+    }
+
+    fn results(&mut self, query: &str, start: usize, count: usize) -> Vec<Row> {
+        let Some(answer) = self.answer(query).filter(|_| start == 0 && count > 0) else {
+            return Vec::new();
+        };
+
+        let description = match &answer.detail {
+            Some(detail) => format!("{detail}, Enter copies the result"),
+            None => "Enter copies the result".to_owned(),
+        };
+
+        let item = Command {
+            name: answer.title.clone(),
+            description,
+            exec: None,
+            icon: Some("accessories-calculator".to_owned()),
+            path: None,
+        };
+
+        vec![Row::Result(MatchOwned { item, score: 0, indices: Vec::new() })]
+    }
+
+    fn relevance(&mut self, query: &str, _rank: usize, _score: i64) -> f64 {
+        match self.answer(query) {
+            Some(answer) if answer.computed => 1.,
+            _ => TRIVIAL_RELEVANCE,
+        }
+    }
+
+    fn activate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        Ok(match self.answer(query).filter(|_| index == 0) {
+            Some(answer) => Activation::Copy(answer.value.clone()),
+            None => Activation::Nothing,
+        })
+    }
+}
+
+/// The answer to `query`, unless it's no arithmetic or a number that stands for itself.
+fn arithmetic(query: &str) -> Option<Answer> {
+    let query = query.trim();
+    let answer = calc::evaluate(query).ok()?;
+    let text = answer.text().ok()?;
+
+    if text == query {
+        return None;
+    }
+
+    Some(Answer {
+        title: text.clone(),
+        value: text,
+        detail: answer.value.fraction().map(|fraction| format!("= {fraction}")),
+        computed: answer.computed,
+    })
+}
+
+/// The quantity in `query` converted to the unit it asks for.
+fn units(query: &str) -> Option<Answer> {
+    let conversion = calc::convert(query)?.ok()?;
+
+    Some(Answer {
+        title: conversion.to_string(),
+        value: conversion.text(),
+        detail: Some(format!("{} in {}", conversion.from, conversion.unit)),
+        computed: true,
+    })
+}
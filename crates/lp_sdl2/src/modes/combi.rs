@@ -0,0 +1,160 @@
+//! The query run through several modes at once, their results combined under headers.
+
+use std::cmp::Ordering;
+use config::{Combi, CombiLayout};
+use crate::Result;
+use super::{Activation, Menu, Mode, Row, Shared};
+
+pub struct CombiMode {
+    sources: Vec<Source>,
+    max_per_mode: usize,
+    layout: CombiLayout,
+    /// The query the rows were built for.
+    query: Option<String>,
+    rows: Vec<Line>,
+}
+
+struct Source {
+    name: String,
+    /// Shared with the slot of the mode, so that it's built and scanned only once.
+    mode: Shared,
+}
+
+/// A built row, along with where a result came from.
+enum Line {
+    Header(usize),
+    /// The `rank`th result of the `source`th source.
+    Result { source: usize, rank: usize },
+}
+
+/// A result of a source, on its way into the combined rows.
+struct Candidate {
+    source: usize,
+    rank: usize,
+    relevance: f64,
+}
+
+impl CombiMode {
+    /// Combines the named `sources`, in their order.
+    pub fn new(settings: &Combi, sources: Vec<(String, Shared)>) -> Self {
+        Self {
+            sources: sources.into_iter().map(|(name, mode)| Source { name, mode }).collect(),
+            max_per_mode: settings.max_per_mode as usize,
+            layout: settings.layout,
+            query: None,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Builds the rows for `query` unless they are already.
+    fn update(&mut self, query: &str) {
+        if self.query.as_deref() == Some(query) {
+            return;
+        }
+
+        let mut candidates = Vec::new();
+
+        for (index, source) in self.sources.iter().enumerate() {
+            let mut mode = source.mode.borrow_mut();
+            let count = mode.count(query).min(self.max_per_mode);
+
+            for (rank, row) in mode.results(query, 0, count).into_iter().enumerate() {
+                // sources don't nest, a source's own headers are left out
+                let Row::Result(matched) = row else {
+                    continue;
+                };
+
+                let relevance = mode.relevance(query, rank, matched.score);
+                candidates.push(Candidate { source: index, rank, relevance });
+            }
+        }
+
+        let by_relevance = |a: f64, b: f64| b.partial_cmp(&a).unwrap_or(Ordering::Equal);
+
+        match self.layout {
+            CombiLayout::Grouped => {
+                let best = |source: usize| {
+                    candidates.iter().filter(|c| c.source == source).map(|c| c.relevance).fold(0., f64::max)
+                };
+                let mut order = (0..self.sources.len()).collect::<Vec<_>>();
+                order.sort_by(|a, b| by_relevance(best(*a), best(*b)));
+
+                candidates.sort_by_key(|c| order.iter().position(|source| *source == c.source));
+            }
+            // stable, so that the results of a source stay in its order on ties
+            CombiLayout::Interleaved => candidates.sort_by(|a, b| by_relevance(a.relevance, b.relevance)),
+        }
+
+        self.rows.clear();
+
+        for candidate in candidates {
+            if !matches!(self.rows.last(), Some(Line::Result { source, .. }) if *source == candidate.source) {
+                self.rows.push(Line::Header(candidate.source));
+            }
+
+            self.rows.push(Line::Result { source: candidate.source, rank: candidate.rank });
+        }
+
+        self.query = Some(query.to_owned());
+    }
+}
+
+impl Mode for CombiMode {
+    fn count(&mut self, query: &str) -> usize {
+        self.update(query);
+        self.rows.len()
+    }
+
+    fn results(&mut self, query: &str, start: usize, count: usize) -> Vec<Row> {
+        self.update(query);
+
+        let Self { sources, rows, .. } = self;
+
+        rows.iter()
+            .skip(start)
+            .take(count)
+            .filter_map(|line| match *line {
+                Line::Header(source) => Some(Row::Header(sources[source].name.clone())),
+                Line::Result { source, rank } => sources[source].mode.borrow_mut().results(query, rank, 1).pop(),
+            })
+            .collect()
+    }
+
+    fn activate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        self.update(query);
+
+        match self.rows.get(index) {
+            Some(Line::Result { source, rank }) => self.sources[*source].mode.borrow_mut().activate(query, *rank),
+            Some(Line::Header(_)) | None => Ok(Activation::Nothing),
+        }
+    }
+
+    fn activate_alternate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        self.update(query);
+
+        match self.rows.get(index) {
+            Some(Line::Result { source, rank }) => self.sources[*source].mode.borrow_mut().activate_alternate(query, *rank),
+            Some(Line::Header(_)) | None => Ok(Activation::Nothing),
+        }
+    }
+
+    fn actions(&mut self, query: &str, index: usize) -> Option<Menu> {
+        self.update(query);
+
+        match self.rows.get(index) {
+            Some(Line::Result { source, rank }) => self.sources[*source].mode.borrow_mut().actions(query, *rank),
+            Some(Line::Header(_)) | None => None,
+        }
+    }
+
+    /// Results that come in from any source rebuild the rows.
+    fn poll(&mut self, query: &str) -> bool {
+        let changed = self.sources.iter().fold(false, |changed, source| source.mode.borrow_mut().poll(query) | changed);
+
+        if changed {
+            self.query = None;
+        }
+
+        changed
+    }
+}
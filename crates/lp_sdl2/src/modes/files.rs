@@ -0,0 +1,336 @@
+//! Files below the configured roots, from an index kept up to date in the background, see
+//! [`index`](crate::index).
+
+use std::path::{Path, PathBuf};
+use sdl2::EventSubsystem;
+use fuzzer::{Fuzzable, Fuzzer, MatchOwned};
+use config::Command;
+use crate::desktop::{self, quote};
+use crate::index::Index;
+use crate::preview::Wake;
+use crate::mimeapps::Handlers;
+use crate::{mime, xdg, Error, Result};
+use super::fuzzy::FuzzyMode;
+use super::{Activation, Menu, Mode, Row};
+
+/// The files, indexed from the first query on and kept up to date from then on, also while
+/// the mode isn't used.
+pub struct FilesMode {
+    settings: config::Files,
+    events: EventSubsystem,
+    index: Option<Index>,
+    /// With whether each file is a directory.
+    files: PathList<bool>,
+}
+
+impl FilesMode {
+    pub fn new(settings: config::Files, events: EventSubsystem) -> Self {
+        Self {
+            settings,
+            events,
+            index: None,
+            files: PathList::new(xdg::home()),
+        }
+    }
+}
+
+impl Mode for FilesMode {
+    /// Every file while the query is empty.
+    fn count(&mut self, query: &str) -> usize {
+        self.files.count(query)
+    }
+
+    /// Named after the file and described by the directory it's in.
+    fn results(&mut self, query: &str, start: usize, count: usize) -> Vec<Row> {
+        self.files.results(query, start, count, |file, parent| (parent, icon(&file.path, file.info)))
+    }
+
+    /// The files are in the order of their paths while the query is empty.
+    fn relevance(&mut self, query: &str, rank: usize, score: i64) -> f64 {
+        self.files.relevance(query, rank, score)
+    }
+
+    /// Opens the file with the application for its type.
+    fn activate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        self.files.activate(query, index)
+    }
+
+    /// Shows the file in the file manager.
+    fn activate_alternate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        self.files.activate_alternate(query, index)
+    }
+
+    /// The applications that can open the file.
+    fn actions(&mut self, query: &str, index: usize) -> Option<Menu> {
+        self.files.actions(query, index)
+    }
+
+    /// Starts indexing on the first call, then picks up the files as they change.
+    fn poll(&mut self, _query: &str) -> bool {
+        let index = self.index.get_or_insert_with(|| {
+            let wake = self.events.event_sender();
+
+            Index::spawn(self.settings.clone(), move || {
+                let _ = wake.push_custom_event(Wake);
+            })
+        });
+
+        let Some(entries) = index.latest() else {
+            return false;
+        };
+
+        self.files.set_files(entries.into_iter().map(|entry| (entry.path, entry.is_dir)));
+        true
+    }
+}
+
+/// A file of a [`PathList`], with what the mode listing it knows about it.
+#[derive(Clone)]
+pub struct Listed<T> {
+    pub path: PathBuf,
+    /// The path with the home directory shortened to `~`.
+    display: String,
+    /// Index of the char of `display` where the file name starts.
+    name_start: usize,
+    pub info: T,
+}
+
+impl<T> Fuzzable for Listed<T> {
+    fn pattern(&self) -> String {
+        self.display.clone()
+    }
+
+    /// Matching the file name counts for more than matching the directories it's in.
+    fn emphasis(&self) -> usize {
+        self.name_start
+    }
+}
+
+/// Files matched by their paths, and what the modes listing files do alike with them:
+/// opening them with the application for their type, showing them in the file manager and
+/// listing the applications that can open them.
+pub struct PathList<T> {
+    fuzzer: Fuzzer<Listed<T>>,
+    home: Option<PathBuf>,
+    handlers: Handlers,
+}
+
+impl<T: Clone> PathList<T> {
+    /// No files yet, shown with `home` shortened to `~`.
+    pub fn new(home: Option<PathBuf>) -> Self {
+        Self {
+            fuzzer: Fuzzer::new(Vec::new()),
+            home,
+            handlers: Handlers::default(),
+        }
+    }
+
+    /// Lists `files` in place of the ones listed so far, in their order.
+    pub fn set_files(&mut self, files: impl IntoIterator<Item = (PathBuf, T)>) {
+        let files = files
+            .into_iter()
+            .map(|(path, info)| {
+                let (display, name_start) = display(self.home.as_deref(), &path);
+                Listed { path, display, name_start, info }
+            })
+            .collect();
+
+        self.fuzzer = Fuzzer::new(files);
+    }
+
+    fn set_query(&mut self, query: &str) {
+        if self.fuzzer.input() != query {
+            let input = self.fuzzer.input_mut();
+            input.clear();
+            input.push_str(query);
+        }
+    }
+
+    /// Every file while the query is empty.
+    pub fn count(&mut self, query: &str) -> usize {
+        self.set_query(query);
+
+        if query.is_empty() {
+            self.fuzzer.get_rankings().len()
+        } else {
+            self.fuzzer.matched_count()
+        }
+    }
+
+    /// Rows named after the files, with the description and icon `describe` gives a file
+    /// and the directory it's in.
+    pub fn results(
+        &mut self,
+        query: &str,
+        start: usize,
+        count: usize,
+        describe: impl Fn(&Listed<T>, String) -> (String, String),
+    ) -> Vec<Row> {
+        let count = count.min(self.count(query).saturating_sub(start));
+
+        self.fuzzer
+            .matches()
+            .skip(start)
+            .take(count)
+            .map(|m| {
+                let file = m.item;
+                let (name, parent) = split_display(&file.display, file.name_start);
+                let (description, icon) = describe(file, parent);
+
+                let item = Command {
+                    name,
+                    description,
+                    exec: None,
+                    icon: Some(icon),
+                    path: Some(file.path.clone()),
+                };
+                let indices = m.indices.iter().filter_map(|i| i.checked_sub(file.name_start)).collect();
+
+                Row::Result(MatchOwned { item, score: m.score, indices })
+            })
+            .collect()
+    }
+
+    /// The files are in their own order while the query is empty.
+    pub fn relevance(&mut self, query: &str, rank: usize, score: i64) -> f64 {
+        self.set_query(query);
+
+        if query.is_empty() {
+            1. / (rank + 1) as f64
+        } else {
+            self.fuzzer.normalize(score)
+        }
+    }
+
+    fn selected(&mut self, query: &str, index: usize) -> Option<Listed<T>> {
+        self.set_query(query);
+        self.fuzzer.matches().nth(index).map(|m| m.item.clone())
+    }
+
+    /// Opens the file with the application for its type.
+    pub fn activate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        let Some(file) = self.selected(query, index) else {
+            return Ok(Activation::Nothing);
+        };
+
+        let exec = open(&file.path, &self.handlers)?;
+        Ok(launch(file, exec))
+    }
+
+    /// Shows the file in the file manager.
+    pub fn activate_alternate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        let Some(file) = self.selected(query, index) else {
+            return Ok(Activation::Nothing);
+        };
+
+        let exec = reveal(&file.path);
+        Ok(launch(file, exec))
+    }
+
+    /// The applications that can open the file.
+    pub fn actions(&mut self, query: &str, index: usize) -> Option<Menu> {
+        let file = self.selected(query, index)?;
+        open_with(&file.path, &self.handlers)
+    }
+}
+
+fn launch<T>(file: Listed<T>, exec: String) -> Activation {
+    Activation::Launch(Command {
+        name: file.display,
+        description: String::new(),
+        exec: Some(exec),
+        icon: None,
+        path: Some(file.path),
+    })
+}
+
+/// `path` with the home directory shortened to `~`, and the index of the char where its
+/// file name starts.
+pub fn display(home: Option<&Path>, path: &Path) -> (String, usize) {
+    let display = match home.and_then(|home| path.strip_prefix(home).ok()) {
+        Some(relative) if relative.as_os_str().is_empty() => "~".to_owned(),
+        Some(relative) => format!("~/{}", relative.display()),
+        None => path.display().to_string(),
+    };
+    let name_start = display.rfind('/').map_or(0, |slash| display[..=slash].chars().count());
+
+    (display, name_start)
+}
+
+/// The name and the directory of a file shown by [`display`], as a result shows them.
+pub fn split_display(display: &str, name_start: usize) -> (String, String) {
+    let name = display.chars().skip(name_start).collect();
+    let parent = display.chars().take(name_start.saturating_sub(1)).collect::<String>();
+
+    (name, if parent.is_empty() { "/".to_owned() } else { parent })
+}
+
+/// Icon for the file at `path`, by the type its name tells, which takes no reading it.
+pub fn icon(path: &Path, is_dir: bool) -> String {
+    if is_dir {
+        return "folder".to_owned();
+    }
+
+    path.file_name()
+        .and_then(|name| mime::database().by_name(&name.to_string_lossy()).map(mime::icon))
+        .unwrap_or_else(|| "text-x-generic".to_owned())
+}
+
+/// Command line opening `path` with `xdg-open`, or else with the default application of
+/// its type.
+pub fn open(path: &Path, handlers: &Handlers) -> Result<String> {
+    if desktop::is_installed("xdg-open") {
+        return Ok(format!("xdg-open {}", quote(&path.to_string_lossy())));
+    }
+
+    mime::database()
+        .guess(path)
+        .and_then(|mime| handlers.default_for(mime))
+        .and_then(|entry| entry.command_line_with(Some(path)))
+        .ok_or_else(|| Error::NoHandler(path.to_owned()))
+}
+
+/// A menu of the applications that can open `path`, by its type, the default one first.
+/// Choosing one opens the file with it.
+pub fn open_with(path: &Path, handlers: &Handlers) -> Option<Menu> {
+    let mime = mime::database().guess(path)?;
+
+    let items = handlers
+        .of(mime)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(rank, entry)| {
+            let description = match rank {
+                0 => "Default application".to_owned(),
+                _ => entry.comment.clone().or_else(|| entry.generic_name.clone()).unwrap_or_default(),
+            };
+
+            Some(Command {
+                exec: Some(entry.command_line_with(Some(path))?),
+                name: entry.name,
+                description,
+                icon: entry.icon,
+                path: Some(entry.path),
+            })
+        })
+        .collect();
+
+    Some(Menu { title: format!("Open with ({mime})"), mode: Box::new(FuzzyMode::new(items)) })
+}
+
+/// Command line showing `path` selected in the file manager, through the FileManager1
+/// D-Bus interface, or else opening the directory it's in.
+pub fn reveal(path: &Path) -> String {
+    let parent = path.parent().unwrap_or(Path::new("/"));
+
+    format!(
+        "dbus-send --session --print-reply --dest=org.freedesktop.FileManager1 --type=method_call \
+         /org/freedesktop/FileManager1 org.freedesktop.FileManager1.ShowItems array:string:{} string: \
+         >/dev/null 2>&1 || xdg-open {}",
+        quote(&desktop::uri(path)),
+        quote(&parent.to_string_lossy()),
+    )
+}
+
+#[cfg(test)]
+mod tests;
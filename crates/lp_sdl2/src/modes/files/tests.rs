@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+use crate::modes::{Activation, Row};
+use super::{display, split_display, PathList};
+
+fn shown(path: &str) -> (String, String) {
+    let (display, name_start) = display(Some(Path::new("/home/user")), Path::new(path));
+    split_display(&display, name_start)
+}
+
+#[test]
+fn shortens_the_home_directory() {
+    assert_eq!(display(Some(Path::new("/home/user")), Path::new("/home/user/docs/a.md")), ("~/docs/a.md".to_owned(), 7));
+    assert_eq!(display(Some(Path::new("/home/user")), Path::new("/home/user")), ("~".to_owned(), 0));
+    assert_eq!(display(Some(Path::new("/home/user")), Path::new("/home/username/a")), ("/home/username/a".to_owned(), 15));
+    assert_eq!(display(None, Path::new("/home/user/a")), ("/home/user/a".to_owned(), 11));
+}
+
+#[test]
+fn splits_the_name_from_its_directory() {
+    assert_eq!(shown("/home/user/docs/a.md"), ("a.md".to_owned(), "~/docs".to_owned()));
+    assert_eq!(shown("/home/user/a.md"), ("a.md".to_owned(), "~".to_owned()));
+    assert_eq!(shown("/etc/hosts"), ("hosts".to_owned(), "/etc".to_owned()));
+    assert_eq!(shown("/vmlinuz"), ("vmlinuz".to_owned(), "/".to_owned()));
+}
+
+#[test]
+fn counts_the_name_start_in_characters() {
+    let (display, name_start) = display(Some(Path::new("/home/user")), Path::new("/home/user/Документы/отчёт.txt"));
+
+    assert_eq!(name_start, "~/Документы/".chars().count());
+    assert_eq!(split_display(&display, name_start), ("отчёт.txt".to_owned(), "~/Документы".to_owned()));
+}
+
+fn list() -> PathList<u32> {
+    let mut list = PathList::new(Some(PathBuf::from("/home/user")));
+    list.set_files([("/home/user/docs/report.md", 1), ("/etc/hosts", 2)].map(|(path, n)| (PathBuf::from(path), n)));
+    list
+}
+
+/// Name, description and highlighted characters of the rows for `query`.
+fn rows(list: &mut PathList<u32>, query: &str) -> Vec<(String, String, Vec<usize>)> {
+    list.results(query, 0, 10, |file, parent| (format!("{parent} #{}", file.info), "icon".to_owned()))
+        .into_iter()
+        .filter_map(Row::result)
+        .map(|m| (m.item.name, m.item.description, m.indices))
+        .collect()
+}
+
+#[test]
+fn lists_files_by_name_in_their_directory() {
+    let mut list = list();
+
+    assert_eq!(list.count(""), 2);
+    assert_eq!(
+        rows(&mut list, ""),
+        [
+            ("report.md".to_owned(), "~/docs #1".to_owned(), Vec::new()),
+            ("hosts".to_owned(), "/etc #2".to_owned(), Vec::new()),
+        ],
+    );
+
+    // the highlights are of the name, the directory isn't part of it
+    assert_eq!(list.count("hosts"), 1);
+    assert_eq!(rows(&mut list, "hosts"), [("hosts".to_owned(), "/etc #2".to_owned(), vec![0, 1, 2, 3, 4])]);
+}
+
+#[test]
+fn shows_files_in_the_file_manager() {
+    let mut list = list();
+
+    let Ok(Activation::Launch(command)) = list.activate_alternate("", 1) else {
+        panic!("the file is launched");
+    };
+
+    assert_eq!(command.name, "/etc/hosts");
+    assert_eq!(command.path, Some(PathBuf::from("/etc/hosts")));
+    assert!(command.exec().contains("org.freedesktop.FileManager1.ShowItems"));
+    assert!(matches!(list.activate_alternate("", 2), Ok(Activation::Nothing)));
+}
@@ -0,0 +1,54 @@
+//! Modes with a fixed list of items, fuzzy matched by name.
+
+use fuzzer::Fuzzer;
+use config::Command;
+use super::{Mode, Row};
+
+pub struct FuzzyMode {
+    fuzzer: Fuzzer<Command>,
+}
+
+impl FuzzyMode {
+    pub fn new(items: Vec<Command>) -> Self {
+        Self { fuzzer: Fuzzer::new(items) }
+    }
+
+    fn set_query(&mut self, query: &str) {
+        if self.fuzzer.input() != query {
+            let input = self.fuzzer.input_mut();
+            input.clear();
+            input.push_str(query);
+        }
+    }
+}
+
+impl Mode for FuzzyMode {
+    /// Every item while the query is empty.
+    fn count(&mut self, query: &str) -> usize {
+        self.set_query(query);
+
+        if query.is_empty() {
+            self.fuzzer.get_rankings().len()
+        } else {
+            self.fuzzer.matched_count()
+        }
+    }
+
+    /// Only these are built, however many items there are.
+    fn results(&mut self, query: &str, start: usize, count: usize) -> Vec<Row> {
+        let count = count.min(self.count(query).saturating_sub(start));
+
+        self.fuzzer.matches().skip(start).take(count).map(|m| Row::Result(m.owned())).collect()
+    }
+
+    /// The items are in their own order while the query is empty.
+    fn relevance(&mut self, query: &str, rank: usize, score: i64) -> f64 {
+        self.set_query(query);
+
+        if query.is_empty() {
+            1. / (rank + 1) as f64
+        } else {
+            self.fuzzer.normalize(score)
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Results from a plugin, see [`plugin`](::plugin).
+
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+use sdl2::EventSubsystem;
+use fuzzer::{Fuzzable, Fuzzer, MatchOwned};
+use config::Command;
+use plugin::{Action, Item, Plugin};
+use crate::preview::Wake;
+use crate::{error, Result};
+use super::{Activation, Menu, Mode, Row};
+
+/// How long a plugin gets to act on an activation before it's killed.
+const ACTIVATE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A plugin, started when the first query comes in and stopped when the mode is left.
+pub struct PluginMode {
+    name: String,
+    command: String,
+    events: EventSubsystem,
+    /// Shared with the menu of actions open on one of its items, which ends it too.
+    session: Rc<RefCell<Option<Session>>>,
+    /// The plugin failed to start, it isn't retried until the mode is left.
+    failed: bool,
+}
+
+struct Session {
+    /// The query the plugin was last given.
+    text: Option<String>,
+    plugin: Plugin,
+}
+
+impl PluginMode {
+    pub fn new(config: &config::Plugin, events: EventSubsystem) -> Self {
+        Self {
+            name: config.name.clone(),
+            command: config.command.clone(),
+            events,
+            session: Rc::default(),
+            failed: false,
+        }
+    }
+
+    fn items(&self) -> Ref<'_, [Item]> {
+        Ref::map(self.session.borrow(), |session| session.as_ref().map_or(&[][..], |s| s.plugin.items()))
+    }
+
+    /// Hands the `index`th item to the plugin with the `rank`th of its actions, or with
+    /// none when it has none.
+    fn activate_with(&mut self, index: usize, rank: usize) -> Result<Activation> {
+        let Some(item) = self.items().get(index).cloned() else {
+            return Ok(Activation::Nothing);
+        };
+
+        let action = item.actions.get(rank.min(item.actions.len().saturating_sub(1))).cloned();
+        hand_over(&self.session, &self.name, &item, action.as_ref())
+    }
+}
+
+impl Mode for PluginMode {
+    /// The plugin ranks its results itself.
+    fn count(&mut self, _query: &str) -> usize {
+        self.items().len()
+    }
+
+    fn results(&mut self, _query: &str, start: usize, count: usize) -> Vec<Row> {
+        self.items()
+            .iter()
+            .skip(start)
+            .take(count)
+            .map(|item| Row::Result(MatchOwned { item: command(item), score: 0, indices: Vec::new() }))
+            .collect()
+    }
+
+    /// Hands the result to the plugin with its default action, which ends the plugin.
+    fn activate(&mut self, _query: &str, index: usize) -> Result<Activation> {
+        self.activate_with(index, 0)
+    }
+
+    /// Hands the result to the plugin with its second action, if it has one.
+    fn activate_alternate(&mut self, _query: &str, index: usize) -> Result<Activation> {
+        self.activate_with(index, 1)
+    }
+
+    /// The actions of the result, choosing one hands the result to the plugin with it.
+    fn actions(&mut self, _query: &str, index: usize) -> Option<Menu> {
+        let item = self.items().get(index).cloned().filter(|item| !item.actions.is_empty())?;
+        let choices = item.actions.iter().cloned().enumerate().map(|(rank, action)| Choice { rank, action }).collect();
+
+        Some(Menu {
+            title: item.title.clone(),
+            mode: Box::new(ActionMenu {
+                name: self.name.clone(),
+                item,
+                fuzzer: Fuzzer::new(choices),
+                session: Rc::clone(&self.session),
+            }),
+        })
+    }
+
+    /// Starts the plugin if needed and hands it the query.
+    fn poll(&mut self, query: &str) -> bool {
+        if self.failed {
+            return false;
+        }
+
+        let mut changed = false;
+        let mut session = self.session.borrow_mut();
+
+        if session.is_none() {
+            let wake = self.events.event_sender();
+
+            match Plugin::spawn(&self.command, move || {
+                let _ = wake.push_custom_event(Wake);
+            }) {
+                Ok(plugin) => {
+                    *session = Some(Session { text: None, plugin });
+                    changed = true;
+                }
+                Err(e) => {
+                    eprintln!("warning: failed to start plugin '{}': {e}", self.name);
+                    self.failed = true;
+                    return false;
+                }
+            }
+        }
+
+        let Some(session) = session.as_mut() else {
+            return changed;
+        };
+
+        if session.text.as_deref() != Some(query) {
+            if let Err(e) = session.plugin.query(query) {
+                eprintln!("warning: failed to query plugin '{}': {e}", self.name);
+            }
+            session.text = Some(query.to_owned());
+        }
+
+        session.plugin.poll() | changed
+    }
+
+    fn stop(&mut self) {
+        *self.session.borrow_mut() = None;
+        self.failed = false;
+    }
+}
+
+/// The actions of an item of a plugin.
+struct ActionMenu {
+    /// Name of the plugin.
+    name: String,
+    item: Item,
+    fuzzer: Fuzzer<Choice>,
+    session: Rc<RefCell<Option<Session>>>,
+}
+
+#[derive(Clone)]
+struct Choice {
+    /// Place of the action among those of the item, the first being the default.
+    rank: usize,
+    action: Action,
+}
+
+impl Fuzzable for Choice {
+    fn pattern(&self) -> String {
+        self.action.title.clone()
+    }
+}
+
+impl ActionMenu {
+    fn set_query(&mut self, query: &str) {
+        if self.fuzzer.input() != query {
+            let input = self.fuzzer.input_mut();
+            input.clear();
+            input.push_str(query);
+        }
+    }
+}
+
+impl Mode for ActionMenu {
+    /// Every action while the query is empty.
+    fn count(&mut self, query: &str) -> usize {
+        self.set_query(query);
+
+        if query.is_empty() {
+            self.fuzzer.get_rankings().len()
+        } else {
+            self.fuzzer.matched_count()
+        }
+    }
+
+    fn results(&mut self, query: &str, start: usize, count: usize) -> Vec<Row> {
+        let count = count.min(self.count(query).saturating_sub(start));
+
+        self.fuzzer
+            .matches()
+            .skip(start)
+            .take(count)
+            .map(|m| {
+                let item = Command {
+                    name: m.item.action.title.clone(),
+                    description: if m.item.rank == 0 { "Default action".to_owned() } else { String::new() },
+                    exec: None,
+                    icon: self.item.icon.clone(),
+                    path: None,
+                };
+
+                Row::Result(MatchOwned { item, score: m.score, indices: m.indices.to_vec() })
+            })
+            .collect()
+    }
+
+    fn activate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        self.set_query(query);
+
+        let Some(action) = self.fuzzer.matches().nth(index).map(|m| m.item.action.clone()) else {
+            return Ok(Activation::Nothing);
+        };
+
+        hand_over(&self.session, &self.name, &self.item, Some(&action))
+    }
+}
+
+/// Hands `item` to the plugin with `action`, which ends the plugin.
+fn hand_over(session: &RefCell<Option<Session>>, name: &str, item: &Item, action: Option<&Action>) -> Result<Activation> {
+    let Some(session) = session.borrow_mut().take() else {
+        return Ok(Activation::Nothing);
+    };
+
+    session.plugin.activate(item, action, ACTIVATE_TIMEOUT).map_err(|e| error::launch(name, e))?;
+
+    Ok(Activation::Done)
+}
+
+fn command(item: &Item) -> Command {
+    Command {
+        name: item.title.clone(),
+        description: item.subtitle.clone(),
+        exec: None,
+        icon: item.icon.clone(),
+        path: None,
+    }
+}
@@ -0,0 +1,91 @@
+//! Documents opened recently, see [`recent`](crate::recent).
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::recent::{self, Document};
+use crate::{mime, xdg, Result};
+use super::files::{self, PathList};
+use super::{Activation, Menu, Mode, Row};
+
+/// The recently used documents, most recent first while the query is empty. They're read
+/// again whenever the file changes.
+pub struct RecentMode {
+    documents: PathList<Document>,
+    /// When the file was modified as of the last read.
+    modified: Option<SystemTime>,
+}
+
+impl RecentMode {
+    /// The documents as they are now.
+    pub fn load() -> Self {
+        let mut mode = Self { documents: PathList::new(xdg::home()), modified: None };
+        mode.reload();
+        mode
+    }
+
+    /// Reads the documents if the file changed since the last time. Returns whether it did.
+    fn reload(&mut self) -> bool {
+        let modified = recent::path().and_then(|path| path.metadata().ok()).and_then(|m| m.modified().ok());
+
+        if modified == self.modified {
+            return false;
+        }
+
+        let documents = recent::documents().into_iter().map(|document| (document.path.clone(), document));
+        self.documents.set_files(documents);
+        self.modified = modified;
+        true
+    }
+}
+
+impl Mode for RecentMode {
+    /// Every document while the query is empty.
+    fn count(&mut self, query: &str) -> usize {
+        self.documents.count(query)
+    }
+
+    /// Described by the directory, when it was used and by which application.
+    fn results(&mut self, query: &str, start: usize, count: usize) -> Vec<Row> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+
+        self.documents.results(query, start, count, |listed, parent| {
+            let document = &listed.info;
+            let used = recent::ago(document.used, now);
+
+            let description = match &document.application {
+                Some(application) => format!("{parent}, {used} in {application}"),
+                None => format!("{parent}, {used}"),
+            };
+            let icon = match &document.mime {
+                Some(mime) => mime::icon(mime),
+                None => files::icon(&document.path, document.path.is_dir()),
+            };
+
+            (description, icon)
+        })
+    }
+
+    /// The documents are by recency while the query is empty.
+    fn relevance(&mut self, query: &str, rank: usize, score: i64) -> f64 {
+        self.documents.relevance(query, rank, score)
+    }
+
+    /// Opens the document with the application for its type.
+    fn activate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        self.documents.activate(query, index)
+    }
+
+    /// Shows the document in the file manager.
+    fn activate_alternate(&mut self, query: &str, index: usize) -> Result<Activation> {
+        self.documents.activate_alternate(query, index)
+    }
+
+    /// The applications that can open the document.
+    fn actions(&mut self, query: &str, index: usize) -> Option<Menu> {
+        self.documents.actions(query, index)
+    }
+
+    /// Picks up documents used since the file was last read.
+    fn poll(&mut self, _query: &str) -> bool {
+        self.reload()
+    }
+}
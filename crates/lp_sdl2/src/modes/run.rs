@@ -0,0 +1,106 @@
+//! Programs on `$PATH`, or whatever command line is typed.
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use fuzzer::MatchOwned;
+use config::Command;
+use super::fuzzy::FuzzyMode;
+use super::{Mode, Row};
+
+pub struct RunMode {
+    programs: FuzzyMode,
+}
+
+impl RunMode {
+    /// The programs on `$PATH` as it is now.
+    pub fn from_path() -> Self {
+        Self { programs: FuzzyMode::new(programs()) }
+    }
+
+    /// The query as a command line of its own, when it doesn't name a program on its own.
+    fn typed(&mut self, query: &str) -> Option<Command> {
+        let query = query.trim();
+
+        if query.is_empty() || self.programs.count(query) > 0 && !query.contains(char::is_whitespace) {
+            return None;
+        }
+
+        Some(Command {
+            name: query.to_owned(),
+            description: "Run command".to_owned(),
+            exec: None,
+            icon: None,
+            path: None,
+        })
+    }
+}
+
+impl Mode for RunMode {
+    fn count(&mut self, query: &str) -> usize {
+        let typed = self.typed(query).is_some() as usize;
+        typed + self.programs.count(query)
+    }
+
+    /// The typed command line comes first.
+    fn results(&mut self, query: &str, start: usize, count: usize) -> Vec<Row> {
+        let Some(typed) = self.typed(query) else {
+            return self.programs.results(query, start, count);
+        };
+
+        let typed = Row::Result(MatchOwned { item: typed, score: 0, indices: Vec::new() });
+
+        if start == 0 {
+            [typed].into_iter().chain(self.programs.results(query, 0, count.saturating_sub(1))).collect()
+        } else {
+            self.programs.results(query, start - 1, count)
+        }
+    }
+
+    /// The typed command line is what was asked for, if it's there.
+    fn relevance(&mut self, query: &str, rank: usize, score: i64) -> f64 {
+        match self.typed(query) {
+            Some(_) if rank == 0 => 1.,
+            Some(_) => self.programs.relevance(query, rank - 1, score),
+            None => self.programs.relevance(query, rank, score),
+        }
+    }
+}
+
+/// The executables on `$PATH` by name, sorted. A name found in several directories is the
+/// one that comes first, like the shell would run.
+fn programs() -> Vec<Command> {
+    let Some(paths) = env::var_os("PATH") else {
+        return Vec::new();
+    };
+
+    let mut seen = HashSet::new();
+    let mut programs = Vec::new();
+
+    for dir in env::split_paths(&paths) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            // following symlinks, which is what most of /usr/bin is made of
+            let Ok(metadata) = fs::metadata(entry.path()) else {
+                continue;
+            };
+
+            if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            if seen.insert(name.clone()) {
+                programs.push(Command { name, description: String::new(), exec: None, icon: None, path: None });
+            }
+        }
+    }
+
+    programs.sort_by(|a, b| a.name.cmp(&b.name));
+    programs
+}
@@ -0,0 +1,94 @@
+//! Where the window opens and how the window manager treats it.
+
+use sdl2::rect::Rect as SdlRect;
+use sdl2::sys::SDL_WindowFlags;
+use sdl2::VideoSubsystem;
+use config::{Placement, Position};
+use crate::{error, Result};
+
+/// Window flags for the configured appearance. They're passed all at once, since setting
+/// them replaces the ones set by the builder methods.
+pub fn window_flags(config: &config::Window) -> u32 {
+    // shown only once it's at its final size and position
+    let mut flags = SDL_WindowFlags::SDL_WINDOW_HIDDEN as u32 | SDL_WindowFlags::SDL_WINDOW_ALLOW_HIGHDPI as u32;
+
+    for (enabled, flag) in [
+        (!config.decorations, SDL_WindowFlags::SDL_WINDOW_BORDERLESS),
+        (config.always_on_top, SDL_WindowFlags::SDL_WINDOW_ALWAYS_ON_TOP),
+        (config.skip_taskbar, SDL_WindowFlags::SDL_WINDOW_SKIP_TASKBAR),
+        (config.utility, SDL_WindowFlags::SDL_WINDOW_UTILITY),
+    ] {
+        if enabled {
+            flags |= flag as u32;
+        }
+    }
+
+    flags
+}
+
+/// The display the window opens on.
+pub struct Display {
+    /// The part of the display not taken by panels and docks, in window coordinates.
+    bounds: SdlRect,
+}
+
+impl Display {
+    /// The display the configured position is on, the primary one unless the window
+    /// follows the cursor or is placed at coordinates on another display.
+    pub fn find(video: &VideoSubsystem, position: Position) -> Result<Self> {
+        let index = match position {
+            Position::Placement(Placement::Cursor) => display_at(video, cursor()),
+            Position::Placement(_) => None,
+            Position::At { x, y } => display_at(video, (x, y)),
+        };
+
+        let index = index.unwrap_or(0);
+        let bounds = video
+            .display_usable_bounds(index)
+            .or_else(|_| video.display_bounds(index))
+            .map_err(error::system_video)?;
+
+        Ok(Self { bounds })
+    }
+
+    /// Size of the window, logical pixels are multiplied by `scale`.
+    pub fn window_size(&self, config: &config::Window, scale: f32) -> (u32, u32) {
+        (
+            config.width.resolve(self.bounds.width(), scale),
+            config.height.resolve(self.bounds.height(), scale),
+        )
+    }
+
+    /// Top left corner of a window of `size` at `position`.
+    pub fn window_origin(&self, position: Position, (width, height): (u32, u32)) -> (i32, i32) {
+        let bounds = self.bounds;
+        let centered = |start: i32, available: u32, length: u32| start + (available as i32 - length as i32) / 2;
+        let x = centered(bounds.x(), bounds.width(), width);
+
+        match position {
+            Position::Placement(Placement::Top) => (x, bounds.y() + bounds.height() as i32 / 6),
+            Position::Placement(Placement::Center | Placement::Cursor) => {
+                (x, centered(bounds.y(), bounds.height(), height))
+            }
+            Position::At { x, y } => (x, y),
+        }
+    }
+}
+
+/// Index of the display containing `point`.
+fn display_at(video: &VideoSubsystem, point: (i32, i32)) -> Option<i32> {
+    (0..video.num_video_displays().ok()?)
+        .find(|&index| video.display_bounds(index).is_ok_and(|bounds| bounds.contains_point(point)))
+}
+
+/// The cursor position on the desktop, wherever it is.
+fn cursor() -> (i32, i32) {
+    let (mut x, mut y) = (0, 0);
+
+    // SAFETY: the function only writes the two integers, which outlive the call
+    unsafe {
+        sdl2::sys::SDL_GetGlobalMouseState(&mut x, &mut y);
+    }
+
+    (x, y)
+}
@@ -0,0 +1,259 @@
+//! Details of the selected result, either built in or from a user-configured command.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Read};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use sdl2::event::EventSender;
+use sdl2::EventSubsystem;
+use config::Command;
+
+/// Lines kept from a file or the command output, more never fit on screen.
+const MAX_LINES: usize = 200;
+/// Bytes read from a file or the command output at most.
+const MAX_BYTES: u64 = 64 * 1024;
+const IMAGE_EXTENSIONS: &[&str] = &["png", "svg", "svgz", "xpm"];
+
+/// Pushed onto the SDL event queue by background work, so that an idle event loop wakes
+/// up to pick up the result.
+pub struct Wake;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Body {
+    Empty,
+    Text(Vec<String>),
+    Image(PathBuf),
+    /// The preview command is still running.
+    Pending,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preview {
+    /// Short facts about the item, like its command line and path.
+    pub header: Vec<String>,
+    pub body: Body,
+}
+
+/// The preview of the selected item, along with the command producing it.
+pub struct PreviewState {
+    command: Option<String>,
+    timeout: Duration,
+    events: EventSubsystem,
+    /// Name and path of the previewed item, rows of different files can share a name.
+    item: Option<(String, Option<PathBuf>)>,
+    preview: Option<Preview>,
+    job: Option<Job>,
+}
+
+impl PreviewState {
+    pub fn new(config: &config::Preview, events: EventSubsystem) -> Self {
+        Self {
+            command: config.command.clone(),
+            timeout: config.timeout(),
+            events,
+            item: None,
+            preview: None,
+            job: None,
+        }
+    }
+
+    /// Starts previewing `item` unless it's already shown, cancelling the previous
+    /// command. Returns whether the preview changed.
+    pub fn select(&mut self, item: Option<&Command>) -> bool {
+        let key = item.map(|c| (c.name.clone(), c.path.clone()));
+
+        if self.item == key {
+            return false;
+        }
+
+        self.job = None;
+        self.item = key;
+        self.preview = item.map(|item| {
+            let header = header(item);
+
+            let body = match &self.command {
+                Some(command) => match Job::spawn(command, item, self.timeout, self.events.event_sender()) {
+                    Ok(job) => {
+                        self.job = Some(job);
+                        Body::Pending
+                    }
+                    Err(e) => Body::Failed(format!("failed to run the preview command: {e}")),
+                },
+                None => item.path.as_deref().map_or(Body::Empty, builtin),
+            };
+
+            Preview { header, body }
+        });
+
+        true
+    }
+
+    /// Picks up the output of the command, or kills it once it took too long. Returns
+    /// whether the preview changed.
+    pub fn poll(&mut self) -> bool {
+        let Some(job) = &mut self.job else {
+            return false;
+        };
+
+        let body = match job.output.try_recv() {
+            Ok(Ok(output)) => Body::Text(lines(&output)),
+            Ok(Err(e)) => Body::Failed(format!("failed to read the preview command output: {e}")),
+            Err(TryRecvError::Empty) if Instant::now() < job.deadline => return false,
+            Err(TryRecvError::Empty) => Body::Failed(format!("timed out after {} ms", self.timeout.as_millis())),
+            Err(TryRecvError::Disconnected) => Body::Failed("the preview command went away".to_owned()),
+        };
+
+        self.job = None;
+
+        if let Some(preview) = &mut self.preview {
+            preview.body = body;
+        }
+
+        true
+    }
+
+    /// Time left until the running command times out.
+    pub fn time_left(&self) -> Option<Duration> {
+        self.job.as_ref().map(|job| job.deadline.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn preview(&self) -> Option<&Preview> {
+        self.preview.as_ref()
+    }
+}
+
+/// A running preview command. Dropping it kills the command.
+struct Job {
+    child: Child,
+    deadline: Instant,
+    output: Receiver<io::Result<String>>,
+}
+
+impl Job {
+    fn spawn(command: &str, item: &Command, timeout: Duration, wake: EventSender) -> io::Result<Self> {
+        let target = item.path.as_deref().map_or(OsStr::new(&item.name), Path::as_os_str);
+        let script = command.replace("{}", &shell_quote(&target.to_string_lossy()));
+
+        let mut child = process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("exec 2>&1\n{script}"))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            // a group of its own, so that whatever the shell started is killed along with it
+            .process_group(0)
+            .spawn()?;
+
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let (sender, output) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let read = (&mut stdout).take(MAX_BYTES).read_to_end(&mut buffer);
+            let _ = sender.send(read.map(|_| String::from_utf8_lossy(&buffer).into_owned()));
+            // the queue may be full, the result is still picked up on the next event
+            let _ = wake.push_custom_event(Wake);
+        });
+
+        Ok(Self {
+            child,
+            deadline: Instant::now() + timeout,
+            output,
+        })
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        // SAFETY: kill has no memory safety requirements, the negative id names the group
+        unsafe {
+            libc::kill(-(self.child.id() as libc::pid_t), libc::SIGKILL);
+        }
+
+        let _ = self.child.wait();
+    }
+}
+
+fn header(item: &Command) -> Vec<String> {
+    let mut header = vec![item.exec().to_owned()];
+    header.extend(item.path.as_ref().map(|path| path.display().to_string()));
+    header
+}
+
+/// What the item's file looks like: its first lines, the image itself or the entries of
+/// the directory.
+fn builtin(path: &Path) -> Body {
+    let extension = path.extension().and_then(OsStr::to_str).unwrap_or_default();
+
+    if path.is_dir() {
+        match listing(path) {
+            Ok(entries) => Body::Text(entries),
+            Err(e) => Body::Failed(e.to_string()),
+        }
+    } else if IMAGE_EXTENSIONS.contains(&extension) {
+        Body::Image(path.to_owned())
+    } else if extension == "desktop" {
+        // the header already shows everything worth knowing about an application
+        Body::Empty
+    } else {
+        match head(path) {
+            Ok(Some(text)) => Body::Text(lines(&text)),
+            Ok(None) => Body::Failed("binary file".to_owned()),
+            Err(e) => Body::Failed(e.to_string()),
+        }
+    }
+}
+
+fn listing(path: &Path) -> io::Result<Vec<String>> {
+    let mut entries = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let mut name = entry.file_name().to_string_lossy().into_owned();
+
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                name.push('/');
+            }
+
+            name
+        })
+        .collect::<Vec<_>>();
+
+    entries.sort_by_key(|name| name.to_lowercase());
+    entries.truncate(MAX_LINES);
+
+    Ok(entries)
+}
+
+/// The start of a text file, `None` if it looks binary.
+fn head(path: &Path) -> io::Result<Option<String>> {
+    let mut buffer = Vec::new();
+    fs::File::open(path)?.take(MAX_BYTES).read_to_end(&mut buffer)?;
+
+    if buffer.contains(&0) {
+        return Ok(None);
+    }
+
+    Ok(Some(String::from_utf8_lossy(&buffer).into_owned()))
+}
+
+/// Splits into the lines shown, with tabs expanded and other control characters removed.
+fn lines(text: &str) -> Vec<String> {
+    text.lines()
+        .take(MAX_LINES)
+        .map(|line| {
+            line.replace('\t', "    ")
+                .chars()
+                .filter(|c| !c.is_control())
+                .collect()
+        })
+        .collect()
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
@@ -0,0 +1,147 @@
+//! Recently used documents, from the `recently-used.xbel` file GTK applications keep
+//! following the freedesktop Desktop Bookmark specification.
+
+use std::fs;
+use std::path::PathBuf;
+use roxmltree::Node;
+use crate::{desktop, xdg};
+
+/// A document an application opened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub path: PathBuf,
+    pub mime: Option<String>,
+    /// Seconds since the Unix epoch of when it was last used.
+    pub used: i64,
+    /// Name of the application that last used it.
+    pub application: Option<String>,
+}
+
+/// Where the recently used documents are kept.
+pub fn path() -> Option<PathBuf> {
+    xdg::data_home().map(|dir| dir.join("recently-used.xbel"))
+}
+
+/// The documents of the file that still exist, most recently used first.
+pub fn documents() -> Vec<Document> {
+    let Some(text) = path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+
+    let mut documents = parse(&text);
+    documents.retain(|document| document.path.exists());
+    documents
+}
+
+/// The local documents bookmarked in `text`, most recently used first.
+pub fn parse(text: &str) -> Vec<Document> {
+    let xml = match roxmltree::Document::parse(text) {
+        Ok(xml) => xml,
+        Err(e) => {
+            eprintln!("warning: can't read the recently used files: {e}");
+            return Vec::new();
+        }
+    };
+
+    let mut documents = xml
+        .root_element()
+        .children()
+        .filter(|node| node.has_tag_name("bookmark"))
+        .filter_map(document)
+        .collect::<Vec<_>>();
+
+    documents.sort_by_key(|document| std::cmp::Reverse(document.used));
+    documents
+}
+
+fn document(bookmark: Node) -> Option<Document> {
+    let path = desktop::path_from_uri(bookmark.attribute("href")?)?;
+
+    // the latest of the bookmark's own times and those of the applications
+    let applications = descendants(bookmark, "application")
+        .filter_map(|app| Some((timestamp(app.attribute("modified")?)?, app.attribute("name")?)))
+        .collect::<Vec<_>>();
+    let own = ["modified", "visited", "added"].iter().filter_map(|key| timestamp(bookmark.attribute(*key)?)).max();
+    let latest_app = applications.iter().max_by_key(|(used, _)| *used);
+
+    Some(Document {
+        path,
+        mime: descendants(bookmark, "mime-type").find_map(|node| node.attribute("type")).map(str::to_owned),
+        used: own.into_iter().chain(latest_app.map(|(used, _)| *used)).max()?,
+        application: latest_app.map(|(_, name)| (*name).to_owned()),
+    })
+}
+
+/// The elements below `node` named `name`, whatever their namespace.
+fn descendants<'a, 'input>(node: Node<'a, 'input>, name: &'a str) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.descendants().filter(move |node| node.tag_name().name() == name)
+}
+
+/// Seconds since the Unix epoch of an ISO 8601 time like `2024-03-05T14:07:31.512Z`.
+/// Fractions of seconds are dropped and times without a zone taken to be in UTC.
+pub fn timestamp(text: &str) -> Option<i64> {
+    let (date, time) = text.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+
+    let (time, offset) = match time.find(['Z', '+', '-']) {
+        Some(zone) => (&time[..zone], zone_offset(&time[zone..])?),
+        None => (time, 0),
+    };
+    let time = time.split('.').next()?;
+    let mut time = time.splitn(3, ':').map(str::parse::<i64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next().unwrap_or(Ok(0)).ok()?);
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second - offset)
+}
+
+/// Seconds a zone like `Z`, `+02:00` or `-0530` is ahead of UTC.
+fn zone_offset(zone: &str) -> Option<i64> {
+    let (sign, rest) = match zone.split_at(1) {
+        ("Z", "") => return Some(0),
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+
+    let digits = rest.replace(':', "");
+    let hours = digits.get(..2)?.parse::<i64>().ok()?;
+    let minutes = digits.get(2..).filter(|m| !m.is_empty()).map_or(Some(0), |m| m.parse::<i64>().ok())?;
+
+    Some(sign * (hours * 3_600 + minutes * 60))
+}
+
+/// Days from the Unix epoch to a date of the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // years starting in March, so that the leap day comes last
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// How long ago `used` was, at `now`, like `5 minutes ago` or `yesterday`.
+pub fn ago(used: i64, now: i64) -> String {
+    let seconds = (now - used).max(0);
+    let plural = |n: i64, unit: &str| format!("{n} {unit}{} ago", if n == 1 { "" } else { "s" });
+
+    match seconds {
+        0..60 => "just now".to_owned(),
+        60..3_600 => plural(seconds / 60, "minute"),
+        3_600..86_400 => plural(seconds / 3_600, "hour"),
+        86_400..172_800 => "yesterday".to_owned(),
+        172_800..2_592_000 => plural(seconds / 86_400, "day"),
+        2_592_000..31_536_000 => plural(seconds / 2_592_000, "month"),
+        _ => plural(seconds / 31_536_000, "year"),
+    }
+}
+
+#[cfg(test)]
+mod tests;
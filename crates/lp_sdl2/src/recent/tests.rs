@@ -0,0 +1,110 @@
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use super::{ago, parse, timestamp, Document};
+
+const XBEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info">
+  <bookmark href="file:///home/user/Documents/report%20final.odt" added="2024-03-01T10:00:00Z" modified="2024-03-01T10:00:00Z" visited="2024-03-01T10:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="application/vnd.oasis.opendocument.text"/>
+        <bookmark:applications>
+          <bookmark:application name="LibreOffice" exec="&apos;soffice %u&apos;" modified="2024-03-04T08:00:00Z" count="3"/>
+          <bookmark:application name="Document Viewer" exec="&apos;evince %u&apos;" modified="2024-03-02T08:00:00Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/user/Tom%20&amp;%20Jerry.txt" added="2024-03-05T09:00:00+01:00" modified="2024-03-05T09:00:00+01:00" visited="2024-03-05T09:00:00+01:00">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:applications>
+          <bookmark:application name="Text &amp; Code" exec="&apos;gedit %u&apos;" modified="2024-03-05T09:00:00+01:00" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/user/caf%E9.txt" added="2024-02-01T00:00:00Z" modified="2024-02-01T00:00:00Z" visited="2024-03-03T00:00:00Z"/>
+  <bookmark href="file://localhost/home/user/todo.md" added="2024-01-01T00:00:00Z" modified="2024-01-02T00:00:00Z" visited="2024-01-01T00:00:00Z"/>
+  <bookmark href="file://elsewhere.example.com/home/user/remote.txt" added="2024-03-06T00:00:00Z" modified="2024-03-06T00:00:00Z" visited="2024-03-06T00:00:00Z"/>
+  <bookmark href="https://example.com/" added="2024-03-06T00:00:00Z" modified="2024-03-06T00:00:00Z" visited="2024-03-06T00:00:00Z"/>
+  <bookmark href="file:///home/user/undated.txt"/>
+</xbel>
+"#;
+
+#[test]
+fn reads_local_documents_most_recent_first() {
+    let documents = parse(XBEL);
+    let paths = documents.iter().map(|document| document.path.clone()).collect::<Vec<_>>();
+
+    assert_eq!(
+        paths,
+        [
+            PathBuf::from("/home/user/Tom & Jerry.txt"),
+            PathBuf::from("/home/user/Documents/report final.odt"),
+            PathBuf::from(OsStr::from_bytes(b"/home/user/caf\xe9.txt")),
+            PathBuf::from("/home/user/todo.md"),
+        ],
+    );
+}
+
+#[test]
+fn takes_the_latest_use_and_its_application() {
+    let documents = parse(XBEL);
+
+    assert_eq!(
+        documents[1],
+        Document {
+            path: PathBuf::from("/home/user/Documents/report final.odt"),
+            mime: Some("application/vnd.oasis.opendocument.text".to_owned()),
+            used: timestamp("2024-03-04T08:00:00Z").unwrap(),
+            application: Some("LibreOffice".to_owned()),
+        },
+    );
+
+    // entities are decoded in attributes
+    assert_eq!(documents[0].application.as_deref(), Some("Text & Code"));
+    assert_eq!(documents[0].used, timestamp("2024-03-05T08:00:00Z").unwrap());
+
+    // without applications, a visit counts as much as a change
+    assert_eq!(documents[2].used, timestamp("2024-03-03T00:00:00Z").unwrap());
+    assert_eq!(documents[2].application, None);
+    assert_eq!(documents[3].used, timestamp("2024-01-02T00:00:00Z").unwrap());
+}
+
+#[test]
+fn reads_nothing_of_a_broken_file() {
+    assert!(parse("<xbel><bookmark href=").is_empty());
+    assert!(parse("").is_empty());
+}
+
+#[test]
+fn converts_times() {
+    assert_eq!(timestamp("1970-01-01T00:00:00Z"), Some(0));
+    assert_eq!(timestamp("2024-03-05T14:07:31.512Z"), Some(1_709_647_651));
+    assert_eq!(timestamp("2024-03-05T16:07:31+02:00"), Some(1_709_647_651));
+    assert_eq!(timestamp("2024-03-05T08:37:31-0530"), Some(1_709_647_651));
+    assert_eq!(timestamp("2024-03-05T14:07:31"), Some(1_709_647_651));
+    assert_eq!(timestamp("2024-02-29T00:00:00Z"), Some(1_709_164_800));
+    assert_eq!(timestamp("2024-13-01T00:00:00Z"), None);
+    assert_eq!(timestamp("yesterday"), None);
+}
+
+#[test]
+fn tells_how_long_ago() {
+    let now = 1_709_647_651;
+
+    assert_eq!(ago(now - 5, now), "just now");
+    assert_eq!(ago(now + 60, now), "just now");
+    assert_eq!(ago(now - 60, now), "1 minute ago");
+    assert_eq!(ago(now - 300, now), "5 minutes ago");
+    assert_eq!(ago(now - 7_200, now), "2 hours ago");
+    assert_eq!(ago(now - 90_000, now), "yesterday");
+    assert_eq!(ago(now - 3 * 86_400, now), "3 days ago");
+    assert_eq!(ago(now - 65 * 86_400, now), "2 months ago");
+    assert_eq!(ago(now - 400 * 86_400, now), "1 year ago");
+}
@@ -0,0 +1,173 @@
+//! Golden image tests of whole frames, rendered offscreen.
+
+use sdl2::keyboard::Keycode;
+use config::{Command, Config, Palette};
+use harness::Harness;
+use crate::Options;
+
+fn command(name: &str, description: &str) -> Command {
+    Command {
+        name: name.to_owned(),
+        description: description.to_owned(),
+        exec: None,
+        icon: None,
+        path: None,
+    }
+}
+
+/// A small window with a few commands, without anything that depends on the system like
+/// icon themes or the installed fonts.
+fn config() -> Config {
+    let mut config = Config::default();
+    config.font.family = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fonts/DejaVuSans.ttf").to_owned();
+    config.font.fallback = Vec::new();
+    config.window.width = 480.into();
+    config.window.height = 320.into();
+    config.commands = vec![
+        command("firefox", "Web browser"),
+        command("files", "Browse the file system"),
+        command("terminal", ""),
+        command("settings", "Change the system settings"),
+    ];
+    config
+}
+
+#[test]
+fn initial_frame() {
+    Harness::run(config(), |harness| {
+        harness.settle().assert_snapshot("initial_frame");
+    });
+}
+
+#[test]
+fn typed_query() {
+    Harness::run(config(), |harness| {
+        harness.type_text("fi").settle().assert_snapshot("typed_query");
+    });
+}
+
+#[test]
+fn initial_query() {
+    let options = Options {
+        query: "set".to_owned(),
+        ..Options::default()
+    };
+
+    Harness::run_with_options(config(), options, |harness| {
+        harness.settle().assert_snapshot("initial_query");
+    });
+}
+
+#[test]
+fn moved_selection() {
+    Harness::run(config(), |harness| {
+        harness
+            .settle()
+            .key(Keycode::Down)
+            .key(Keycode::Down)
+            .settle()
+            .assert_snapshot("moved_selection");
+    });
+}
+
+#[test]
+fn clicked_row_and_caret() {
+    Harness::run(config(), |harness| {
+        harness
+            .type_text("fi")
+            .settle()
+            // the second row, then within the query
+            .click(240, 64 + 52 + 26)
+            .click(60, 32)
+            .settle()
+            .assert_snapshot("clicked_row_and_caret");
+    });
+}
+
+#[test]
+fn scrolled_list() {
+    let mut config = config();
+    config.commands = (1..=40).map(|n| command(&format!("command {n}"), "")).collect();
+
+    Harness::run(config, |harness| {
+        harness.settle();
+
+        for _ in 0..25 {
+            harness.key(Keycode::Down);
+        }
+
+        harness.settle().assert_snapshot("scrolled_list");
+    });
+}
+
+#[test]
+fn long_text_elided() {
+    let mut config = config();
+    config.commands = vec![
+        command("a launcher entry whose name is much too long to match", "/usr/share/applications/very/deeply/nested/entry.desktop"),
+        command("another entry with a long name ending in the match", "A description that goes on for longer than the row"),
+    ];
+
+    Harness::run(config, |harness| {
+        harness.type_text("match").settle().assert_snapshot("long_text_elided");
+    });
+}
+
+#[test]
+fn mode_tabs() {
+    let mut config = config();
+    config.modes = vec!["commands".to_owned(), "run".to_owned()];
+
+    Harness::run(config, |harness| {
+        harness.type_text("fi").settle().assert_snapshot("mode_tabs");
+    });
+}
+
+#[test]
+fn combined_mode() {
+    let mut config = config();
+    config.mode = "combi".to_owned();
+    config.combi.modes = vec!["commands".to_owned()];
+    config.combi.max_per_mode = 2;
+
+    // the header row is passed over
+    Harness::run(config, |harness| {
+        harness.type_text("s").settle().assert_snapshot("combined_mode");
+    });
+}
+
+#[test]
+fn highlight_mid_animation() {
+    Harness::run(config(), |harness| {
+        harness
+            .settle()
+            .key(Keycode::Down)
+            .frames(4)
+            .assert_snapshot("highlight_mid_animation");
+    });
+}
+
+#[test]
+fn compact_light_theme() {
+    let mut config = config();
+    config.theme = "light".to_owned();
+    config.colors = Palette::LIGHT;
+    config.layout.compact = true;
+
+    Harness::run(config, |harness| {
+        harness.settle().assert_snapshot("compact_light_theme");
+    });
+}
+
+#[test]
+fn preview_command() {
+    let mut config = config();
+    config.preview.enabled = true;
+    config.preview.command = Some("echo previewing {}".to_owned());
+
+    Harness::run(config, |harness| {
+        harness.settle().assert_snapshot("preview_command");
+    });
+}
+
+mod harness;
@@ -0,0 +1,241 @@
+//! Drives the app offscreen with scripted input and compares its frames against golden
+//! images.
+//!
+//! Set `LP_UPDATE_GOLDEN=1` to write the rendered frames as the new golden images instead
+//! of comparing against them.
+
+use std::env;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+use config::Config;
+use crate::icon::decode::{self, Image};
+use crate::layout::Scale;
+use crate::{error, App, Options, Result, Screen};
+
+/// Every frame advances the clock by exactly this much, so that animations end up in the
+/// same state on every run.
+const FRAME: Duration = Duration::from_micros(16_667);
+/// Frames rendered at most while waiting for the animations to settle.
+const MAX_FRAMES: usize = 600;
+/// Channel difference below which pixels count as equal, font rasterization varies
+/// slightly between FreeType versions.
+const CHANNEL_TOLERANCE: u8 = 16;
+/// Fraction of the pixels that may differ by more than the channel tolerance.
+const PIXEL_TOLERANCE: f64 = 0.002;
+const UPDATE_VAR: &str = "LP_UPDATE_GOLDEN";
+
+/// SDL can only be initialized once at a time, so the tests take turns.
+static SDL: Mutex<()> = Mutex::new(());
+
+impl Screen<Surface<'static>> {
+    /// A surface the size of the configured window.
+    pub fn headless(config: &Config) -> Result<Self> {
+        // the event subsystem needs a video driver, even though nothing is shown
+        sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+
+        let context = sdl2::init().map_err(error::system_core)?;
+        // the environment of whoever runs the tests shouldn't change the frames
+        let scale = config.window.scale.map_or(Scale::ONE, Scale::new);
+
+        // percentages are of a display as large as the default window
+        let defaults = config::Window::default();
+        let (width, height) = (
+            config.window.width.resolve(defaults.width.resolve(0, 1.), 1.),
+            config.window.height.resolve(defaults.height.resolve(0, 1.), 1.),
+        );
+
+        let canvas = Surface::new(width, height, PixelFormatEnum::RGBA32)
+            .and_then(Surface::into_canvas)
+            .map_err(error::draw)?;
+
+        Ok(Self { context, canvas, vsync: false, scale })
+    }
+}
+
+pub struct Harness<'a> {
+    app: App<'a, Surface<'static>>,
+}
+
+impl Harness<'_> {
+    /// Runs `test` on the app drawing offscreen with `config`.
+    pub fn run(config: Config, test: impl FnOnce(&mut Harness<'_>)) {
+        Harness::run_with_options(config, Options::default(), test);
+    }
+
+    pub fn run_with_options(config: Config, options: Options, test: impl FnOnce(&mut Harness<'_>)) {
+        // a failed test poisons the lock, but leaves nothing behind that the others see
+        let _sdl = SDL.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let screen = Screen::headless(&config).expect("headless screen");
+        let creator = screen.texture_creator();
+        let app = App::with_screen(screen, &creator, config, options).expect("headless app");
+
+        test(&mut Harness { app });
+    }
+
+    pub fn key(&mut self, keycode: Keycode) -> &mut Self {
+        let event = Event::KeyDown {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod: Mod::NOMOD,
+            repeat: false,
+        };
+
+        self.app.handle_event(event).expect("event handled");
+        self
+    }
+
+    /// Clicks the left button once at `(x, y)`.
+    pub fn click(&mut self, x: i32, y: i32) -> &mut Self {
+        let event = Event::MouseButtonDown {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mouse_btn: MouseButton::Left,
+            clicks: 1,
+            x,
+            y,
+        };
+
+        self.app.handle_event(event).expect("event handled");
+        self
+    }
+
+    /// Types `text` in, as the keyboard layout would deliver it.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        let event = Event::TextInput {
+            timestamp: 0,
+            window_id: 0,
+            text: text.to_owned(),
+        };
+
+        self.app.handle_event(event).expect("event handled");
+        self
+    }
+
+    /// Renders `count` frames, one [`FRAME`] apart.
+    pub fn frames(&mut self, count: usize) -> &mut Self {
+        for _ in 0..count {
+            self.app.update_modes();
+            self.app.update_preview();
+            self.app.draw_frame(FRAME).expect("frame rendered");
+        }
+
+        self
+    }
+
+    /// Renders frames until the animations are over and the preview command finished.
+    pub fn settle(&mut self) -> &mut Self {
+        self.frames(1);
+
+        for _ in 0..MAX_FRAMES {
+            if !self.app.resources.is_animating() && self.app.preview.time_left().is_none() {
+                return self;
+            }
+
+            if self.app.preview.time_left().is_some() {
+                thread::sleep(Duration::from_millis(1));
+            }
+
+            self.frames(1);
+        }
+
+        panic!("still animating after {MAX_FRAMES} frames");
+    }
+
+    /// Compares the last frame against `golden/<name>.png`.
+    pub fn assert_snapshot(&mut self, name: &str) {
+        let actual = self.frame();
+        let golden = golden_path(name);
+
+        if env::var_os(UPDATE_VAR).is_some_and(|v| v != "0") {
+            write_png(&golden, &actual);
+            return;
+        }
+
+        let expected = match decode::load(&golden, 0) {
+            Ok(image) => image,
+            Err(e) => {
+                let path = golden.with_extension("actual.png");
+                write_png(&path, &actual);
+                panic!("can't read {}: {e}, run with {UPDATE_VAR}=1 to create it", golden.display());
+            }
+        };
+
+        if let Err(difference) = compare(&actual, &expected) {
+            let path = golden.with_extension("actual.png");
+            write_png(&path, &actual);
+            panic!("'{name}' doesn't match its golden image: {difference}, the frame is in {}", path.display());
+        }
+    }
+
+    /// The pixels of the last frame.
+    fn frame(&mut self) -> Image {
+        let mut target = self.app.canvas.borrow_mut();
+        let surface = target.canvas().surface();
+        let (width, height, pitch) = (surface.width(), surface.height(), surface.pitch() as usize);
+
+        let pixels = surface.with_lock(|pixels| {
+            pixels
+                .chunks(pitch)
+                .take(height as usize)
+                .flat_map(|row| &row[..width as usize * 4])
+                .copied()
+                .collect()
+        });
+
+        Image { width, height, pixels }
+    }
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("golden").join(format!("{name}.png"))
+}
+
+fn compare(actual: &Image, expected: &Image) -> std::result::Result<(), String> {
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        return Err(format!(
+            "the size is {}x{} instead of {}x{}",
+            actual.width, actual.height, expected.width, expected.height,
+        ));
+    }
+
+    let differing = actual.pixels
+        .chunks_exact(4)
+        .zip(expected.pixels.chunks_exact(4))
+        .filter(|(a, b)| a.iter().zip(b.iter()).any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE))
+        .count();
+
+    let total = (actual.width * actual.height).max(1) as usize;
+
+    if differing as f64 / total as f64 > PIXEL_TOLERANCE {
+        return Err(format!("{differing} of {total} pixels differ"));
+    }
+
+    Ok(())
+}
+
+fn write_png(path: &Path, image: &Image) {
+    fs::create_dir_all(path.parent().expect("golden directory")).expect("golden directory created");
+
+    let file = File::create(path).expect("image file created");
+    let mut encoder = png::Encoder::new(BufWriter::new(file), image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&image.pixels))
+        .expect("image written");
+}
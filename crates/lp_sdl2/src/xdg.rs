@@ -0,0 +1,54 @@
+//! Base directories of the freedesktop Base Directory specification.
+
+use std::env;
+use std::path::PathBuf;
+
+pub fn home() -> Option<PathBuf> {
+    env::var_os("HOME").filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// `$XDG_DATA_HOME`, by default `~/.local/share`.
+pub fn data_home() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home().map(|home| home.join(".local/share")))
+}
+
+/// `$XDG_DATA_DIRS`, by default `/usr/local/share` and `/usr/share`.
+pub fn data_dirs() -> Vec<PathBuf> {
+    let dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_owned());
+
+    env::split_paths(&dirs).collect()
+}
+
+/// The data home followed by the data directories, most important first.
+pub fn data_paths() -> Vec<PathBuf> {
+    data_home().into_iter().chain(data_dirs()).collect()
+}
+
+/// `$XDG_CONFIG_HOME`, by default `~/.config`.
+pub fn config_home() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home().map(|home| home.join(".config")))
+}
+
+/// `$XDG_CONFIG_DIRS`, by default `/etc/xdg`.
+pub fn config_dirs() -> Vec<PathBuf> {
+    let dirs = env::var("XDG_CONFIG_DIRS").ok().filter(|v| !v.is_empty()).unwrap_or_else(|| "/etc/xdg".to_owned());
+
+    env::split_paths(&dirs).collect()
+}
+
+/// `$XDG_CACHE_HOME`, by default `~/.cache`.
+pub fn cache_home() -> Option<PathBuf> {
+    env::var_os("XDG_CACHE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home().map(|home| home.join(".cache")))
+}